@@ -0,0 +1,484 @@
+use crate::gadgets::state::{AppState, CommandLine, CommandLineKind};
+use std::collections::HashMap;
+use std::time::Duration;
+use user_config::action::{KeyboardAction, KeyboardMapping, TrackPosition, ACTION_NAMES};
+use user_config::help::help_sections;
+use user_config::Config;
+
+/// What a line typed after `:` asks for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Action(KeyboardAction),
+    /// Switch to theme of this name
+    Theme(String),
+    /// Switch to layout of this name
+    Layout(String),
+}
+
+/// Commands other than action names
+pub const COMMANDS: &[&str] = &["volume", "seek", "theme", "layout", "queue", "quit"];
+
+const QUEUE_COMMANDS: &[&str] = &["clear", "shuffle", "repeat", "next", "previous"];
+const DIRECTIONS: &[&str] = &[
+    "Up", "Down", "Left", "Right", "PageUp", "PageDown", "First", "Last",
+];
+const PANES: &[&str] = &["Music", "Playlist", "Artist"];
+
+/// Name of theme or layout that is the one written in config itself
+pub const DEFAULT_PRESET: &str = "default";
+
+/// Parse a command line such as `volume 40`, `seek 1:30` or `queue clear`
+///
+/// Any action can also be given by it's name, with argument
+/// either seperated by space or in config syntax: `SetVolume 40`, `SetVolume(40)`
+pub fn parse_command(input: &str) -> Result<Command, String> {
+    let input = input.trim();
+    let (name, argument) = match input.split_once(char::is_whitespace) {
+        Some((name, argument)) => (name, Some(argument.trim())),
+        None => (input, None),
+    };
+
+    let action = match (name, argument) {
+        ("", _) => return Err("Nothing to run".to_string()),
+        ("theme", Some(theme)) => return Ok(Command::Theme(theme.to_string())),
+        ("layout", Some(layout)) => return Ok(Command::Layout(layout.to_string())),
+        ("volume", Some(volume)) => {
+            if let Some(by) = volume.strip_prefix('+') {
+                format!("VolumeUp({by})").parse()?
+            } else if let Some(by) = volume.strip_prefix('-') {
+                format!("VolumeDown({by})").parse()?
+            } else {
+                format!("SetVolume({volume})").parse()?
+            }
+        }
+        ("seek", Some(position)) => {
+            if let Some(by) = position.strip_prefix('+') {
+                format!("SeekForward({by})").parse()?
+            } else if let Some(by) = position.strip_prefix('-') {
+                format!("SeekBackward({by})").parse()?
+            } else {
+                match parse_clock(position) {
+                    Some(to) => KeyboardAction::Seek(TrackPosition::Time(to)),
+                    None => format!("Seek({position})").parse()?,
+                }
+            }
+        }
+        ("queue", Some("clear")) => KeyboardAction::ClearQueue,
+        ("queue", Some("shuffle")) => KeyboardAction::ShuffleToggle,
+        ("queue", Some("repeat")) => KeyboardAction::RepeatSwitch,
+        ("queue", Some("next")) => KeyboardAction::NextTrack,
+        ("queue", Some("previous")) => KeyboardAction::PreviousTrack,
+        ("queue", Some(other)) => return Err(format!("Unknown queue command: {other}")),
+        ("quit" | "q", None) => KeyboardAction::Quit,
+        ("volume" | "seek" | "theme" | "layout" | "queue", None) => {
+            return Err(format!("Command {name} needs an argument"))
+        }
+        (name, None) => name.parse()?,
+        (name, Some(argument)) => format!("{name}({argument})").parse()?,
+    };
+    Ok(Command::Action(action))
+}
+
+/// Parse time written as on clock: `1:30` or `1:02:03`
+fn parse_clock(clock: &str) -> Option<Duration> {
+    if !clock.contains(':') {
+        return None;
+    }
+    let parts = clock
+        .split(':')
+        .map(|part| part.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let secs = match parts.as_slice() {
+        [minutes, seconds] if *seconds < 60 => minutes.checked_mul(60)?.checked_add(*seconds)?,
+        [hours, minutes, seconds] if *minutes < 60 && *seconds < 60 => hours
+            .checked_mul(3600)?
+            .checked_add(minutes * 60 + seconds)?,
+        _ => return None,
+    };
+    Some(Duration::from_secs(secs))
+}
+
+/// Things that may complete the last word of `input`
+/// along with the position that last word starts at
+pub fn completions(input: &str, config: &Config) -> (usize, Vec<String>) {
+    let start = input.rfind(' ').map_or(0, |space| space + 1);
+    let typed = input[start..].to_lowercase();
+
+    let preset_names = |presets: Vec<&String>| {
+        let mut names = presets.into_iter().cloned().collect::<Vec<_>>();
+        names.sort();
+        std::iter::once(DEFAULT_PRESET.to_string())
+            .chain(names)
+            .collect::<Vec<_>>()
+    };
+    let owned = |names: &[&str]| {
+        names
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>()
+    };
+    let candidates: Vec<String> = match input.split_whitespace().next() {
+        _ if start == 0 => owned(COMMANDS)
+            .into_iter()
+            .chain(owned(ACTION_NAMES))
+            .collect(),
+        Some("theme") => preset_names(config.themes.keys().collect()),
+        Some("layout") => preset_names(config.layouts.keys().collect()),
+        Some("queue") => owned(QUEUE_COMMANDS),
+        Some("GotoPane") => owned(PANES),
+        Some(name) if name.starts_with("MoveIn") => owned(DIRECTIONS),
+        _ => Vec::new(),
+    };
+
+    let matching = candidates
+        .into_iter()
+        .filter(|candidate| candidate.to_lowercase().starts_with(&typed))
+        .collect();
+    (start, matching)
+}
+
+/// Longest start that all of these have in common
+fn common_prefix(candidates: &[String]) -> &str {
+    let Some((first, rest)) = candidates.split_first() else {
+        return "";
+    };
+    let mut prefix = first.as_str();
+    for candidate in rest {
+        while !candidate.starts_with(prefix) {
+            let mut chars = prefix.chars();
+            chars.next_back();
+            prefix = chars.as_str();
+        }
+    }
+    prefix
+}
+
+/// Every action as (action, it's bindings) that matches `filter`
+/// best match first. Actions without any binding are listed by name
+pub fn palette_entries(filter: &str, keyboard: &KeyboardMapping) -> Vec<(String, String)> {
+    let mut bound_by_name = HashMap::<String, Vec<KeyboardAction>>::new();
+    for action in keyboard.bound_actions() {
+        bound_by_name
+            .entry(name_of(action))
+            .or_default()
+            .push(action);
+    }
+
+    let mut entries = Vec::new();
+    for name in ACTION_NAMES {
+        match bound_by_name.get_mut(*name) {
+            Some(actions) => {
+                actions.sort_by_key(|action| action.to_string());
+                for action in actions {
+                    let bindings = keyboard.bindings_of(*action).join(", ");
+                    entries.push((action.to_string(), bindings));
+                }
+            }
+            None => entries.push((name.to_string(), String::new())),
+        }
+    }
+
+    let mut scored = entries
+        .into_iter()
+        .filter_map(|entry| Some((fuzzy_score(filter, &entry.0)?, entry)))
+        .collect::<Vec<_>>();
+    // stable sort keeps listing order among equal scores
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().map(|(_, entry)| entry).collect()
+}
+
+fn name_of(action: KeyboardAction) -> String {
+    let action = action.to_string();
+    match action.split_once('(') {
+        Some((name, _)) => name.to_string(),
+        None => action,
+    }
+}
+
+/// How well `text` matches `pattern`, None if it does not match at all
+///
+/// Every character of pattern must be found in text in the same order,
+/// ignoring case. Characters found right after one another,
+/// or at start of a word, score higher
+fn fuzzy_score(pattern: &str, text: &str) -> Option<i64> {
+    let mut score = 0;
+    let mut text_chars = text.chars().enumerate();
+    let mut previous_char = None;
+    let mut last_match = None;
+
+    for wanted in pattern.chars().filter(|c| !c.is_whitespace()) {
+        loop {
+            let (position, found) = text_chars.next()?;
+            let before = previous_char.replace(found);
+            if !found.to_lowercase().eq(wanted.to_lowercase()) {
+                continue;
+            }
+
+            score += 1;
+            if position > 0 && last_match == Some(position - 1) {
+                score += 5;
+            }
+            let is_word_start = match before {
+                None => true,
+                Some(before) => {
+                    (found.is_uppercase() && !before.is_uppercase()) || !before.is_alphanumeric()
+                }
+            };
+            if is_word_start {
+                score += 10;
+            }
+            last_match = Some(position);
+            break;
+        }
+    }
+    Some(score)
+}
+
+/// Recompute what is shown below command line after it's input changed
+pub fn refresh_choices(command_line: &mut CommandLine, config: &Config) {
+    match command_line.kind {
+        CommandLineKind::Prompt => command_line.choices.clear(),
+        CommandLineKind::Palette => {
+            command_line.choices = palette_entries(&command_line.input, &config.keyboard);
+        }
+        // Title of each section is listed without hint before it's bindings
+        CommandLineKind::Help => {
+            command_line.choices = help_sections(&config.keyboard, &command_line.input)
+                .into_iter()
+                .flat_map(|section| {
+                    let bindings = section
+                        .bindings
+                        .into_iter()
+                        .map(|(keys, action)| (keys, action.to_string()));
+                    std::iter::once((section.title, String::new())).chain(bindings)
+                })
+                .collect();
+        }
+    }
+    command_line.selected = match command_line.kind {
+        CommandLineKind::Palette if !command_line.choices.is_empty() => Some(0),
+        _ => None,
+    };
+}
+
+/// Complete last word typed in prompt as far as it can be
+/// Everything it could be completed into is listed as choices
+pub fn complete(command_line: &mut CommandLine, config: &Config) {
+    if command_line.kind != CommandLineKind::Prompt {
+        return;
+    }
+    let (start, candidates) = completions(&command_line.input, config);
+    match candidates.as_slice() {
+        [] => (),
+        [only] => {
+            command_line.input = format!("{}{only} ", &command_line.input[..start]);
+            command_line.choices.clear();
+            command_line.selected = None;
+        }
+        _ => {
+            let prefix = common_prefix(&candidates);
+            if prefix.len() > command_line.input.len() - start {
+                command_line.input = format!("{}{prefix}", &command_line.input[..start]);
+            }
+            command_line.choices = candidates
+                .into_iter()
+                .map(|candidate| (candidate, String::new()))
+                .collect();
+            command_line.selected = None;
+        }
+    }
+}
+
+/// Put selected completion in place of word being typed
+pub fn apply_selected_completion(command_line: &mut CommandLine) {
+    if command_line.kind != CommandLineKind::Prompt {
+        return;
+    }
+    let Some((choice, _)) = command_line
+        .selected
+        .and_then(|selected| command_line.choices.get(selected))
+    else {
+        return;
+    };
+    let start = command_line.input.rfind(' ').map_or(0, |space| space + 1);
+    command_line.input = format!("{}{choice}", &command_line.input[..start]);
+}
+
+/// Close command line and return what it asked for
+///
+/// Palette entry of an action that needs argument is not run,
+/// prompt is opened with it's name instead so that argument can be typed
+pub fn take_command(appstate: &mut AppState) -> Result<Option<Command>, String> {
+    let Some(command_line) = appstate.close_command_line() else {
+        return Ok(None);
+    };
+
+    let input = match command_line.kind {
+        CommandLineKind::Prompt => command_line.input,
+        // Nothing to run, help is only looked at
+        CommandLineKind::Help => return Ok(None),
+        CommandLineKind::Palette => {
+            let Some((entry, _)) = command_line
+                .selected
+                .and_then(|selected| command_line.choices.into_iter().nth(selected))
+            else {
+                return Ok(None);
+            };
+            if KeyboardAction::needs_argument(&entry) {
+                appstate.open_command_line(CommandLine {
+                    input: format!("{entry} "),
+                    ..CommandLine::new(CommandLineKind::Prompt)
+                });
+                return Ok(None);
+            }
+            entry
+        }
+    };
+    parse_command(&input).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use user_config::action::MoveDirection;
+
+    #[test]
+    fn commands_into_actions() {
+        let action = |action| Ok(Command::Action(action));
+        let commands = [
+            ("volume 40", action(KeyboardAction::SetVolume(40))),
+            ("volume +10", action(KeyboardAction::VolumeUp(10))),
+            ("volume -5%", action(KeyboardAction::VolumeDown(5))),
+            (
+                "seek 1:30",
+                action(KeyboardAction::Seek(TrackPosition::Time(
+                    Duration::from_secs(90),
+                ))),
+            ),
+            (
+                "seek 25%",
+                action(KeyboardAction::Seek(TrackPosition::Percent(25))),
+            ),
+            (
+                "seek -10s",
+                action(KeyboardAction::SeekBackward(Duration::from_secs(10))),
+            ),
+            ("theme dark", Ok(Command::Theme("dark".to_string()))),
+            ("  layout mini ", Ok(Command::Layout("mini".to_string()))),
+            ("queue clear", action(KeyboardAction::ClearQueue)),
+            ("q", action(KeyboardAction::Quit)),
+            ("NextTrack", action(KeyboardAction::NextTrack)),
+            (
+                "MoveInPaneWindow Down",
+                action(KeyboardAction::MoveInPaneWindow(MoveDirection::Down)),
+            ),
+            (
+                "SeekForward(10s)",
+                action(KeyboardAction::SeekForward(Duration::from_secs(10))),
+            ),
+        ];
+        for (command, expected) in commands {
+            assert_eq!(expected, parse_command(command), "{command}");
+        }
+
+        for invalid in [
+            "",
+            "volume",
+            "volume 200",
+            "seek 1:75",
+            "queue dance",
+            "Fly",
+            "volume é",
+            "volume +é",
+            "seek ½",
+            "seek -½",
+            "seek 999999999999999999:00",
+        ] {
+            assert!(parse_command(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn completing_commands() {
+        let config = user_config::default_config();
+        let complete_input = |input: &str| {
+            let mut command_line = CommandLine {
+                input: input.to_string(),
+                ..CommandLine::new(CommandLineKind::Prompt)
+            };
+            complete(&mut command_line, &config);
+            let choices = command_line
+                .choices
+                .into_iter()
+                .map(|(choice, _)| choice)
+                .collect::<Vec<_>>();
+            (command_line.input, choices)
+        };
+
+        assert_eq!(("theme ".to_string(), vec![]), complete_input("th"));
+        assert_eq!(
+            (
+                "theme d".to_string(),
+                vec!["default".to_string(), "dark".to_string()]
+            ),
+            complete_input("theme ")
+        );
+        assert_eq!(
+            ("theme dark ".to_string(), vec![]),
+            complete_input("theme da")
+        );
+        assert_eq!(
+            ("queue clear ".to_string(), vec![]),
+            complete_input("queue c")
+        );
+        assert_eq!(
+            (
+                "MoveInPaneWindow Page".to_string(),
+                vec!["PageUp".to_string(), "PageDown".to_string()]
+            ),
+            complete_input("MoveInPaneWindow p")
+        );
+        // common part of every candidate is filled in
+        assert_eq!("MoveIn", complete_input("movei").0);
+    }
+
+    #[test]
+    fn help_is_grouped_by_window() {
+        let config = user_config::default_config();
+        let mut help = CommandLine {
+            input: "gauge".to_string(),
+            ..CommandLine::new(CommandLineKind::Help)
+        };
+        refresh_choices(&mut help, &config);
+        assert_eq!(
+            vec![
+                ("Gauge".to_string(), String::new()),
+                ("<left>".to_string(), "SeekBackward(5s)".to_string()),
+                ("<right>".to_string(), "SeekForward(5s)".to_string()),
+            ],
+            help.choices
+        );
+
+        let mut appstate = AppState::default();
+        appstate.open_command_line(help);
+        assert_eq!(Ok(None), take_command(&mut appstate));
+        assert!(appstate.popups.is_empty());
+    }
+
+    #[test]
+    fn palette_lists_bindings() {
+        let keyboard = user_config::default_config().keyboard;
+
+        let everything = palette_entries("", &keyboard);
+        assert!(everything.contains(&("Quit".to_string(), "q".to_string())));
+        assert!(everything.contains(&("SetVolume".to_string(), String::new())));
+        assert!(everything.contains(&(
+            "MoveInPaneWindow(First)".to_string(),
+            "<home>|PaneWindow, gg|PaneWindow".to_string()
+        )));
+
+        let found = palette_entries("nxtr", &keyboard);
+        assert_eq!("NextTrack", found[0].0);
+        assert!(palette_entries("zzz", &keyboard).is_empty());
+    }
+}
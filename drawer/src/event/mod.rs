@@ -1,185 +1,1273 @@
-use crate::gadgets;
 use crate::gadgets::state::AppState;
+use crate::gadgets::state::PendingKeys;
+use crate::gadgets::state::PlayerState;
+use crate::gadgets::state::Viewport;
+use crate::gadgets::state::{CommandLine, CommandLineKind, Details, Popup, PopupLayer};
 use crate::gadgets::window::PaneWindow;
+use crate::init::config::{get_config, ConfigSources};
+use crate::player::{Player, PlayerEvent, Seek};
+use crate::search::{SearchRequest, SearchResponse};
+use crate::types::query::{Query, ResultKind, SearchQuery};
+use crate::types::queue::{Advance, Enqueue};
+use crate::types::suggestion::Suggestions;
+use crate::types::unit::MusicUnit;
 use crate::types::window::Window;
+use std::fs;
+use std::sync::mpsc::{Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+use tui::widgets::TableState;
 use user_config::action::KeyboardAction;
 use user_config::action::KeyboardMapping;
+use user_config::action::Motion;
 use user_config::action::MoveDirection;
-use user_config::keyboard::Key;
+use user_config::action::TrackPosition;
+use user_config::keyboard::{Key, KeyCode};
+use user_config::mode::Mode;
+use user_config::preferences::pane::PanePreferences;
+use user_config::preferences::shortcut::{Shortcut, ShortcutAction};
+use user_config::preferences::theme::Theme;
+use user_config::preferences::WidgetPreferences;
+use user_config::reexports::layout_config::ui::UI;
+use user_config::Config;
 
-#[derive(Clone, Copy, Eq, PartialEq)]
-pub enum EventSummary {
-    Execution(KeyboardAction),
+pub mod command;
+
+use command::Command;
+
+/// Everything that can change AppState
+/// Each source sends these from it's own thread
+/// and all of them are applied one by one in `reduce`
+pub enum Message {
+    /// Key pressed in terminal
+    Key(Key),
+    /// Terminal got resized
     Resize,
-    Nothing,
-    Ignored,
+    /// Something changed in player
+    Player(PlayerEvent),
+    /// Search worker have finished a query
+    Search(SearchResponse),
+    /// Sent periodically by timer
+    Tick,
+    /// Config files have changed and were read again
+    Config(Result<Box<Config>, String>),
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub enum Flow {
+    Continue,
+    Quit,
+    /// Draw with this theme from now on
+    UseTheme(Theme),
+    /// Draw with this layout from now on
+    UseLayout(UI),
 }
 
-pub fn listen_for_event(keyboard: &KeyboardMapping, appstate: &AppState) -> EventSummary {
+/// Handles to things living outside of AppState
+/// that actions are allowed to drive
+pub struct Services<'p> {
+    pub player: &'p mut dyn Player,
+    /// Requests sent here are answered with Message::Search
+    pub searcher: Sender<SearchRequest>,
+}
+
+/// Send every event from terminal as Message
+pub fn spawn_terminal_listener(sender: Sender<Message>) {
     #[cfg(feature = "crossterm")]
-    return crossterm_event::listen_for_event(keyboard, appstate);
+    crossterm_event::spawn_listener(sender);
 
     #[cfg(feature = "termion")]
-    return termion_event::listen_for_event(keyboard);
+    termion_event::spawn_listener(sender);
+}
+
+/// Send Message::Tick on every `interval`
+pub fn spawn_ticker(sender: Sender<Message>, interval: Duration) {
+    thread::spawn(move || {
+        while sender.send(Message::Tick).is_ok() {
+            thread::sleep(interval);
+        }
+    });
+}
+
+/// Send newly built config whenever any config file changes
+/// Changes are looked for on every `interval`
+pub fn spawn_config_watcher(sources: ConfigSources, sender: Sender<Message>, interval: Duration) {
+    let modified_times = |sources: &ConfigSources| {
+        [&sources.system_file, &sources.user_file].map(|path| {
+            path.as_ref()
+                .and_then(|path| fs::metadata(path).and_then(|meta| meta.modified()).ok())
+        })
+    };
+    // Taken right away so that changes made while thread starts are not missed
+    let mut last_seen = modified_times(&sources);
+    thread::spawn(move || loop {
+        thread::sleep(interval);
+        let now = modified_times(&sources);
+        if now == last_seen {
+            continue;
+        }
+        last_seen = now;
+        if sender
+            .send(Message::Config(get_config(&sources).map(Box::new)))
+            .is_err()
+        {
+            break;
+        }
+    });
+}
+
+/// Send everything that arrives in `receiver` as Message
+pub fn forward<T: Send + 'static>(
+    receiver: Receiver<T>,
+    sender: Sender<Message>,
+    into_message: fn(T) -> Message,
+) {
+    thread::spawn(move || {
+        for item in receiver {
+            if sender.send(into_message(item)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Apply single message to appstate
+pub fn reduce(
+    message: Message,
+    appstate: &mut AppState,
+    config: &Config,
+    services: &mut Services,
+) -> Flow {
+    let keyboard = &config.keyboard;
+    match message {
+        Message::Key(key) => {
+            let action = action_for_key(key, keyboard, appstate);
+            return perform(action, appstate, config, services);
+        }
+        Message::Tick => {
+            if let Some(typed) = appstate.suggestions.take_due_fetch(Instant::now()) {
+                if services
+                    .searcher
+                    .send(SearchRequest::Suggestions(typed))
+                    .is_err()
+                {
+                    appstate.notification = Some("Search worker is not running".to_string());
+                }
+            }
+            if let Some(action) = expire_pending_keys(keyboard, appstate, Instant::now()) {
+                return perform(action, appstate, config, services);
+            }
+        }
+        Message::Player(player_event) => {
+            if let Err(e) = handle_player_event(player_event, appstate, services) {
+                appstate.notification = Some(e);
+            }
+        }
+        Message::Search(response) => {
+            if let Err(e) = handle_search_response(response, appstate, config, services) {
+                appstate.notification = Some(e);
+            }
+        }
+        // Caller is expected to recompute layout on resize
+        // otherwise it just need a redraw
+        Message::Resize => (),
+        // Caller owns the config and is expected to swap it
+        Message::Config(_) => (),
+    }
+
+    Flow::Continue
+}
+
+fn perform(
+    action: KeyboardAction,
+    appstate: &mut AppState,
+    config: &Config,
+    services: &mut Services,
+) -> Flow {
+    let action = match action {
+        KeyboardAction::ExecuteCommandLine => match command::take_command(appstate) {
+            Ok(Some(Command::Action(action))) => action,
+            Ok(Some(Command::Theme(name))) => return use_theme(&name, appstate, config),
+            Ok(Some(Command::Layout(name))) => return use_layout(&name, appstate, config),
+            Ok(None) => return Flow::Continue,
+            Err(e) => {
+                appstate.notification = Some(e);
+                return Flow::Continue;
+            }
+        },
+        action => action,
+    };
+    if action == KeyboardAction::Quit {
+        return Flow::Quit;
+    }
+
+    let count = appstate.count.take();
+    if action == KeyboardAction::Nothing {
+        // Count is still waiting for it's action
+        appstate.count = count;
+    }
+    let times = if action.is_movement() {
+        count.unwrap_or(1)
+    } else {
+        1
+    };
+    appstate.notification = (0..times)
+        .try_for_each(|_| handle_action(action, appstate, config, services))
+        .err();
+    Flow::Continue
+}
+
+/// Flow to switch to named theme, if there is one by that name
+fn use_theme(name: &str, appstate: &mut AppState, config: &Config) -> Flow {
+    match name {
+        command::DEFAULT_PRESET => Flow::UseTheme(config.theme.clone()),
+        name => match config.themes.get(name) {
+            Some(theme) => Flow::UseTheme(theme.clone()),
+            None => {
+                appstate.notification = Some(format!("No theme named {name}"));
+                Flow::Continue
+            }
+        },
+    }
+}
+
+/// Flow to switch to named layout, if there is one by that name
+fn use_layout(name: &str, appstate: &mut AppState, config: &Config) -> Flow {
+    match name {
+        command::DEFAULT_PRESET => Flow::UseLayout(config.layout.clone()),
+        name => match config.layouts.get(name) {
+            Some(layout) => Flow::UseLayout(layout.clone()),
+            None => {
+                appstate.notification = Some(format!("No layout named {name}"));
+                Flow::Continue
+            }
+        },
+    }
+}
+
+/// Largest count that can be typed before an action
+const MAX_COUNT: u32 = 9999;
+
+/// How long to wait for next key of a sequence
+pub const SEQUENCE_TIMEOUT: Duration = Duration::from_millis(800);
+
+/// Action for this key press, taking keys pressed before it into account
+///
+/// If the keys so far can be continued into some longer binding, nothing
+/// is done until either next key arrives or SEQUENCE_TIMEOUT passes.
+pub fn action_for_key(
+    key: Key,
+    keyboard: &KeyboardMapping,
+    appstate: &mut AppState,
+) -> KeyboardAction {
+    let (window, mode) = (appstate.active_window, appstate.mode);
+    if let Some(count) = count_with_key(key, keyboard, appstate) {
+        appstate.count = Some(count);
+        return KeyboardAction::Nothing;
+    }
+
+    let mut keys = appstate
+        .pending_keys
+        .take()
+        .map(|pending| pending.keys)
+        .unwrap_or_default();
+    keys.push(key);
+
+    let continuations = keyboard.continuations(&keys, window, mode);
+    if !continuations.is_empty() {
+        appstate.pending_keys = Some(PendingKeys {
+            keys,
+            expires_at: Instant::now() + SEQUENCE_TIMEOUT,
+            continuations,
+        });
+        return KeyboardAction::Nothing;
+    }
+
+    match keyboard.resolve(&keys, window, mode) {
+        Some(action) => action,
+        // Sequence lead nowhere, start over from this key
+        None if keys.len() > 1 => action_for_key(key, keyboard, appstate),
+        None => unbound_key_action(key, window, mode),
+    }
+}
+
+/// Count typed so far including this key, if this key is a digit of count
+///
+/// Digits are part of count only in normal mode, when they are not bound
+/// to anything themselves. `0` only continues a count, never starts one
+fn count_with_key(key: Key, keyboard: &KeyboardMapping, appstate: &AppState) -> Option<u32> {
+    let (window, mode) = (appstate.active_window, appstate.mode);
+    if mode != Mode::Normal || appstate.pending_keys.is_some() || !key.modifiers.is_empty() {
+        return None;
+    }
+    let KeyCode::Char(ch) = key.code else {
+        return None;
+    };
+    let digit = ch.to_digit(10)?;
+    if digit == 0 && appstate.count.is_none() {
+        return None;
+    }
+    if keyboard.resolve(&[key], window, mode).is_some()
+        || !keyboard.continuations(&[key], window, mode).is_empty()
+    {
+        return None;
+    }
+
+    let count = appstate.count.unwrap_or_default();
+    Some((count * 10 + digit).min(MAX_COUNT))
+}
+
+/// Action of pending sequence once it have waited long enough for next key
+pub fn expire_pending_keys(
+    keyboard: &KeyboardMapping,
+    appstate: &mut AppState,
+    now: Instant,
+) -> Option<KeyboardAction> {
+    if appstate.pending_keys.as_ref()?.expires_at > now {
+        return None;
+    }
+    let keys = appstate.pending_keys.take()?.keys;
+    let (window, mode) = (appstate.active_window, appstate.mode);
+    let action = keyboard
+        .resolve(&keys, window, mode)
+        .unwrap_or(match keys.as_slice() {
+            [key] => unbound_key_action(*key, window, mode),
+            _ => KeyboardAction::Nothing,
+        });
+    Some(action)
 }
 
-pub fn handle_action(action: KeyboardAction, appstate: &mut AppState) {
+/// What a key does when nothing is bound to it
+fn unbound_key_action(key: Key, window: Window, mode: Mode) -> KeyboardAction {
+    let KeyCode::Char(ch) = key.code else {
+        return KeyboardAction::Nothing;
+    };
+    if !key.modifiers.is_empty() {
+        return KeyboardAction::Nothing;
+    }
+    match (mode, window) {
+        // Typing goes into the text field
+        (Mode::Insert, _) => KeyboardAction::PushSearchQuery(ch),
+        // Unbound letters jump through the list
+        (Mode::Normal, Window::PaneWindow) => KeyboardAction::JumpToLetter(ch),
+        (Mode::Command, _) => KeyboardAction::PushCommandLine(ch),
+        _ => KeyboardAction::Nothing,
+    }
+}
+
+pub fn handle_action(
+    action: KeyboardAction,
+    appstate: &mut AppState,
+    config: &Config,
+    services: &mut Services,
+) -> Result<(), String> {
+    // Reverse search through history takes the typing, any
+    // other key ends it leaving what was found in searchbar
+    let in_history_search = matches!(
+        action,
+        KeyboardAction::PushSearchQuery(_)
+            | KeyboardAction::PopSearchQuery
+            | KeyboardAction::ReverseSearchHistory
+            | KeyboardAction::Execute
+            | KeyboardAction::Nothing
+    );
+    if !in_history_search {
+        accept_history_search(appstate);
+    }
+
+    let player = &mut *services.player;
+    let preferences = &config.preferences;
     match action {
         KeyboardAction::Quit => (),
 
         KeyboardAction::ForceQuit => std::process::exit(0),
 
-        KeyboardAction::PushSearchQuery(ch) => {
-            appstate.altering_query.push(ch);
+        KeyboardAction::PushSearchQuery(ch) => match appstate.search_history.search() {
+            Some(_) => appstate
+                .search_history
+                .edit_search(|pattern| pattern.push(ch)),
+            None => {
+                appstate.altering_query.insert(ch);
+                suggest(appstate, config);
+            }
+        },
+
+        KeyboardAction::PopSearchQuery => match appstate.search_history.search() {
+            Some(_) => appstate.search_history.edit_search(|pattern| {
+                pattern.pop();
+            }),
+            None => {
+                appstate.altering_query.delete(Motion::Left);
+                suggest(appstate, config);
+            }
+        },
+
+        KeyboardAction::MoveSearchCursor(motion) => {
+            appstate.altering_query.move_cursor(motion);
+        }
+
+        KeyboardAction::DeleteSearchQuery(motion) => {
+            appstate.altering_query.delete(motion);
+            suggest(appstate, config);
         }
 
-        KeyboardAction::PopSearchQuery => {
-            appstate.altering_query.pop();
+        KeyboardAction::MoveInSearchHistory(direction) => recall_history(direction, appstate),
+
+        KeyboardAction::MoveInSuggestions(direction) => {
+            if appstate.suggestions.is_empty() {
+                recall_history(direction, appstate);
+            } else if let Some(text) = appstate.suggestions.select(direction) {
+                appstate.altering_query.set_text(text);
+            }
         }
 
+        KeyboardAction::ReverseSearchHistory => appstate.search_history.search_older(),
+
         KeyboardAction::GotoNextWindow => {
-            (*appstate).active_window = appstate.active_window.next().unwrap_or_else(Window::first);
+            appstate.focus(shown_window(appstate.active_window, true, preferences));
         }
 
         KeyboardAction::GotoPrviousWindow => {
-            (*appstate).active_window = appstate.active_window.prev().unwrap_or_else(Window::last);
+            appstate.focus(shown_window(appstate.active_window, false, preferences));
         }
 
         KeyboardAction::Escape => {
-            (*appstate).active_window = appstate.active_window.next().unwrap_or_else(Window::first);
+            if appstate.close_popup().is_none() {
+                appstate.focus(shown_window(appstate.active_window, true, preferences));
+            }
         }
 
         KeyboardAction::StartSearching => {
-            (*appstate).active_window = Window::SearchBar;
+            appstate.focus(Window::SearchBar);
         }
 
         KeyboardAction::MoveInPaneWindow(MoveDirection::Right) => {
-            (*appstate).panetab_state.active_tab = appstate
-                .panetab_state
-                .active_tab
-                .next()
-                .unwrap_or_else(PaneWindow::first);
+            appstate.panetab_state.active_tab =
+                shown_pane(appstate.panetab_state.active_tab, true, preferences);
         }
 
         KeyboardAction::MoveInPaneWindow(MoveDirection::Left) => {
-            (*appstate).panetab_state.active_tab = appstate
-                .panetab_state
-                .active_tab
-                .prev()
-                .unwrap_or_else(PaneWindow::last);
+            appstate.panetab_state.active_tab =
+                shown_pane(appstate.panetab_state.active_tab, false, preferences);
         }
 
-        KeyboardAction::MoveInPaneWindow(_) => {}
+        KeyboardAction::MoveInPaneWindow(direction) => {
+            move_in_pane(
+                appstate.panetab_state.active_tab,
+                direction,
+                appstate,
+                &services.searcher,
+            )?;
+        }
 
-        KeyboardAction::MoveInShortcuts(MoveDirection::Down) => {
-            let next = appstate
-                .shortcut_list_state
-                .get_ref()
-                .selected()
-                .map(|s| {
-                    if s + 1 < gadgets::shortcut::LIST_ITEMS.len() {
-                        Some(s + 1)
-                    } else {
-                        None
-                    }
-                })
-                .unwrap_or(Some(0));
-            (*appstate).shortcut_list_state.get_mut_ref().select(next);
+        KeyboardAction::GotoPane(pane) => {
+            if pane_preferences(pane, preferences).is_some() {
+                appstate.panetab_state.active_tab = pane;
+                appstate.focus(Window::PaneWindow);
+            }
         }
 
-        KeyboardAction::MoveInShortcuts(MoveDirection::Up) => {
-            let next = appstate
-                .shortcut_list_state
-                .get_ref()
-                .selected()
-                .map(|s| if s > 0 { Some(s - 1) } else { None })
-                .unwrap_or(Some(gadgets::shortcut::LIST_ITEMS.len() - 1));
-            (*appstate).shortcut_list_state.get_mut_ref().select(next);
+        KeyboardAction::MoveInMusicList(direction) => {
+            move_in_pane(
+                PaneWindow::MusicPane,
+                direction,
+                appstate,
+                &services.searcher,
+            )?;
+        }
+
+        KeyboardAction::MoveInPlaylistList(direction) => {
+            move_in_pane(
+                PaneWindow::PlaylistPane,
+                direction,
+                appstate,
+                &services.searcher,
+            )?;
+        }
+
+        KeyboardAction::MoveInArtistList(direction) => {
+            move_in_pane(
+                PaneWindow::ArtistPane,
+                direction,
+                appstate,
+                &services.searcher,
+            )?;
+        }
+
+        KeyboardAction::JumpToLetter(ch) => {
+            let (state, viewport, labels) = pane_list(appstate, appstate.panetab_state.active_tab);
+            let start = state.selected().map_or(0, |s| s + 1);
+            let found = (0..labels.len())
+                .map(|i| (start + i) % labels.len())
+                .find(|i| {
+                    labels[*i]
+                        .chars()
+                        .next()
+                        .is_some_and(|first| first.to_lowercase().eq(ch.to_lowercase()))
+                });
+            if found.is_some() {
+                state.select(found);
+                viewport.follow(found);
+            }
+        }
+
+        KeyboardAction::MoveInShortcuts(direction) => {
+            let list_len = preferences
+                .shortcuts
+                .as_ref()
+                .map_or(0, |shortcuts| shortcuts.items.len());
+            let state = appstate.shortcut_list_state.get_mut_ref();
+            let next = moved_selection(state.selected(), list_len, direction, list_len);
+            state.select(next);
+        }
+
+        KeyboardAction::PausePlay => player.toggle_pause()?,
+
+        KeyboardAction::SeekForward(by) => player.seek(Seek::Forward(by))?,
+
+        KeyboardAction::SeekBackward(by) => player.seek(Seek::Backward(by))?,
+
+        KeyboardAction::Seek(TrackPosition::Time(to)) => player.seek(Seek::Absolute(to))?,
+
+        KeyboardAction::Seek(TrackPosition::Percent(percent)) => {
+            if let Some(duration) = player.duration()? {
+                player.seek(Seek::Absolute(duration * percent as u32 / 100))?;
+            }
         }
 
+        KeyboardAction::VolumeUp(step) => {
+            let volume = player.volume()?;
+            player.set_volume(volume.saturating_add(step))?;
+        }
+
+        KeyboardAction::VolumeDown(step) => {
+            let volume = player.volume()?;
+            player.set_volume(volume.saturating_sub(step))?;
+        }
+
+        KeyboardAction::SetVolume(volume) => player.set_volume(volume)?,
+
+        KeyboardAction::Execute => match appstate.active_window {
+            Window::SearchBar => submit_search(appstate, services)?,
+            Window::Shortcut => run_shortcut(appstate, config, services)?,
+            _ => enqueue_highlighted(Enqueue::Replace, appstate, services)?,
+        },
+
+        KeyboardAction::AddToQueue => enqueue_highlighted(Enqueue::Append, appstate, services)?,
+
+        KeyboardAction::NextTrack => {
+            if appstate.queue.advance(Advance::UserRequested).is_some() {
+                play_current(appstate, player)?;
+            }
+        }
+
+        KeyboardAction::PreviousTrack => {
+            if appstate.queue.previous().is_some() {
+                play_current(appstate, player)?;
+            }
+        }
+
+        KeyboardAction::ShuffleToggle => {
+            appstate.queue.toggle_shuffle();
+        }
+
+        KeyboardAction::RepeatSwitch => {
+            appstate.queue.switch_repeat();
+        }
+
+        KeyboardAction::ClearQueue => {
+            appstate.queue.clear();
+            appstate.player_state = PlayerState {
+                volume: appstate.player_state.volume,
+                ..Default::default()
+            };
+            player.stop()?;
+        }
+
+        KeyboardAction::OpenCommandLine => {
+            appstate.open_command_line(CommandLine::new(CommandLineKind::Prompt));
+        }
+
+        KeyboardAction::OpenPalette => {
+            let mut palette = CommandLine::new(CommandLineKind::Palette);
+            command::refresh_choices(&mut palette, config);
+            appstate.open_command_line(palette);
+        }
+
+        KeyboardAction::AppDetails => {
+            let mut help = CommandLine::new(CommandLineKind::Help);
+            command::refresh_choices(&mut help, config);
+            appstate.open_command_line(help);
+        }
+
+        KeyboardAction::PushCommandLine(ch) => {
+            if let Some(command_line) = appstate.active_command_line() {
+                command_line.input.push(ch);
+                command::refresh_choices(command_line, config);
+            }
+        }
+
+        KeyboardAction::PopCommandLine => match appstate.active_command_line() {
+            // Nothing left to erase, same as giving up
+            Some(command_line) if command_line.input.is_empty() => {
+                appstate.close_command_line();
+            }
+            Some(command_line) => {
+                command_line.input.pop();
+                command::refresh_choices(command_line, config);
+            }
+            None => (),
+        },
+
+        KeyboardAction::CompleteCommandLine => {
+            if let Some(command_line) = appstate.active_command_line() {
+                command::complete(command_line, config);
+            }
+        }
+
+        KeyboardAction::MoveInCommandLine(direction) => {
+            if let Some(command_line) = appstate.active_command_line() {
+                let len = command_line.choices.len();
+                command_line.selected = moved_selection(command_line.selected, len, direction, len);
+                command::apply_selected_completion(command_line);
+            }
+        }
+
+        KeyboardAction::CloseCommandLine => {
+            appstate.close_command_line();
+        }
+
+        KeyboardAction::ShowDetails | KeyboardAction::Expand => {
+            if let Some(details) = highlighted_details(appstate) {
+                appstate.open_popup(Popup::Details(details));
+            }
+        }
+
+        KeyboardAction::MoveInPopup(direction) => {
+            // text goes inside the borders
+            let width = appstate.popup_area.width.saturating_sub(2) as usize;
+            let height = appstate.popup_area.height.saturating_sub(2) as usize;
+            if let Some(PopupLayer {
+                popup: Popup::Details(details),
+                ..
+            }) = appstate.popups.last_mut()
+            {
+                details.scroll(direction, width, height);
+            }
+        }
+
+        // Needs to change flow of app, so is done in `perform`
+        KeyboardAction::ExecuteCommandLine => (),
+
+        KeyboardAction::ClearResult => appstate.clear_results(),
+
         KeyboardAction::Nothing => (),
+    }
+
+    Ok(())
+}
+
+/// Selection after moving in list of `len` items
+/// Moving a single step past either end wraps around
+/// while pages stop at the ends
+fn moved_selection(
+    selected: Option<usize>,
+    len: usize,
+    direction: MoveDirection,
+    page: usize,
+) -> Option<usize> {
+    let last = len.checked_sub(1)?;
+    // list may have shrunk since selection was made
+    let selected = selected.map(|s| s.min(last));
+    let next = match (direction, selected) {
+        (MoveDirection::Down, Some(s)) if s < last => s + 1,
+        (MoveDirection::Down, _) | (MoveDirection::First, _) => 0,
+        (MoveDirection::Up, Some(s)) if s > 0 => s - 1,
+        (MoveDirection::Up, _) | (MoveDirection::Last, _) => last,
+        (MoveDirection::PageDown, s) => s.map_or(0, |s| (s + page).min(last)),
+        (MoveDirection::PageUp, s) => s.map_or(0, |s| s.saturating_sub(page)),
+        (MoveDirection::Left | MoveDirection::Right, s) => return s,
+    };
+    Some(next)
+}
+
+/// Selection and viewport of list shown in pane
+/// along with the text each row is known by
+fn pane_list(
+    appstate: &mut AppState,
+    pane: PaneWindow,
+) -> (&mut TableState, &mut Viewport, Vec<&str>) {
+    match pane {
+        PaneWindow::MusicPane => (
+            appstate.music_pane_state.get_mut_ref(),
+            &mut appstate.music_viewport,
+            appstate
+                .music_result
+                .list
+                .iter()
+                .map(|m| m.title.as_str())
+                .collect(),
+        ),
+        PaneWindow::PlaylistPane => (
+            appstate.playlist_pane_state.get_mut_ref(),
+            &mut appstate.playlist_viewport,
+            appstate
+                .playlist_result
+                .list
+                .iter()
+                .map(|p| p.title.as_str())
+                .collect(),
+        ),
+        PaneWindow::ArtistPane => (
+            appstate.artist_pane_state.get_mut_ref(),
+            &mut appstate.artist_viewport,
+            appstate
+                .artist_result
+                .list
+                .iter()
+                .map(|a| a.name.as_str())
+                .collect(),
+        ),
+    }
+}
+
+/// Move selection in list of given pane, scrolling along
+/// Once selection is within a page of the end,
+/// next page of result is fetched in background
+fn move_in_pane(
+    pane: PaneWindow,
+    direction: MoveDirection,
+    appstate: &mut AppState,
+    searcher: &Sender<SearchRequest>,
+) -> Result<(), String> {
+    let (state, viewport, labels) = pane_list(appstate, pane);
+    let len = labels.len();
+    let next = moved_selection(state.selected(), len, direction, viewport.page());
+    state.select(next);
+    viewport.follow(next);
+
+    let near_end = next.is_some_and(|next| next + viewport.page() >= len);
+    match near_end {
+        true => fetch_next_page(pane, appstate, searcher),
+        false => Ok(()),
+    }
+}
+
+/// Ask for next page of result in pane, unless
+/// there is none or it is already being fetched
+fn fetch_next_page(
+    pane: PaneWindow,
+    appstate: &mut AppState,
+    searcher: &Sender<SearchRequest>,
+) -> Result<(), String> {
+    let next_page = match pane {
+        PaneWindow::MusicPane => appstate
+            .music_result
+            .start_loading()
+            .map(|continuation| (ResultKind::Music, continuation)),
+        PaneWindow::PlaylistPane => appstate
+            .playlist_result
+            .start_loading()
+            .map(|continuation| (ResultKind::Playlist, continuation)),
+        PaneWindow::ArtistPane => appstate
+            .artist_result
+            .start_loading()
+            .map(|continuation| (ResultKind::Artist, continuation)),
+    };
+    match next_page {
+        Some((kind, continuation)) => searcher
+            .send(SearchRequest::NextPage(kind, continuation))
+            .map_err(|_| "Search worker is not running".to_string()),
+        None => Ok(()),
+    }
+}
+
+/// Most pages fetched for a query without user scrolling to them
+/// Filters may drop most of what backend gives, so rows wanted may never be reached
+const FILL_PAGE_LIMIT: usize = 5;
+
+/// Keep fetching pages of result in pane until
+/// it has as many rows as preferences ask for
+/// Gives up when last page `added` no rows or enough pages are fetched
+fn fill_pane(
+    pane: PaneWindow,
+    added: usize,
+    appstate: &mut AppState,
+    config: &Config,
+    searcher: &Sender<SearchRequest>,
+) -> Result<(), String> {
+    let Some(pane_preferences) = pane_preferences(pane, &config.preferences) else {
+        return Ok(());
+    };
+    let (query, page) = match pane {
+        PaneWindow::MusicPane => (&appstate.music_result.query, appstate.music_result.page),
+        PaneWindow::PlaylistPane => (
+            &appstate.playlist_result.query,
+            appstate.playlist_result.page,
+        ),
+        PaneWindow::ArtistPane => (&appstate.artist_result.query, appstate.artist_result.page),
+    };
+    if added == 0 || page + 1 >= FILL_PAGE_LIMIT {
+        return Ok(());
+    }
+    let wanted = match query.is_local() {
+        true => pane_preferences.local_result_count,
+        false => pane_preferences.remote_result_count,
+    };
+    match pane_list(appstate, pane).2.len() < wanted as usize {
+        true => fetch_next_page(pane, appstate, searcher),
+        false => Ok(()),
+    }
+}
+
+/// Preferences of pane, None if it is hidden
+fn pane_preferences(pane: PaneWindow, preferences: &WidgetPreferences) -> Option<&PanePreferences> {
+    match pane {
+        PaneWindow::MusicPane => preferences.music_pane.as_ref(),
+        PaneWindow::PlaylistPane => preferences.playlist_pane.as_ref(),
+        PaneWindow::ArtistPane => preferences.artist_pane.as_ref(),
+    }
+}
+
+/// Shown pane after (or before if not `forward`) given one, wrapping around
+/// Stays on the same pane if no other is shown
+fn shown_pane(from: PaneWindow, forward: bool, preferences: &WidgetPreferences) -> PaneWindow {
+    let step = |pane: PaneWindow| match forward {
+        true => pane.next().unwrap_or_else(PaneWindow::first),
+        false => pane.prev().unwrap_or_else(PaneWindow::last),
+    };
+    let mut pane = step(from);
+    while pane != from && pane_preferences(pane, preferences).is_none() {
+        pane = step(pane);
+    }
+    pane
+}
+
+/// Shown window after (or before if not `forward`) given one, wrapping around
+/// Windows of hidden gadgets are skipped
+fn shown_window(from: Window, forward: bool, preferences: &WidgetPreferences) -> Window {
+    let step = |window: Window| match forward {
+        true => window.next().unwrap_or_else(Window::first),
+        false => window.prev().unwrap_or_else(Window::last),
+    };
+    let is_shown = |window| match window {
+        Window::SearchBar => preferences.searchbar.is_some(),
+        Window::Shortcut => preferences.shortcuts.is_some(),
+        Window::PaneTab => preferences.pane_tab.is_some(),
+        Window::PaneWindow => [
+            PaneWindow::MusicPane,
+            PaneWindow::PlaylistPane,
+            PaneWindow::ArtistPane,
+        ]
+        .into_iter()
+        .any(|pane| pane_preferences(pane, preferences).is_some()),
+        _ => true,
+    };
+    let mut window = step(from);
+    while window != from && !is_shown(window) {
+        window = step(window);
+    }
+    window
+}
+
+/// Load current track of queue in player
+fn play_current(appstate: &mut AppState, player: &mut dyn Player) -> Result<(), String> {
+    match appstate.queue.current() {
+        Some(track) => {
+            // Until player reports the title on it's own
+            appstate.player_state.title = Some(track.title.clone());
+            player.load(&track.url())
+        }
+        None => Ok(()),
+    }
+}
+
+/// Put tracks in queue and start playing if they are now current
+fn enqueue(
+    tracks: Vec<MusicUnit>,
+    start: usize,
+    how: Enqueue,
+    appstate: &mut AppState,
+    player: &mut dyn Player,
+) -> Result<(), String> {
+    let was_playing = appstate.queue.current().is_some();
+    appstate.queue.add(tracks, start, how);
+    if how == Enqueue::Replace || !was_playing {
+        play_current(appstate, player)?;
+    }
+    Ok(())
+}
+
+/// Put highlighted row of active pane in queue
+/// Playlists are fetched first and queued once they arrive
+fn enqueue_highlighted(
+    how: Enqueue,
+    appstate: &mut AppState,
+    services: &mut Services,
+) -> Result<(), String> {
+    if appstate.active_window != Window::PaneWindow {
+        return Ok(());
+    }
+
+    match appstate.panetab_state.active_tab {
+        PaneWindow::MusicPane => {
+            let selected = appstate.music_pane_state.get_ref().selected();
+            if let Some(selected) = selected.filter(|s| *s < appstate.music_result.list.len()) {
+                // On replace, rest of the results are played after highlighted one
+                let (tracks, start) = match how {
+                    Enqueue::Replace => (appstate.music_result.list.clone(), selected),
+                    Enqueue::Append => (vec![appstate.music_result.list[selected].clone()], 0),
+                };
+                enqueue(tracks, start, how, appstate, &mut *services.player)?;
+            }
+        }
+        PaneWindow::PlaylistPane => {
+            let selected = appstate.playlist_pane_state.get_ref().selected();
+            if let Some(playlist) = selected.and_then(|s| appstate.playlist_result.list.get(s)) {
+                services
+                    .searcher
+                    .send(SearchRequest::PlaylistTracks {
+                        id: playlist.id.clone(),
+                        enqueue: how,
+                    })
+                    .map_err(|_| "Search worker is not running".to_string())?;
+            }
+        }
+        PaneWindow::ArtistPane => (),
+    }
+
+    Ok(())
+}
+
+/// Search for what is in searchbar and go to results
+/// which are shown once they arrive
+fn submit_search(appstate: &mut AppState, services: &mut Services) -> Result<(), String> {
+    accept_history_search(appstate);
+    let text = appstate.altering_query.text().trim().to_string();
+    if text.is_empty() {
+        return Ok(());
+    }
+    let search = text
+        .parse::<SearchQuery>()
+        .map_err(|e| format!("Search not sent: {e}"))?;
+    if search.search_text().is_empty() {
+        return Err("Search needs some words to look for, not only filters".to_string());
+    }
+    send_query(Query::SearchAll(search), appstate, services)?;
+    appstate.suggestions = Suggestions::default();
+    appstate.search_history.record(&text)
+}
+
+/// Ask for results of query and go to panes,
+/// where they are shown once they arrive
+fn send_query(query: Query, appstate: &mut AppState, services: &Services) -> Result<(), String> {
+    let sent = query.clone().get_final_query();
+    services
+        .searcher
+        .send(SearchRequest::Query(query))
+        .map_err(|_| "Search worker is not running".to_string())?;
+    appstate.sent_query = Some(sent);
+    appstate.focus(Window::PaneWindow);
+    Ok(())
+}
+
+/// Run highlighted entry of sidebar, results of which go to panes
+fn run_shortcut(
+    appstate: &mut AppState,
+    config: &Config,
+    services: &Services,
+) -> Result<(), String> {
+    let selected = appstate.shortcut_list_state.get_ref().selected();
+    let Some(item) = selected.and_then(|i| config.preferences.shortcuts.as_ref()?.items.get(i))
+    else {
+        return Ok(());
+    };
+    let query = match &item.action {
+        ShortcutAction::Search(text) => {
+            let search = text
+                .parse::<SearchQuery>()
+                .map_err(|e| format!("Search of {} is not valid: {e}", item.label))?;
+            // shown in searchbar as if it was typed there
+            appstate.altering_query.set_text(text.clone());
+            Query::SearchAll(search)
+        }
+        ShortcutAction::Playlist(url) => Query::Playlist(playlist_id(url).to_string()),
+        ShortcutAction::LocalDir(dir) => Query::Local(dir.clone()),
+        ShortcutAction::View(Shortcut::Trending) => Query::Trending,
+        ShortcutAction::View(Shortcut::LikedSongs) => Query::LikedMusic,
+        ShortcutAction::View(Shortcut::MyPlaylist) => Query::SavedPlaylist,
+        ShortcutAction::View(Shortcut::FollowingArtist) => Query::FollowingArtist,
+        ShortcutAction::View(Shortcut::Local) => {
+            Query::Local(dirs::audio_dir().ok_or("Cannot get user music dir")?)
+        }
+        ShortcutAction::View(Shortcut::Search) => {
+            appstate.focus(Window::SearchBar);
+            return Ok(());
+        }
+        ShortcutAction::View(Shortcut::YoutubeCommunity | Shortcut::Downloaded) => {
+            return Err(format!("{} is not available yet", item.label));
+        }
+    };
+    send_query(query, appstate, services)
+}
+
+/// Id of playlist in it's url, or the text itself if it is not an url
+/// Eg: `https://www.youtube.com/playlist?list=PL42&si=xyz` gives `PL42`
+fn playlist_id(url: &str) -> &str {
+    match url.split_once("list=") {
+        Some((_, rest)) => rest.split('&').next().unwrap_or_default(),
+        None => url.trim(),
+    }
+}
+
+/// Put older or newer search from history in searchbar
+fn recall_history(direction: MoveDirection, appstate: &mut AppState) {
+    let typed = appstate.altering_query.text();
+    if let Some(recalled) = appstate.search_history.recall(direction, typed) {
+        appstate.altering_query.set_text(recalled);
+        appstate.suggestions = Suggestions::default();
+    }
+}
+
+/// Suggest completions of what is now typed in searchbar
+/// from history and from music in queue and results
+fn suggest(appstate: &mut AppState, config: &Config) {
+    let library = appstate
+        .queue
+        .tracks()
+        .iter()
+        .chain(&appstate.music_result.list)
+        .collect::<Vec<_>>();
+    appstate.suggestions = Suggestions::new(
+        appstate.altering_query.text(),
+        &appstate.search_history,
+        &library,
+        config.backend.suggestions,
+        Instant::now(),
+    );
+}
+
+/// End reverse search through history, if on,
+/// putting what was found in searchbar
+fn accept_history_search(appstate: &mut AppState) {
+    if let Some(found) = appstate.search_history.end_search() {
+        appstate.altering_query.set_text(found);
+    }
+}
+
+/// Details of highlighted row of active pane
+/// None if pane is not focused, has nothing highlighted or lists artists
+fn highlighted_details(appstate: &AppState) -> Option<Details> {
+    if appstate.active_window != Window::PaneWindow {
+        return None;
+    }
+    match appstate.panetab_state.active_tab {
+        PaneWindow::MusicPane => {
+            let selected = appstate.music_pane_state.get_ref().selected()?;
+            appstate
+                .music_result
+                .list
+                .get(selected)
+                .map(Details::of_music)
+        }
+        PaneWindow::PlaylistPane => {
+            let selected = appstate.playlist_pane_state.get_ref().selected()?;
+            appstate
+                .playlist_result
+                .list
+                .get(selected)
+                .map(Details::of_playlist)
+        }
+        PaneWindow::ArtistPane => None,
+    }
+}
+
+pub fn handle_player_event(
+    event: PlayerEvent,
+    appstate: &mut AppState,
+    services: &mut Services,
+) -> Result<(), String> {
+    let player_state = &mut appstate.player_state;
+    match event {
+        PlayerEvent::TrackStarted => {
+            player_state.position = None;
+            player_state.duration = None;
+        }
+        PlayerEvent::TrackEnded => {
+            player_state.position = player_state.duration;
+            if appstate.queue.advance(Advance::TrackEnded).is_some() {
+                play_current(appstate, &mut *services.player)?;
+            }
+        }
+        PlayerEvent::PauseChanged(paused) => player_state.paused = paused,
+        PlayerEvent::PositionChanged(position) => player_state.position = Some(position),
+        PlayerEvent::DurationChanged(duration) => player_state.duration = Some(duration),
+        PlayerEvent::VolumeChanged(volume) => player_state.volume = volume,
+        PlayerEvent::TitleChanged(title) => player_state.title = Some(title),
+    }
+
+    Ok(())
+}
 
-        _ => todo!(),
+pub fn handle_search_response(
+    response: SearchResponse,
+    appstate: &mut AppState,
+    config: &Config,
+    services: &mut Services,
+) -> Result<(), String> {
+    let searcher = &services.searcher;
+    match response {
+        SearchResponse::Music(Ok(result)) => {
+            let added = if result.page > 0 {
+                appstate.music_result.append(result)
+            } else if appstate.sent_query.as_ref() == Some(&result.query) {
+                let added = result.list.len();
+                appstate.music_result = result;
+                appstate.music_pane_state.get_mut_ref().select(None);
+                appstate.music_viewport.follow(None);
+                added
+            } else {
+                0
+            };
+            fill_pane(PaneWindow::MusicPane, added, appstate, config, searcher)?;
+        }
+        SearchResponse::Playlist(Ok(result)) => {
+            let added = if result.page > 0 {
+                appstate.playlist_result.append(result)
+            } else if appstate.sent_query.as_ref() == Some(&result.query) {
+                let added = result.list.len();
+                appstate.playlist_result = result;
+                appstate.playlist_pane_state.get_mut_ref().select(None);
+                appstate.playlist_viewport.follow(None);
+                added
+            } else {
+                0
+            };
+            fill_pane(PaneWindow::PlaylistPane, added, appstate, config, searcher)?;
+        }
+        SearchResponse::Artist(Ok(result)) => {
+            let added = if result.page > 0 {
+                appstate.artist_result.append(result)
+            } else if appstate.sent_query.as_ref() == Some(&result.query) {
+                let added = result.list.len();
+                appstate.artist_result = result;
+                appstate.artist_pane_state.get_mut_ref().select(None);
+                appstate.artist_viewport.follow(None);
+                added
+            } else {
+                0
+            };
+            fill_pane(PaneWindow::ArtistPane, added, appstate, config, searcher)?;
+        }
+        SearchResponse::PlaylistTracks(Ok(tracks), how) => {
+            enqueue(tracks, 0, how, appstate, &mut *services.player)?;
+        }
+        SearchResponse::Suggestions(typed, Ok(suggestions)) => {
+            appstate.suggestions.receive(&typed, suggestions);
+        }
+        SearchResponse::Suggestions(_, Err(e)) => {
+            return Err(format!("While fetching suggestions: {e}"));
+        }
+        // Failed page can be asked for again by scrolling
+        SearchResponse::Music(Err(e)) => {
+            appstate.music_result.loading = false;
+            return Err(e);
+        }
+        SearchResponse::Playlist(Err(e)) => {
+            appstate.playlist_result.loading = false;
+            return Err(e);
+        }
+        SearchResponse::Artist(Err(e)) => {
+            appstate.artist_result.loading = false;
+            return Err(e);
+        }
+        SearchResponse::PlaylistTracks(Err(e), _) => return Err(e),
     }
+
+    Ok(())
 }
 
 #[cfg(feature = "crossterm")]
 mod crossterm_event {
-    use crate::gadgets::window::Window;
-
     use super::*;
-    use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
-    use std::time::Duration;
-
-    // TODO:
-    // make this configurable
-    const REFRESH_RATE: Duration = Duration::from_secs(2);
-
-    pub fn listen_for_event(keyboard: &KeyboardMapping, appstate: &AppState) -> EventSummary {
-        if event::poll(REFRESH_RATE).map_err(|_| "crossterm pool event error") == Ok(true) {
-            match event::read().unwrap() {
-                Event::Resize(_col, _rows) => EventSummary::Resize,
-                Event::Key(k) => {
-                    let key = into_native_event(k);
-                    let action = {
-                        if appstate.active_window == Window::SearchBar {
-                            if let Key::Char(ch) = key {
-                                KeyboardAction::PushSearchQuery(ch)
-                            } else if key == Key::Backspace {
-                                KeyboardAction::PopSearchQuery
-                            } else if key == Key::Esc {
-                                KeyboardAction::GotoNextWindow
-                            } else {
-                                KeyboardAction::Nothing
-                            }
-                        } else {
-                            keyboard
-                                .action_for(&(key, Some(appstate.active_window)).into())
-                                .unwrap_or_else(|| {
-                                    keyboard
-                                        .action_for(&(key, None).into())
-                                        .unwrap_or(KeyboardAction::Nothing)
-                                })
-                        }
-                    };
-
-                    EventSummary::Execution(action)
+    use crossterm::event::{self, Event, KeyCode as NativeKeyCode, KeyEvent, KeyModifiers};
+    use user_config::keyboard::Modifiers;
+
+    pub fn spawn_listener(sender: Sender<Message>) {
+        thread::spawn(move || {
+            while let Ok(event) = event::read() {
+                let message = match event {
+                    Event::Resize(_col, _rows) => Message::Resize,
+                    Event::Key(key) => Message::Key(into_native_event(key)),
+                    _ => continue,
+                };
+                if sender.send(message).is_err() {
+                    break;
                 }
-                _ => EventSummary::Ignored,
             }
-        } else {
-            EventSummary::Nothing
-        }
+        });
     }
 
     fn into_native_event(key: KeyEvent) -> Key {
-        match key.code {
-            KeyCode::BackTab => Key::BackTab,
-            KeyCode::Backspace => Key::Backspace,
-            KeyCode::Up => Key::Up,
-            KeyCode::Down => Key::Down,
-            KeyCode::PageDown => Key::PageDown,
-            KeyCode::PageUp => Key::PageUp,
-            KeyCode::Esc => Key::Esc,
-            KeyCode::End => Key::End,
-            KeyCode::Delete => Key::Delete,
-            KeyCode::Left => Key::Left,
-            KeyCode::Right => Key::Right,
-            KeyCode::Null => Key::Null,
-            KeyCode::Insert => Key::Insert,
-            KeyCode::Home => Key::Home,
-            KeyCode::Tab => Key::Tab,
-            KeyCode::F(n) => Key::F(n),
-            KeyCode::Char(c) => {
-                if key.modifiers.contains(KeyModifiers::CONTROL) {
-                    Key::Ctrl(c)
-                } else if key.modifiers.contains(KeyModifiers::ALT) {
-                    Key::Alt(c)
-                } else {
-                    Key::Char(c)
-                }
-            }
-            _ => Key::Null,
+        let code = match key.code {
+            NativeKeyCode::BackTab => KeyCode::BackTab,
+            NativeKeyCode::Backspace => KeyCode::Backspace,
+            NativeKeyCode::Up => KeyCode::Up,
+            NativeKeyCode::Down => KeyCode::Down,
+            NativeKeyCode::PageDown => KeyCode::PageDown,
+            NativeKeyCode::PageUp => KeyCode::PageUp,
+            NativeKeyCode::Esc => KeyCode::Esc,
+            NativeKeyCode::End => KeyCode::End,
+            NativeKeyCode::Delete => KeyCode::Delete,
+            NativeKeyCode::Left => KeyCode::Left,
+            NativeKeyCode::Right => KeyCode::Right,
+            NativeKeyCode::Null => KeyCode::Null,
+            NativeKeyCode::Insert => KeyCode::Insert,
+            NativeKeyCode::Home => KeyCode::Home,
+            NativeKeyCode::Tab => KeyCode::Tab,
+            NativeKeyCode::Enter => KeyCode::Enter,
+            NativeKeyCode::F(n) => KeyCode::F(n),
+            NativeKeyCode::Char(c) => KeyCode::Char(c),
+        };
+        let modifiers = Modifiers {
+            ctrl: key.modifiers.contains(KeyModifiers::CONTROL),
+            alt: key.modifiers.contains(KeyModifiers::ALT),
+            shift: key.modifiers.contains(KeyModifiers::SHIFT),
+        };
+        Key::new(code, modifiers)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn modifiers_are_kept() {
+            let native = |code, modifiers| into_native_event(KeyEvent { code, modifiers });
+            assert_eq!(
+                Key::new(KeyCode::Char('x'), Modifiers::CTRL.with(Modifiers::ALT)),
+                native(
+                    NativeKeyCode::Char('x'),
+                    KeyModifiers::CONTROL | KeyModifiers::ALT
+                )
+            );
+            assert_eq!(
+                Key::new(KeyCode::Up, Modifiers::SHIFT),
+                native(NativeKeyCode::Up, KeyModifiers::SHIFT)
+            );
+            assert_eq!(
+                Key::new(KeyCode::F(5), Modifiers::CTRL),
+                native(NativeKeyCode::F(5), KeyModifiers::CONTROL)
+            );
+            // shifted characters arrive already uppercased
+            assert_eq!(
+                Key::from(KeyCode::Char('G')),
+                native(NativeKeyCode::Char('G'), KeyModifiers::SHIFT)
+            );
+            assert_eq!(
+                Key::from(KeyCode::BackTab),
+                native(NativeKeyCode::BackTab, KeyModifiers::SHIFT)
+            );
         }
     }
 }
@@ -188,7 +1276,891 @@ mod crossterm_event {
 mod termion_event {
     use super::*;
 
-    pub fn listen_for_event(keyboard: &KeyboardMapping, app_state: &AppState) -> EventSummary {
+    pub fn spawn_listener(sender: Sender<Message>) {
+        let _ = sender;
         todo!()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::state::QueryResult;
+    use crate::types::state::{GeometryData, MusicPaneState};
+    use crate::types::unit::PlaylistUnit;
+    use std::sync::mpsc;
+
+    /// Player that only remembers what it was asked to do
+    #[derive(Default)]
+    struct RecordingPlayer {
+        calls: Vec<String>,
+        volume: u8,
+        paused: bool,
+    }
+
+    impl Player for RecordingPlayer {
+        fn load(&mut self, url: &str) -> Result<(), String> {
+            self.calls.push(format!("load {url}"));
+            Ok(())
+        }
+        fn stop(&mut self) -> Result<(), String> {
+            self.calls.push("stop".to_string());
+            Ok(())
+        }
+        fn pause(&mut self) -> Result<(), String> {
+            self.paused = true;
+            self.calls.push("pause".to_string());
+            Ok(())
+        }
+        fn resume(&mut self) -> Result<(), String> {
+            self.paused = false;
+            self.calls.push("resume".to_string());
+            Ok(())
+        }
+        fn is_paused(&mut self) -> Result<bool, String> {
+            Ok(self.paused)
+        }
+        fn seek(&mut self, seek: Seek) -> Result<(), String> {
+            self.calls.push(format!("seek {seek:?}"));
+            Ok(())
+        }
+        fn volume(&mut self) -> Result<u8, String> {
+            Ok(self.volume)
+        }
+        fn set_volume(&mut self, volume: u8) -> Result<(), String> {
+            self.volume = volume.min(100);
+            self.calls.push(format!("volume {}", self.volume));
+            Ok(())
+        }
+        fn position(&mut self) -> Result<Option<Duration>, String> {
+            Ok(None)
+        }
+        fn duration(&mut self) -> Result<Option<Duration>, String> {
+            Ok(None)
+        }
+    }
+
+    /// Everything `reduce` works with, as main loop would have it
+    struct TestApp {
+        appstate: AppState,
+        config: Config,
+        player: RecordingPlayer,
+        searcher: Sender<SearchRequest>,
+        /// What was sent to search worker
+        requests: Receiver<SearchRequest>,
+    }
+
+    /// Freshly started app with default config
+    fn harness() -> TestApp {
+        let (searcher, requests) = mpsc::channel();
+        TestApp {
+            appstate: AppState::default(),
+            config: user_config::default_config(),
+            player: RecordingPlayer::default(),
+            searcher,
+            requests,
+        }
+    }
+
+    impl TestApp {
+        /// Same app with focus on `window`
+        fn focused(mut self, window: Window) -> Self {
+            self.appstate.focus(window);
+            self
+        }
+
+        fn reduce(&mut self, message: Message) -> Flow {
+            let mut services = Services {
+                player: &mut self.player,
+                searcher: self.searcher.clone(),
+            };
+            reduce(message, &mut self.appstate, &self.config, &mut services)
+        }
+
+        /// Press keys written as in config, one after another
+        /// Flow after the last one is returned
+        fn press(&mut self, keys: &str) -> Flow {
+            let keys = Key::parse_sequence(keys).unwrap();
+            keys.into_iter()
+                .map(|key| self.reduce(Message::Key(key)))
+                .last()
+                .unwrap()
+        }
+
+        fn search(&mut self, response: SearchResponse) -> Flow {
+            self.reduce(Message::Search(response))
+        }
+
+        /// Requests sent to search worker since last asked
+        fn requests(&self) -> Vec<SearchRequest> {
+            self.requests.try_iter().collect()
+        }
+
+        fn selected_music(&self) -> Option<usize> {
+            self.appstate.music_pane_state.get_ref().selected()
+        }
+    }
+
+    fn music(id: &str) -> MusicUnit {
+        MusicUnit {
+            title: format!("title of {id}"),
+            artist: String::new(),
+            duration: None,
+            id: id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn keys_drive_player() {
+        let mut app = harness().focused(Window::Shortcut);
+        app.player.volume = 98;
+
+        for key in ["<space>", "<space>", "+"] {
+            assert!(app.press(key) == Flow::Continue);
+        }
+        assert!(app.press("q") == Flow::Quit);
+
+        assert_eq!(vec!["pause", "resume", "volume 100"], app.player.calls);
+    }
+
+    #[test]
+    fn queue_feeds_player() {
+        let mut app = harness().focused(Window::PaneWindow);
+        let appstate = &mut app.appstate;
+        appstate.music_result.list = vec![music("a"), music("b"), music("c")];
+        appstate.music_pane_state.get_mut_ref().select(Some(1));
+        appstate.playlist_result.list = vec![PlaylistUnit {
+            title: String::new(),
+            creator: String::new(),
+            song_count: 1,
+            id: "PL1".to_string(),
+        }];
+        appstate.playlist_pane_state.get_mut_ref().select(Some(0));
+
+        app.press("<enter>");
+        app.reduce(Message::Player(PlayerEvent::TrackEnded));
+        app.reduce(Message::Player(PlayerEvent::TrackEnded));
+        for key in ["p", "n", "n", "<right>", "a"] {
+            app.press(key);
+        }
+        app.search(SearchResponse::PlaylistTracks(
+            Ok(vec![music("d")]),
+            Enqueue::Append,
+        ));
+        app.press("n");
+
+        let expected_loads =
+            ["b", "c", "c", "d"].map(|id| format!("load https://www.youtube.com/watch?v={id}"));
+        assert_eq!(expected_loads.to_vec(), app.player.calls);
+        assert!(matches!(
+            app.requests().as_slice(),
+            [SearchRequest::PlaylistTracks { id, enqueue: Enqueue::Append }] if id == "PL1"
+        ));
+        assert_eq!(
+            Some("title of d".to_string()),
+            app.appstate.player_state.title
+        );
+    }
+
+    #[test]
+    fn clearing_queue_stops_player() {
+        let mut app = harness().focused(Window::PaneWindow);
+        app.appstate.music_result.list = vec![music("a"), music("b")];
+        app.appstate.music_pane_state.get_mut_ref().select(Some(0));
+        app.appstate.player_state.volume = 40;
+
+        app.press("<enter>");
+        app.reduce(Message::Player(PlayerEvent::PositionChanged(
+            Duration::from_secs(3),
+        )));
+        app.press(":queue clear<enter>");
+        // mpv reports the stop as end of file
+        app.reduce(Message::Player(PlayerEvent::TrackEnded));
+
+        assert_eq!(
+            vec!["load https://www.youtube.com/watch?v=a", "stop"],
+            app.player.calls
+        );
+        assert!(app.appstate.queue.current().is_none());
+        assert_eq!(None, app.appstate.player_state.title);
+        assert_eq!(None, app.appstate.player_state.position);
+        assert_eq!(40, app.appstate.player_state.volume);
+    }
+
+    #[test]
+    fn selection_moves_and_wraps() {
+        use MoveDirection::*;
+        assert_eq!(None, moved_selection(None, 0, Down, 10));
+        assert_eq!(Some(0), moved_selection(None, 5, Down, 10));
+        assert_eq!(Some(4), moved_selection(None, 5, Up, 10));
+        assert_eq!(Some(0), moved_selection(Some(4), 5, Down, 10));
+        assert_eq!(Some(4), moved_selection(Some(0), 5, Up, 10));
+        assert_eq!(Some(4), moved_selection(Some(1), 5, PageDown, 10));
+        assert_eq!(
+            Some(0),
+            moved_selection(Some(3), 5, PageUp, 2)
+                .and_then(|s| { moved_selection(Some(s), 5, PageUp, 2) })
+        );
+        assert_eq!(Some(0), moved_selection(Some(3), 5, First, 10));
+        assert_eq!(Some(4), moved_selection(Some(3), 5, Last, 10));
+        assert_eq!(Some(3), moved_selection(Some(3), 5, Left, 10));
+        // selection beyond shrunk list is brought back in
+        assert_eq!(Some(1), moved_selection(Some(9), 3, Up, 10));
+    }
+
+    #[test]
+    fn count_repeats_movement() {
+        let mut app = harness().focused(Window::PaneWindow);
+        app.player.volume = 50;
+        app.appstate.music_result.list =
+            ["a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l"]
+                .map(music)
+                .to_vec();
+        app.appstate.music_pane_state.get_mut_ref().select(Some(0));
+
+        app.press("3<down>");
+        assert_eq!(Some(3), app.selected_music());
+        assert_eq!(None, app.appstate.count);
+        // single steps wrap around the end of list
+        app.press("12<up>");
+        assert_eq!(Some(3), app.selected_music());
+        // leading zero is not a count
+        app.press("0<down>");
+        assert_eq!(Some(4), app.selected_music());
+
+        // count is dropped by actions other than movement
+        app.press("4+");
+        assert_eq!(Some(4), app.selected_music());
+        assert_eq!(None, app.appstate.count);
+        app.press("<down>");
+        assert_eq!(Some(5), app.selected_music());
+
+        assert_eq!(vec!["volume 55"], app.player.calls);
+    }
+
+    #[test]
+    fn pane_navigation_scrolls() {
+        let mut app = harness().focused(Window::PaneWindow);
+        app.appstate.music_result.list =
+            ["Aagya", "Bimbakash", "Chiso", "Bistarai", "Euta Manchhe"]
+                .into_iter()
+                .map(|title| MusicUnit {
+                    title: title.to_string(),
+                    ..music("")
+                })
+                .collect();
+        app.appstate.fit_viewports(&GeometryData {
+            musicpane: tui::layout::Rect::new(0, 0, 10, 5),
+            ..Default::default()
+        });
+        assert_eq!(2, app.appstate.music_viewport.height);
+
+        let mut press = |key| {
+            app.press(key);
+            (app.selected_music(), app.appstate.music_viewport.offset)
+        };
+
+        assert_eq!((Some(0), 0), press("<down>"));
+        assert_eq!((Some(2), 1), press("<pageDown>"));
+        assert_eq!((Some(4), 3), press("<end>"));
+        assert_eq!((Some(3), 3), press("<up>"));
+        assert_eq!((Some(4), 3), press("<down>"));
+        assert_eq!((Some(0), 0), press("<down>"));
+        assert_eq!((Some(1), 0), press("b"));
+        assert_eq!((Some(3), 2), press("B"));
+        assert_eq!((Some(1), 1), press("b"));
+        // bound letters keep doing what they are bound to
+        assert_eq!((Some(1), 1), press("s"));
+        assert_eq!((Some(4), 3), press("e"));
+
+        // rows above viewport are hidden from widget
+        let provided =
+            <AppState as crate::gadgets::ui::Provider<MusicPaneState>>::provide(&app.appstate);
+        assert_eq!(Some(1), provided.get_ref().selected());
+    }
+
+    #[test]
+    fn preferences_shape_the_ui() {
+        let mut app = harness().focused(Window::SearchBar);
+        app.appstate.mode = Mode::Normal;
+        app.config.preferences.shortcuts = None;
+        app.config.preferences.playlist_pane = None;
+        app.config.preferences.music_pane = Some(PanePreferences {
+            local_result_count: 10,
+            remote_result_count: 6,
+        });
+
+        // short result is filled up to wanted count right away
+        let query = Query::SearchAll("aagya".parse().unwrap());
+        app.appstate.sent_query = Some(query.clone().get_final_query());
+        let list = ["a", "b", "c", "d"].into_iter().map(music).collect();
+        let result = QueryResult::page(&query, 0, list, Some("2".to_string()));
+        app.search(SearchResponse::Music(Ok(result)));
+        assert!(matches!(
+            app.requests().as_slice(),
+            [SearchRequest::NextPage(ResultKind::Music, _)]
+        ));
+
+        // hidden gadgets are skipped
+        app.press("<tab>");
+        assert_eq!(Window::PaneTab, app.appstate.active_window);
+        app.press("<backtab>");
+        assert_eq!(Window::SearchBar, app.appstate.active_window);
+
+        app.appstate.focus(Window::PaneWindow);
+        app.press("<right>");
+        assert_eq!(
+            PaneWindow::ArtistPane,
+            app.appstate.panetab_state.active_tab
+        );
+        app.press("<right>");
+        assert_eq!(PaneWindow::MusicPane, app.appstate.panetab_state.active_tab);
+    }
+
+    #[test]
+    fn shortcuts_run_into_panes() {
+        use user_config::preferences::shortcut::{ShortcutItem, ShortcutPreferences};
+
+        let mut app = harness();
+        let item = |label: &str, action| ShortcutItem {
+            label: label.to_string(),
+            icon: None,
+            action,
+        };
+        app.config.preferences.shortcuts = Some(ShortcutPreferences {
+            items: vec![
+                item("Chill", ShortcutAction::Search("lofi -live".to_string())),
+                item(
+                    "Mix",
+                    ShortcutAction::Playlist(
+                        "https://www.youtube.com/playlist?list=PL42&si=xyz".to_string(),
+                    ),
+                ),
+                item("Disk", ShortcutAction::LocalDir("/music".into())),
+                item("Hot", ShortcutAction::View(Shortcut::Trending)),
+                item("Later", ShortcutAction::View(Shortcut::Downloaded)),
+                item("Bad", ShortcutAction::Search("year:soon".to_string())),
+            ],
+        });
+
+        let run = |app: &mut TestApp, index| {
+            app.appstate.focus(Window::Shortcut);
+            app.appstate.notification = None;
+            app.appstate
+                .shortcut_list_state
+                .get_mut_ref()
+                .select(Some(index));
+            app.press("<enter>");
+            match app.requests().into_iter().next() {
+                Some(SearchRequest::Query(query)) => Some(query.get_final_query()),
+                _ => None,
+            }
+        };
+
+        let sent = run(&mut app, 0).unwrap();
+        assert_eq!("lofi -live", sent.as_str());
+        assert_eq!("lofi -live", app.appstate.altering_query.text());
+        assert_eq!(Window::PaneWindow, app.appstate.active_window);
+        assert_eq!(":playlist PL42", run(&mut app, 1).unwrap().as_str());
+        let local = run(&mut app, 2).unwrap();
+        assert!(local.is_local(), "{}", local.as_str());
+        assert_eq!(":trending", run(&mut app, 3).unwrap().as_str());
+
+        assert_eq!(None, run(&mut app, 4));
+        assert_eq!(
+            Some("Later is not available yet".to_string()),
+            app.appstate.notification
+        );
+        assert_eq!(None, run(&mut app, 5));
+        assert!(app.appstate.notification.is_some());
+    }
+
+    #[test]
+    fn scrolling_fetches_next_page() {
+        let mut app = harness().focused(Window::PaneWindow);
+        app.appstate.fit_viewports(&GeometryData {
+            musicpane: tui::layout::Rect::new(0, 0, 10, 5),
+            ..Default::default()
+        });
+        // first page is all that is wanted before scrolling
+        app.config.preferences.music_pane = Some(PanePreferences {
+            local_result_count: 4,
+            remote_result_count: 4,
+        });
+        let query = Query::SearchAll("aagya".parse().unwrap());
+        app.appstate.sent_query = Some(query.clone().get_final_query());
+        let page = |number, ids: &[&str], token: Option<&str>| {
+            let list = ids.iter().map(|id| music(id)).collect();
+            SearchResponse::Music(Ok(QueryResult::page(
+                &query,
+                number,
+                list,
+                token.map(str::to_string),
+            )))
+        };
+
+        app.search(page(0, &["a", "b", "c", "d"], Some("2")));
+        app.press("<down>");
+        assert!(app.requests().is_empty());
+        // within a page of the end
+        app.press("<down><down>");
+        assert!(app.appstate.music_result.loading);
+        match app.requests().as_slice() {
+            [SearchRequest::NextPage(ResultKind::Music, continuation)] => {
+                assert_eq!(1, continuation.page);
+                assert_eq!("2", continuation.token);
+            }
+            _ => panic!("Next page of music is requested"),
+        }
+        // not asked again while it is coming
+        app.press("<down>");
+        assert!(app.requests().is_empty());
+
+        app.search(page(1, &["e", "f"], None));
+        assert_eq!(6, app.appstate.music_result.list.len());
+        assert!(!app.appstate.music_result.loading);
+        assert_eq!(Some(3), app.selected_music());
+
+        // nothing more to fetch after last page
+        app.press("<down><down>");
+        assert!(app.requests().is_empty());
+    }
+
+    #[test]
+    fn filling_pane_gives_up() {
+        let mut app = harness();
+        app.config.preferences.music_pane = Some(PanePreferences {
+            local_result_count: 100,
+            remote_result_count: 100,
+        });
+        let send_page = |app: &mut TestApp, query: &Query, number, ids: &[&str]| {
+            app.appstate.sent_query = Some(query.clone().get_final_query());
+            let list = ids.iter().map(|id| music(id)).collect();
+            let result = QueryResult::page(query, number, list, Some("more".to_string()));
+            app.search(SearchResponse::Music(Ok(result)));
+            app.requests().len()
+        };
+
+        // page left empty by filters
+        let query = Query::SearchAll("aagya duration:<1s".parse().unwrap());
+        assert_eq!(1, send_page(&mut app, &query, 0, &["a"]));
+        assert_eq!(0, send_page(&mut app, &query, 1, &[]));
+
+        // rows keep coming, but only so many pages are fetched
+        let query = Query::SearchAll("aagya".parse().unwrap());
+        let requested = (0..FILL_PAGE_LIMIT)
+            .map(|number| send_page(&mut app, &query, number, &["a"]))
+            .sum::<usize>();
+        assert_eq!(FILL_PAGE_LIMIT, app.appstate.music_result.list.len());
+        assert_eq!(FILL_PAGE_LIMIT - 1, requested);
+    }
+
+    #[test]
+    fn late_results_are_dropped() {
+        let mut app = harness();
+        app.config.preferences.music_pane = Some(PanePreferences {
+            local_result_count: 10,
+            remote_result_count: 10,
+        });
+        let page = |text: &str, number, id| {
+            let query = Query::SearchAll(text.parse().unwrap());
+            let list = vec![music(id)];
+            SearchResponse::Music(Ok(QueryResult::page(
+                &query,
+                number,
+                list,
+                Some("more".to_string()),
+            )))
+        };
+
+        app.press("aagya<enter>");
+        app.appstate.focus(Window::SearchBar);
+        app.press("<ctrl>wkabira<enter>");
+        assert_eq!(2, app.requests().len());
+
+        // first page of search that was replaced is not shown
+        app.search(page("aagya", 0, "a"));
+        assert!(app.appstate.music_result.list.is_empty());
+        assert!(app.requests().is_empty());
+
+        app.search(page("kabira", 0, "k"));
+        assert_eq!("kabira", app.appstate.music_result.query.as_str());
+        assert_eq!(1, app.requests().len());
+
+        // dropped page adds nothing, so next one is not asked for again
+        app.search(SearchResponse::Music(Err("offline".to_string())));
+        app.search(page("aagya", 1, "b"));
+        assert_eq!(1, app.appstate.music_result.list.len());
+        assert!(app.requests().is_empty());
+    }
+
+    #[test]
+    fn config_changes_are_sent() {
+        let path = std::env::temp_dir().join(format!("ytui-watch-{}.json", std::process::id()));
+        let write = |content: &str, age: u64| {
+            fs::write(&path, content).unwrap();
+            // do not depend on timestamp granularity of filesystem
+            let modified = std::time::SystemTime::now() - Duration::from_secs(age);
+            fs::File::options()
+                .write(true)
+                .open(&path)
+                .and_then(|file| file.set_modified(modified))
+                .unwrap();
+        };
+        write("{}", 100);
+
+        let (sender, messages) = mpsc::channel();
+        let sources = ConfigSources {
+            user_file: Some(path.clone()),
+            ..Default::default()
+        };
+        spawn_config_watcher(sources, sender, Duration::from_millis(5));
+
+        write(
+            r#"{ "backend": { "instance": "https://changed.example" } }"#,
+            50,
+        );
+        let reloaded = match messages.recv_timeout(Duration::from_secs(5)) {
+            Ok(Message::Config(Ok(config))) => config,
+            _ => panic!("Expected config to be reloaded"),
+        };
+        assert_eq!("https://changed.example", reloaded.backend.instance);
+
+        write("{ not json", 10);
+        let reloaded = messages.recv_timeout(Duration::from_secs(5));
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(reloaded, Ok(Message::Config(Err(_)))));
+    }
+
+    #[test]
+    fn key_sequences_wait_for_rest() {
+        let mut app = harness().focused(Window::PaneWindow);
+        app.appstate.music_result.list = vec![music("a"), music("b"), music("c")];
+        app.appstate.music_pane_state.get_mut_ref().select(Some(2));
+
+        app.press("g");
+        assert_eq!(Some(2), app.selected_music());
+        let pending = app.appstate.pending_keys.as_ref().unwrap();
+        assert_eq!(vec![Key::from(KeyCode::Char('g'))], pending.keys);
+        assert_eq!(
+            vec![(
+                vec![Key::from(KeyCode::Char('g'))],
+                KeyboardAction::MoveInPaneWindow(MoveDirection::First)
+            )],
+            pending.continuations
+        );
+
+        // Tick before timeout changes nothing
+        app.reduce(Message::Tick);
+        assert!(app.appstate.pending_keys.is_some());
+
+        app.press("g");
+        assert_eq!(Some(0), app.selected_music());
+        assert!(app.appstate.pending_keys.is_none());
+
+        // Single `g` falls back to jumping once timed out
+        app.press("g");
+        assert_eq!(
+            None,
+            expire_pending_keys(&app.config.keyboard, &mut app.appstate, Instant::now())
+        );
+        let later = Instant::now() + SEQUENCE_TIMEOUT * 2;
+        assert_eq!(
+            Some(KeyboardAction::JumpToLetter('g')),
+            expire_pending_keys(&app.config.keyboard, &mut app.appstate, later)
+        );
+
+        // Broken sequence starts over with the last key
+        app.press("gG");
+        assert_eq!(Some(2), app.selected_music());
+        assert!(app.appstate.pending_keys.is_none());
+    }
+
+    #[test]
+    fn insert_mode_types_into_searchbar() {
+        let mut app = harness();
+        assert_eq!(Mode::Insert, app.appstate.mode);
+
+        for key in ["q", "<space>", "<shift>x", "<backspace>", "n"] {
+            assert!(app.press(key) == Flow::Continue);
+        }
+        assert_eq!("q n", app.appstate.altering_query.text());
+
+        // typing goes where cursor is
+        app.press("<left><left>u<end><ctrl>w");
+        assert_eq!("qu ", app.appstate.altering_query.text());
+
+        app.press("<esc>");
+        assert_eq!(
+            (Window::Shortcut, Mode::Normal),
+            (app.appstate.active_window, app.appstate.mode)
+        );
+
+        // Editing keys are whatever config says
+        app.config.keyboard = [(
+            (vec![KeyCode::Esc.into()], None, Mode::Insert).into(),
+            KeyboardAction::PopSearchQuery,
+        )]
+        .into_iter()
+        .collect::<std::collections::HashMap<_, _>>()
+        .into();
+        app.appstate.focus(Window::SearchBar);
+        app.press("<esc>");
+        assert_eq!("qu", app.appstate.altering_query.text());
+        assert_eq!(Window::SearchBar, app.appstate.active_window);
+
+        assert!(app.player.calls.is_empty());
+    }
+
+    #[test]
+    fn searches_are_sent_and_recalled() {
+        let mut app = harness();
+
+        app.press("aagya<space><enter>");
+        assert_eq!(Window::PaneWindow, app.appstate.active_window);
+        assert_eq!(["aagya"], app.appstate.search_history.entries());
+
+        app.appstate.focus(Window::SearchBar);
+        app.appstate.altering_query.set_text("kabira".to_string());
+        app.press("<enter>");
+        app.appstate.focus(Window::SearchBar);
+
+        // up goes to older searches, down back to what was typed
+        app.appstate.altering_query.set_text("new".to_string());
+        app.press("<up><up>");
+        assert_eq!("aagya", app.appstate.altering_query.text());
+        app.press("<down><down>");
+        assert_eq!("new", app.appstate.altering_query.text());
+
+        // Typing while searching history looks for it
+        app.press("<ctrl>rAA");
+        assert_eq!(Some("aagya"), app.appstate.search_history.found());
+        assert_eq!("new", app.appstate.altering_query.text());
+        app.press("<enter>");
+        assert!(app.appstate.search_history.search().is_none());
+        assert_eq!(["kabira", "aagya"], app.appstate.search_history.entries());
+
+        // Query that can not be read is kept in searchbar to be fixed
+        app.appstate.focus(Window::SearchBar);
+        app.appstate
+            .altering_query
+            .set_text("aagya duration:5".to_string());
+        app.press("<enter>");
+        assert_eq!(Window::SearchBar, app.appstate.active_window);
+        assert!(app
+            .appstate
+            .notification
+            .as_ref()
+            .unwrap()
+            .starts_with("Search not sent"));
+        assert_eq!(["kabira", "aagya"], app.appstate.search_history.entries());
+
+        let sent = app
+            .requests()
+            .into_iter()
+            .map(|request| match request {
+                SearchRequest::Query(Query::SearchAll(search)) => search.to_string(),
+                _ => panic!("Only searches are requested"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["aagya", "kabira", "aagya"], sent);
+    }
+
+    #[test]
+    fn suggestions_follow_typing() {
+        let mut app = harness();
+        for entry in ["kabira", "bartika eam rai", "bartika live"] {
+            app.appstate.search_history.record(entry).unwrap();
+        }
+        let texts = |appstate: &AppState| {
+            let list = appstate.suggestions.list();
+            list.iter().map(|s| s.text.clone()).collect::<Vec<_>>()
+        };
+
+        app.press("ba");
+        assert_eq!(
+            vec!["bartika live", "bartika eam rai"],
+            texts(&app.appstate)
+        );
+        // backend is asked after a pause in typing, not on every key
+        assert!(app.appstate.next_deadline().is_some());
+
+        app.search(SearchResponse::Suggestions(
+            "ba".to_string(),
+            Ok(vec!["bajre".to_string()]),
+        ));
+        assert_eq!(
+            vec!["bartika live", "bartika eam rai", "bajre"],
+            texts(&app.appstate)
+        );
+
+        app.press("<down><down>");
+        assert_eq!("bartika eam rai", app.appstate.altering_query.text());
+        app.press("<up><up>");
+        assert_eq!("ba", app.appstate.altering_query.text());
+
+        app.press("<down><enter>");
+        assert!(app.appstate.suggestions.is_empty());
+        assert!(matches!(
+            app.requests().as_slice(),
+            [SearchRequest::Query(Query::SearchAll(search))] if search.to_string() == "bartika live"
+        ));
+
+        // with nothing suggested arrows go through history
+        app.appstate.focus(Window::SearchBar);
+        app.appstate.altering_query.set_text(String::new());
+        app.press("<up>");
+        assert_eq!("bartika live", app.appstate.altering_query.text());
+        assert!(app.appstate.suggestions.is_empty());
+    }
+
+    #[test]
+    fn messages_update_appstate() {
+        let mut app = harness();
+
+        let query = Query::SearchMusic("something".parse().unwrap());
+        app.appstate.sent_query = Some(query.clone().get_final_query());
+        let messages = [
+            Message::Player(PlayerEvent::TitleChanged("Aagya".to_string())),
+            Message::Player(PlayerEvent::PositionChanged(Duration::from_secs(65))),
+            Message::Search(SearchResponse::Music(Ok(QueryResult::new(
+                &query,
+                vec![MusicUnit {
+                    title: "Found".to_string(),
+                    artist: "Someone".to_string(),
+                    duration: Some(Duration::from_secs(60)),
+                    id: "id1".to_string(),
+                    ..Default::default()
+                }],
+            )))),
+            Message::Search(SearchResponse::Playlist(Err("offline".to_string()))),
+            Message::Tick,
+        ];
+        for message in messages {
+            app.reduce(message);
+        }
+
+        let appstate = &app.appstate;
+        assert_eq!(Some("Aagya".to_string()), appstate.player_state.title);
+        assert_eq!(
+            Some(Duration::from_secs(65)),
+            appstate.player_state.position
+        );
+        assert_eq!(1, appstate.music_result.list.len());
+        assert_eq!("something type:music", appstate.music_result.query.as_str());
+        assert_eq!(Some("offline".to_string()), appstate.notification);
+    }
+
+    #[test]
+    fn command_line_runs_commands() {
+        let mut app = harness().focused(Window::Shortcut);
+
+        // `q` goes into the line instead of quitting
+        app.press(":volq<backspace>");
+        assert_eq!(Mode::Command, app.appstate.mode);
+        assert_eq!("vol", app.appstate.command_line.as_ref().unwrap().input);
+        app.press("ume<space>40");
+        assert!(app.press("<enter>") == Flow::Continue);
+        assert!(app.appstate.command_line.is_none());
+        assert_eq!(Mode::Normal, app.appstate.mode);
+
+        let dark = app.config.themes["dark"].clone();
+        assert!(app.press(":theme dark<enter>") == Flow::UseTheme(dark));
+        assert!(app.press(":theme bright<enter>") == Flow::Continue);
+        assert_eq!(
+            Some("No theme named bright".to_string()),
+            app.appstate.notification
+        );
+
+        // Erasing empty line gives up on it
+        app.press(":<backspace>");
+        assert!(app.appstate.command_line.is_none());
+
+        // Picked from palette, actions needing argument ask for it
+        app.press("<ctrl>psetvol<enter>");
+        let command_line = app.appstate.command_line.as_ref().unwrap();
+        assert_eq!(CommandLineKind::Prompt, command_line.kind);
+        assert_eq!("SetVolume ", command_line.input);
+        app.press("70<enter>");
+        assert!(app.press("<ctrl>pquit<enter>") == Flow::Quit);
+
+        assert_eq!(vec!["volume 40", "volume 70"], app.player.calls);
+    }
+
+    #[test]
+    fn popups_keep_focus() {
+        let mut app = harness().focused(Window::PaneWindow);
+        let press = |app: &mut TestApp, key| {
+            app.press(key);
+            let appstate = &app.appstate;
+            (appstate.active_window, appstate.mode, appstate.popups.len())
+        };
+
+        assert_eq!((Window::Popup, Mode::Command, 1), press(&mut app, "?"));
+        // Keys that move between windows are taken by popup
+        assert_eq!((Window::Popup, Mode::Command, 1), press(&mut app, "<tab>"));
+        assert_eq!(
+            (Window::PaneWindow, Mode::Normal, 0),
+            press(&mut app, "<esc>")
+        );
+
+        // Stacked popups close one at a time
+        app.appstate
+            .open_command_line(CommandLine::new(CommandLineKind::Palette));
+        app.appstate
+            .open_command_line(CommandLine::new(CommandLineKind::Help));
+        assert_eq!((Window::Popup, Mode::Command, 1), press(&mut app, "<esc>"));
+        assert!(matches!(
+            &app.appstate.popups[0].popup,
+            crate::types::state::Popup::Picker(picker) if picker.kind == CommandLineKind::Palette
+        ));
+        assert_eq!(
+            (Window::PaneWindow, Mode::Normal, 0),
+            press(&mut app, "<esc>")
+        );
+    }
+
+    #[test]
+    fn details_popup_scrolls() {
+        let mut app = harness().focused(Window::PaneWindow);
+        app.appstate.music_result.list = vec![MusicUnit {
+            views: Some(1234),
+            description: Some("line one\nline two".to_string()),
+            ..music("id1")
+        }];
+        app.appstate.music_pane_state.get_mut_ref().select(Some(0));
+        // 28 x 4 inside borders
+        app.appstate.fit_viewports(&GeometryData {
+            popup: tui::layout::Rect::new(0, 0, 30, 6),
+            ..Default::default()
+        });
+        let scroll = |app: &mut TestApp, key| {
+            app.press(key);
+            match app.appstate.popups.last() {
+                Some(PopupLayer {
+                    popup: Popup::Details(details),
+                    ..
+                }) => Some(details.scroll),
+                _ => None,
+            }
+        };
+
+        assert_eq!(Some(0), scroll(&mut app, "i"));
+        assert_eq!(
+            (Window::Popup, Mode::Normal),
+            (app.appstate.active_window, app.appstate.mode)
+        );
+        assert_eq!(Some(1), scroll(&mut app, "<down>"));
+        assert_eq!(Some(3), scroll(&mut app, "<end>"));
+        assert_eq!(Some(3), scroll(&mut app, "<pageDown>"));
+        assert_eq!(Some(2), scroll(&mut app, "<up>"));
+
+        assert_eq!(None, scroll(&mut app, "<esc>"));
+        assert_eq!(Window::PaneWindow, app.appstate.active_window);
+
+        // Artists have no details to show
+        app.appstate.panetab_state.active_tab = PaneWindow::ArtistPane;
+        assert_eq!(None, scroll(&mut app, "i"));
+    }
+}
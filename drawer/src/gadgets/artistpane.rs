@@ -0,0 +1,128 @@
+use crate::gadgets::state::AppState;
+use crate::gadgets::state::GeometryData;
+use crate::gadgets::unit::ArtistUnit;
+use crate::gadgets::window::PaneWindow;
+use tui::layout::Constraint;
+use tui::style::Modifier;
+use tui::style::Style;
+
+use tui::widgets::Block;
+use tui::widgets::BorderType;
+use tui::widgets::Borders;
+use tui::widgets::Row;
+use tui::widgets::Table;
+use user_config::preferences::theme::Theme;
+
+pub trait ArtistpaneAppdata {
+    fn is_artistpane_active(&self) -> bool;
+    fn selected(&self) -> Option<usize>;
+    fn artist_list(&self) -> &[ArtistUnit];
+    fn get_title(&self) -> String {
+        "Artists ".to_string()
+    }
+    /// Index of first row to be shown
+    fn scroll_offset(&self) -> usize {
+        0
+    }
+    /// If more of the list is being fetched
+    fn is_loading(&self) -> bool {
+        false
+    }
+}
+
+pub trait ArtistpaneGeometry {
+    fn column_division(&self) -> &[Constraint];
+    fn column_spacing(&self) -> u16;
+}
+
+impl ArtistpaneAppdata for AppState {
+    fn is_artistpane_active(&self) -> bool {
+        self.panetab_state.active_tab == PaneWindow::ArtistPane
+    }
+    fn selected(&self) -> Option<usize> {
+        self.artist_pane_state.get_ref().selected()
+    }
+    fn artist_list(&self) -> &[ArtistUnit] {
+        &self.artist_result.list
+    }
+    fn scroll_offset(&self) -> usize {
+        self.artist_viewport.offset
+    }
+    fn is_loading(&self) -> bool {
+        self.artist_result.loading
+    }
+    fn get_title(&self) -> String {
+        self.artist_result.query.pane_title("Artists")
+    }
+}
+
+impl ArtistpaneGeometry for GeometryData {
+    fn column_division(&self) -> &[Constraint] {
+        &self.artistpane_division.splits
+    }
+    fn column_spacing(&self) -> u16 {
+        self.artistpane_division.spacing
+    }
+}
+
+pub fn get_artistpane_list<'a, A, G>(appdata: &A, geometry: &'a G, theme: &Theme) -> Table<'a>
+where
+    A: ArtistpaneAppdata,
+    G: ArtistpaneGeometry,
+{
+    let border_style = if appdata.is_artistpane_active() {
+        Style::default()
+            .fg(theme.active_color.into())
+            .add_modifier(Modifier::ITALIC)
+    } else {
+        Style::default()
+            .fg(theme.inactive_color.into())
+            .add_modifier(Modifier::ITALIC)
+    };
+
+    let header_style = Style::default()
+        .fg(theme.inactive_color.into())
+        .add_modifier(Modifier::BOLD);
+    let base_style = Style::default().fg(theme.base_color.into());
+    let highlight_style = Style::default().fg(theme.highlight_color.into());
+    let loading_style = Style::default()
+        .fg(theme.inactive_color.into())
+        .add_modifier(Modifier::ITALIC);
+
+    let block = Block::default()
+        .title(appdata.get_title())
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(border_style);
+
+    let rows = appdata
+        .artist_list()
+        .iter()
+        .skip(appdata.scroll_offset())
+        .map(
+            |ArtistUnit {
+                 name,
+                 subscriber_count,
+                 ..
+             }| { Row::new(vec![name.clone(), subscriber_count.to_string()]) },
+        )
+        // footer while next page is on it's way
+        .chain(
+            appdata
+                .is_loading()
+                .then(|| Row::new(vec!["loading…"]).style(loading_style)),
+        )
+        .collect::<Vec<Row>>();
+
+    let header = Row::new(vec!["Name", "Subscribers"]).style(header_style);
+    let widths = geometry.column_division();
+    let col_spacing = geometry.column_spacing();
+
+    Table::new(rows)
+        .column_spacing(col_spacing)
+        .widths(widths)
+        .header(header)
+        .style(base_style)
+        .highlight_style(highlight_style)
+        .block(block)
+}
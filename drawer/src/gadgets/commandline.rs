@@ -0,0 +1,157 @@
+use crate::gadgets::state::{AppState, CommandLine, CommandLineKind};
+use tui::layout::Rect;
+use tui::style::Modifier;
+use tui::style::Style;
+use tui::text::{Span, Spans};
+use tui::widgets::Block;
+use tui::widgets::BorderType;
+use tui::widgets::Borders;
+use tui::widgets::List;
+use tui::widgets::ListItem;
+use tui::widgets::Paragraph;
+use user_config::preferences::theme::Theme;
+
+pub trait CommandlineAppdata {
+    /// The `:` prompt. None if it is not open
+    fn prompt(&self) -> Option<CommandLine> {
+        None
+    }
+}
+
+impl CommandlineAppdata for AppState {
+    fn prompt(&self) -> Option<CommandLine> {
+        self.command_line.clone()
+    }
+}
+
+/// Places for (input line, choices) of prompt
+/// Prompt takes place of `bottom` with it's choices just above it
+pub fn get_prompt_rects(prompt: &CommandLine, area: Rect, bottom: Rect) -> (Rect, Rect) {
+    let input = if bottom.area() > 1 {
+        bottom
+    } else {
+        Rect {
+            y: area.y + area.height.saturating_sub(3),
+            height: area.height.min(3),
+            ..area
+        }
+    };
+    let list_height = match prompt.choices.len() as u16 {
+        0 => 0,
+        // borders on both side
+        choices => (choices + 2).min(input.y - area.y),
+    };
+    let list = Rect {
+        y: input.y - list_height,
+        height: list_height,
+        ..input
+    };
+    (input, list)
+}
+
+/// Places for (input line, choices) of palette or help shown in popup
+pub fn get_picker_rects(popup: Rect) -> (Rect, Rect) {
+    let input_height = popup.height.min(3);
+    let input = Rect {
+        height: input_height,
+        ..popup
+    };
+    let list = Rect {
+        y: popup.y + input_height,
+        height: popup.height - input_height,
+        ..popup
+    };
+    (input, list)
+}
+
+pub fn get_commandline<'a>(command_line: &CommandLine, theme: &Theme) -> Paragraph<'a> {
+    let (title, lead) = match command_line.kind {
+        CommandLineKind::Prompt => ("Command ".to_string(), ":"),
+        CommandLineKind::Palette => ("Actions ".to_string(), "> "),
+        CommandLineKind::Help => (
+            format!("Keys of ytui-music {} ", env!("CARGO_PKG_VERSION")),
+            "/ ",
+        ),
+    };
+    let line = Spans::from(vec![
+        Span::styled(lead, Style::default().fg(theme.highlight_color.into())),
+        Span::raw(command_line.input.clone()),
+    ]);
+
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(theme.active_color.into()));
+
+    Paragraph::new(line)
+        .style(Style::default().fg(theme.base_color.into()))
+        .block(block)
+}
+
+/// List of choices fitting in `rows`, scrolled to keep selected one visible
+pub fn get_commandline_choices<'a>(
+    command_line: &CommandLine,
+    theme: &Theme,
+    rows: usize,
+) -> List<'a> {
+    let selected = command_line.selected;
+    let is_help = command_line.kind == CommandLineKind::Help;
+    let offset = match selected {
+        Some(selected) if rows > 0 && selected >= rows => selected + 1 - rows,
+        _ => 0,
+    };
+    let hint_style = Style::default()
+        .fg(theme.inactive_color.into())
+        .add_modifier(Modifier::ITALIC);
+
+    // keys of help are lined up so that actions start at same column
+    let keys_width = if is_help {
+        command_line
+            .choices
+            .iter()
+            .filter(|(_, action)| !action.is_empty())
+            .map(|(keys, _)| keys.chars().count())
+            .max()
+            .unwrap_or_default()
+    } else {
+        0
+    };
+
+    let list_items = command_line
+        .choices
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .map(|(i, (choice, hint))| {
+            // Section titles of help are the only rows without hint there
+            if is_help && hint.is_empty() {
+                let title_style = Style::default()
+                    .fg(theme.active_color.into())
+                    .add_modifier(Modifier::BOLD);
+                return ListItem::new(Span::styled(choice.clone(), title_style));
+            }
+            let choice_color = if selected == Some(i) {
+                theme.highlight_color
+            } else {
+                theme.base_color
+            };
+            let mut spans = vec![Span::styled(
+                format!("{choice:keys_width$}"),
+                Style::default().fg(choice_color.into()),
+            )];
+            if !hint.is_empty() {
+                spans.push(Span::raw("  "));
+                spans.push(Span::styled(hint.clone(), hint_style));
+            }
+            ListItem::new(Spans::from(spans))
+        })
+        .collect::<Vec<_>>();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(theme.active_color.into()));
+
+    List::new(list_items).block(block)
+}
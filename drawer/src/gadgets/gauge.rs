@@ -1,6 +1,8 @@
 use crate::gadgets::state::AppState;
 use crate::gadgets::state::PlayerInfo;
 use crate::gadgets::window::Window;
+use crate::types::utils;
+use std::time::Duration;
 use tui::layout::Alignment;
 use tui::style::Modifier;
 use tui::style::Style;
@@ -13,43 +15,76 @@ use user_config::preferences::theme::Theme;
 pub trait GaugeAppData {
     fn is_gauge_active(&self) -> bool;
     fn music_title(&self) -> String;
-    fn music_total_duration(&self) -> String;
-    fn played_music_duration(&self) -> String;
+    fn music_total_duration(&self) -> Option<Duration>;
+    fn played_music_duration(&self) -> Option<Duration>;
+    /// If some, this is shown in place of music title
+    fn notification(&self) -> Option<String> {
+        None
+    }
+    /// Shuffle and repeat state. Shown after durations if not empty
+    fn playback_modes(&self) -> String {
+        String::new()
+    }
 }
 
 impl GaugeAppData for AppState {
     fn is_gauge_active(&self) -> bool {
         self.active_window == Window::Gauge
     }
-    fn music_total_duration(&self) -> String {
+    fn music_total_duration(&self) -> Option<Duration> {
         self.playing_track_duration()
     }
-    fn played_music_duration(&self) -> String {
+    fn played_music_duration(&self) -> Option<Duration> {
         self.playing_track_completed()
     }
     fn music_title(&self) -> String {
         self.playing_track_title()
     }
+    fn notification(&self) -> Option<String> {
+        self.notification.clone()
+    }
+    fn playback_modes(&self) -> String {
+        let repeat: &str = self.queue.repeat_mode().into();
+        if self.queue.is_shuffled() {
+            format!("shuffle, {repeat}")
+        } else {
+            repeat.to_string()
+        }
+    }
 }
 
-// TODO:
-// receive actual Duration type
-fn get_played_percent(total: String, played: String) -> u16 {
-    let (_, _) = (total, played);
-    21
+/// Part of track that is played, 0 if not known
+fn get_played_percent(total: Option<Duration>, played: Option<Duration>) -> u16 {
+    match (total, played) {
+        (Some(total), Some(played)) if !total.is_zero() => {
+            let percent = played.as_secs_f64() / total.as_secs_f64() * 100.0;
+            percent.clamp(0.0, 100.0) as u16
+        }
+        _ => 0,
+    }
 }
 
 pub fn get_gauge<'a, A>(appdata: &A, theme: &Theme) -> Gauge<'a>
 where
     A: GaugeAppData,
 {
-    let playing_title = appdata.music_title();
-    let combined_duration = format!(
-        "{played} / {total}",
-        played = appdata.played_music_duration(),
-        total = appdata.music_total_duration()
+    let playing_title = appdata
+        .notification()
+        .unwrap_or_else(|| appdata.music_title());
+    let (played, total) = (
+        appdata.played_music_duration(),
+        appdata.music_total_duration(),
+    );
+    let mut combined_duration = format!(
+        "{} / {}",
+        utils::format_maybe_duration(played),
+        utils::format_maybe_duration(total)
     );
-    let played_percent = get_played_percent(String::new(), String::new());
+    let modes = appdata.playback_modes();
+    if !modes.is_empty() {
+        combined_duration.push_str(&format!(" [{modes}]"));
+    }
+    let played_percent = get_played_percent(total, played);
     let base_style = Style::default().fg(theme.base_color.into());
     let gauge_style = Style::default().fg(theme.inactive_color.into());
     let border_style = if appdata.is_gauge_active() {
@@ -76,3 +111,20 @@ where
         .percent(played_percent)
         .block(block)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn played_percent() {
+        let secs = |s| Some(Duration::from_secs(s));
+        assert_eq!(0, get_played_percent(None, secs(10)));
+        assert_eq!(0, get_played_percent(secs(200), None));
+        assert_eq!(0, get_played_percent(secs(0), secs(0)));
+        assert_eq!(25, get_played_percent(secs(200), secs(50)));
+        assert_eq!(99, get_played_percent(secs(200), secs(199)));
+        // position may be reported past the end
+        assert_eq!(100, get_played_percent(secs(200), secs(201)));
+    }
+}
@@ -0,0 +1,97 @@
+use crate::gadgets::state::AppState;
+use tui::layout::Rect;
+use tui::style::Modifier;
+use tui::style::Style;
+use tui::text::{Span, Spans};
+use tui::widgets::Block;
+use tui::widgets::BorderType;
+use tui::widgets::Borders;
+use tui::widgets::Paragraph;
+use user_config::keyboard::Key;
+use user_config::preferences::theme::Theme;
+
+pub trait KeyhintAppdata {
+    /// Count and keys of unfinished sequence. None if nothing is pending
+    fn pending_keys(&self) -> Option<String> {
+        None
+    }
+    /// (keys that may follow, what they do)
+    fn key_continuations(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+}
+
+impl KeyhintAppdata for AppState {
+    fn pending_keys(&self) -> Option<String> {
+        if self.count.is_none() && self.pending_keys.is_none() {
+            return None;
+        }
+        let count = self.count.map(|count| count.to_string());
+        let keys = self
+            .pending_keys
+            .as_ref()
+            .map(|pending| Key::sequence_to_string(&pending.keys));
+        Some(count.unwrap_or_default() + &keys.unwrap_or_default())
+    }
+    fn key_continuations(&self) -> Vec<(String, String)> {
+        self.pending_keys
+            .iter()
+            .flat_map(|pending| pending.continuations.iter())
+            .map(|(keys, action)| (Key::sequence_to_string(keys), action.to_string()))
+            .collect()
+    }
+}
+
+/// Place for hint in bottom right corner of `area`
+/// Returns None if there is nothing to hint about
+pub fn get_keyhint_rect<A: KeyhintAppdata>(appdata: &A, area: Rect) -> Option<Rect> {
+    let title_width = appdata.pending_keys()?.chars().count();
+    let continuations = appdata.key_continuations();
+    let content_width = continuations
+        .iter()
+        .map(|(keys, action)| keys.chars().count() + action.chars().count() + 3)
+        .max()
+        .unwrap_or_default()
+        .max(title_width);
+
+    // borders on each side
+    let width = (content_width as u16 + 2).min(area.width);
+    let height = (continuations.len() as u16 + 2).min(area.height);
+    Some(Rect {
+        x: area.x + area.width - width,
+        y: area.y + area.height - height,
+        width,
+        height,
+    })
+}
+
+pub fn get_keyhint<'a, A>(appdata: &A, theme: &Theme) -> Paragraph<'a>
+where
+    A: KeyhintAppdata,
+{
+    let key_style = Style::default()
+        .fg(theme.highlight_color.into())
+        .add_modifier(Modifier::BOLD);
+    let base_style = Style::default().fg(theme.base_color.into());
+    let border_style = Style::default().fg(theme.active_color.into());
+
+    let lines = appdata
+        .key_continuations()
+        .into_iter()
+        .map(|(keys, action)| {
+            Spans::from(vec![
+                Span::styled(keys, key_style),
+                Span::raw(" → "),
+                Span::raw(action),
+            ])
+        })
+        .collect::<Vec<_>>();
+
+    let block = Block::default()
+        .title(appdata.pending_keys().unwrap_or_default())
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(border_style);
+
+    Paragraph::new(lines).style(base_style).block(block)
+}
@@ -1,8 +1,13 @@
+pub mod artistpane;
+pub mod commandline;
 pub mod gauge;
+pub mod keyhint;
 pub mod musicpane;
 pub mod panetab;
 pub mod playlistpane;
+pub mod popup;
 pub mod searchbar;
 pub mod shortcut;
+pub mod suggestion;
 pub mod ui;
 pub use crate::types::{query, state, unit, window};
@@ -2,6 +2,7 @@ use crate::gadgets::state::AppState;
 use crate::gadgets::state::GeometryData;
 use crate::gadgets::unit::MusicUnit;
 use crate::gadgets::window::PaneWindow;
+use crate::types::utils;
 use tui::layout::Constraint;
 use tui::style::Modifier;
 use tui::style::Style;
@@ -17,8 +18,16 @@ pub trait MusicpaneAppdata {
     fn is_musicpane_active(&self) -> bool;
     fn selected(&self) -> Option<usize>;
     fn music_list(&self) -> &[MusicUnit];
-    fn get_title(&self) -> &'static str {
-        "Musics "
+    fn get_title(&self) -> String {
+        "Musics ".to_string()
+    }
+    /// Index of first row to be shown
+    fn scroll_offset(&self) -> usize {
+        0
+    }
+    /// If more of the list is being fetched
+    fn is_loading(&self) -> bool {
+        false
     }
 }
 
@@ -37,6 +46,15 @@ impl MusicpaneAppdata for AppState {
     fn music_list(&self) -> &[MusicUnit] {
         &self.music_result.list
     }
+    fn scroll_offset(&self) -> usize {
+        self.music_viewport.offset
+    }
+    fn is_loading(&self) -> bool {
+        self.music_result.loading
+    }
+    fn get_title(&self) -> String {
+        self.music_result.query.pane_title("Musics")
+    }
 }
 
 impl MusicpaneGeometry for GeometryData {
@@ -68,6 +86,9 @@ where
         .add_modifier(Modifier::BOLD);
     let base_style = Style::default().fg(theme.base_color.into());
     let highlight_style = Style::default().fg(theme.highlight_color.into());
+    let loading_style = Style::default()
+        .fg(theme.inactive_color.into())
+        .add_modifier(Modifier::ITALIC);
 
     let block = Block::default()
         .border_type(BorderType::Rounded)
@@ -78,12 +99,26 @@ where
     let rows = appdata
         .music_list()
         .iter()
+        .skip(appdata.scroll_offset())
         .map(
             |MusicUnit {
                  title,
                  duration,
                  artist,
-             }| { Row::new(vec![title.clone(), artist.clone(), duration.clone()]) },
+                 ..
+             }| {
+                Row::new(vec![
+                    title.clone(),
+                    artist.clone(),
+                    utils::format_maybe_duration(*duration),
+                ])
+            },
+        )
+        // footer while next page is on it's way
+        .chain(
+            appdata
+                .is_loading()
+                .then(|| Row::new(vec!["loading…"]).style(loading_style)),
         )
         .collect::<Vec<Row>>();
 
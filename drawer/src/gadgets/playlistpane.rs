@@ -17,8 +17,16 @@ pub trait PlaylistpaneAppdata {
     fn is_playlistpane_active(&self) -> bool;
     fn selected(&self) -> Option<usize>;
     fn playlist_list(&self) -> &[PlaylistUnit];
-    fn get_title(&self) -> &'static str {
-        "Playlists "
+    fn get_title(&self) -> String {
+        "Playlists ".to_string()
+    }
+    /// Index of first row to be shown
+    fn scroll_offset(&self) -> usize {
+        0
+    }
+    /// If more of the list is being fetched
+    fn is_loading(&self) -> bool {
+        false
     }
 }
 
@@ -37,6 +45,15 @@ impl PlaylistpaneAppdata for AppState {
     fn playlist_list(&self) -> &[PlaylistUnit] {
         &self.playlist_result.list
     }
+    fn scroll_offset(&self) -> usize {
+        self.playlist_viewport.offset
+    }
+    fn is_loading(&self) -> bool {
+        self.playlist_result.loading
+    }
+    fn get_title(&self) -> String {
+        self.playlist_result.query.pane_title("Playlists")
+    }
 }
 
 impl PlaylistpaneGeometry for GeometryData {
@@ -68,6 +85,9 @@ where
         .add_modifier(Modifier::BOLD);
     let base_style = Style::default().fg(theme.base_color.into());
     let highlight_style = Style::default().fg(theme.highlight_color.into());
+    let loading_style = Style::default()
+        .fg(theme.inactive_color.into())
+        .add_modifier(Modifier::ITALIC);
 
     let block = Block::default()
         .border_type(tui::widgets::BorderType::Rounded)
@@ -79,15 +99,23 @@ where
     let rows = appdata
         .playlist_list()
         .iter()
+        .skip(appdata.scroll_offset())
         .map(
             |PlaylistUnit {
                  song_count,
                  title,
                  creator: artist,
+                 ..
              }| {
                 Row::new(vec![song_count.to_string(), title.clone(), artist.clone()])
             },
         )
+        // footer while next page is on it's way
+        .chain(
+            appdata
+                .is_loading()
+                .then(|| Row::new(vec!["", "loading…"]).style(loading_style)),
+        )
         .collect::<Vec<Row>>();
 
     let header = Row::new(vec!["count", "Title", "Created by"]).style(header_style);
@@ -0,0 +1,56 @@
+use crate::gadgets::state::{AppState, Details, Popup};
+use tui::layout::Rect;
+use tui::style::{Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, BorderType, Borders, Paragraph};
+use user_config::preferences::theme::Theme;
+
+pub trait PopupAppdata {
+    /// Open popups, bottom one first
+    fn popups(&self) -> Vec<Popup> {
+        Vec::new()
+    }
+}
+
+impl PopupAppdata for AppState {
+    fn popups(&self) -> Vec<Popup> {
+        self.popups
+            .iter()
+            .map(|layer| layer.popup.clone())
+            .collect()
+    }
+}
+
+/// Rows of details that fit in `area`, starting from where it is scrolled to
+/// Nothing is cut short so that long values and links can be copied whole
+pub fn get_details<'a>(details: &Details, theme: &Theme, area: Rect) -> Paragraph<'a> {
+    let (width, height) = (
+        area.width.saturating_sub(2) as usize,
+        area.height.saturating_sub(2) as usize,
+    );
+    let label_style = Style::default()
+        .fg(theme.inactive_color.into())
+        .add_modifier(Modifier::BOLD);
+    let text_style = Style::default().fg(theme.base_color.into());
+
+    let lines = details
+        .rows(width)
+        .into_iter()
+        .skip(details.scroll)
+        .take(height)
+        .map(|(label, text)| {
+            Spans::from(vec![
+                Span::styled(label, label_style),
+                Span::styled(text, text_style),
+            ])
+        })
+        .collect::<Vec<_>>();
+
+    let block = Block::default()
+        .title("Details ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(theme.active_color.into()));
+
+    Paragraph::new(lines).block(block)
+}
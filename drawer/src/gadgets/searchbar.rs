@@ -1,5 +1,7 @@
+use crate::gadgets::query::{QueryError, SearchQuery};
 use crate::gadgets::state::AppState;
 use crate::gadgets::window::Window;
+use std::ops::Range;
 use tui::style::Modifier;
 use tui::style::Style;
 use tui::text::{Span, Spans, Text};
@@ -7,16 +9,163 @@ use tui::widgets::Block;
 use tui::widgets::BorderType;
 use tui::widgets::Borders;
 use tui::widgets::Paragraph;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+use user_config::action::Motion;
+use user_config::preferences::searchbar::SearchbarPreferences;
 use user_config::preferences::theme::Theme;
 
+/// Single line of text with a cursor in it
+///
+/// Cursor moves and text is removed a grapheme at a time
+/// so that emoji and combined characters stay whole
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineEditor {
+    text: String,
+    /// Byte offset in text, always at a grapheme boundary
+    cursor: usize,
+}
+
+impl LineEditor {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Byte offset of cursor in text
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Replace whole text, leaving cursor at the end
+    pub fn set_text(&mut self, text: String) {
+        self.cursor = text.len();
+        self.text = text;
+    }
+
+    pub fn insert(&mut self, ch: char) {
+        self.text.insert(self.cursor, ch);
+        self.cursor += ch.len_utf8();
+    }
+
+    pub fn move_cursor(&mut self, motion: Motion) {
+        self.cursor = self.target(motion);
+    }
+
+    /// Remove text between cursor and where motion would move it
+    pub fn delete(&mut self, motion: Motion) {
+        let target = self.target(motion);
+        let (from, to) = (self.cursor.min(target), self.cursor.max(target));
+        self.text.replace_range(from..to, "");
+        self.cursor = from;
+    }
+
+    /// Where cursor goes with this motion
+    fn target(&self, motion: Motion) -> usize {
+        let before = &self.text[..self.cursor];
+        let after = &self.text[self.cursor..];
+        let is_space = |grapheme: &&str| grapheme.chars().all(char::is_whitespace);
+        match motion {
+            Motion::Left => before
+                .grapheme_indices(true)
+                .next_back()
+                .map_or(0, |(i, _)| i),
+            Motion::Right => self.cursor + after.graphemes(true).next().map_or(0, str::len),
+            // Spaces are skipped first, then rest of the word
+            Motion::WordLeft => {
+                let graphemes = before.graphemes(true).rev();
+                let spaces = graphemes.clone().take_while(is_space);
+                let word = graphemes.skip_while(is_space).take_while(|g| !is_space(g));
+                self.cursor - spaces.chain(word).map(str::len).sum::<usize>()
+            }
+            Motion::WordRight => {
+                let graphemes = after.graphemes(true);
+                let spaces = graphemes.clone().take_while(is_space);
+                let word = graphemes.skip_while(is_space).take_while(|g| !is_space(g));
+                self.cursor + spaces.chain(word).map(str::len).sum::<usize>()
+            }
+            Motion::Start => 0,
+            Motion::End => self.text.len(),
+        }
+    }
+}
+
+/// Byte ranges of the part of `text` that fits in `width` columns with
+/// cursor visible, as [before cursor, under cursor, after cursor]
+///
+/// Text scrolls to left only as much as needed to show cursor
+/// Range under the cursor is empty when it is at the end
+/// and a space is to be shown there
+pub fn visible_parts(text: &str, cursor: usize, width: usize) -> [Range<usize>; 3] {
+    let (before, after) = text.split_at(cursor);
+    let mut after = after.graphemes(true);
+    let under = after.next().unwrap_or_default();
+
+    // drop from start until cursor fits
+    let room = width.saturating_sub(under.width().max(1));
+    let mut start = cursor;
+    let mut before_width = 0;
+    for (i, grapheme) in before.grapheme_indices(true).rev() {
+        before_width += grapheme.width();
+        if before_width > room {
+            before_width -= grapheme.width();
+            break;
+        }
+        start = i;
+    }
+
+    let under_end = cursor + under.len();
+    let mut used = before_width + under.width().max(1);
+    let end = under_end
+        + after
+            .take_while(|grapheme| {
+                used += grapheme.width();
+                used <= width
+            })
+            .map(str::len)
+            .sum::<usize>();
+    [start..cursor, cursor..under_end, under_end..end]
+}
+
+/// Spans of `range` in text, the part of it that
+/// is also in `marked` having `marked_style`
+fn marked_spans<'a>(
+    text: &str,
+    range: Range<usize>,
+    marked: Option<&Range<usize>>,
+    style: Style,
+    marked_style: Style,
+) -> Vec<Span<'a>> {
+    let (from, to) = match marked {
+        Some(marked) => (
+            marked.start.clamp(range.start, range.end),
+            marked.end.clamp(range.start, range.end),
+        ),
+        None => (range.end, range.end),
+    };
+    [
+        (range.start..from, style),
+        (from..to, marked_style),
+        (to..range.end, style),
+    ]
+    .into_iter()
+    .filter(|(part, _)| !part.is_empty())
+    .map(|(part, style)| Span::styled(text[part].to_string(), style))
+    .collect()
+}
+
 pub trait SearchbarAppdata {
     fn is_searchbar_active(&self) -> bool;
     fn get_altering_query(&self) -> &str;
-    fn get_title(&self) -> &'static str {
-        "Search "
+    fn get_title(&self) -> String {
+        "Search ".to_string()
     }
-    fn get_cursor(&self) -> &'static str {
-        "/"
+    /// Byte offset of cursor in query
+    fn get_cursor(&self) -> usize {
+        self.get_altering_query().len()
+    }
+    /// What is wrong with query, to be marked in it
+    fn get_query_error(&self) -> Option<QueryError> {
+        None
     }
 }
 
@@ -24,12 +173,44 @@ impl SearchbarAppdata for AppState {
     fn is_searchbar_active(&self) -> bool {
         self.active_window == Window::SearchBar
     }
+    /// What was found is shown while searching through history
     fn get_altering_query(&self) -> &str {
-        &self.altering_query
+        match self.search_history.search() {
+            Some(_) => self.search_history.found().unwrap_or_default(),
+            None => self.altering_query.text(),
+        }
+    }
+    fn get_title(&self) -> String {
+        match (self.search_history.search(), self.get_query_error()) {
+            (Some(search), _) if search.found.is_none() => {
+                format!("Nothing in history matches `{}` ", search.pattern)
+            }
+            (Some(search), _) => format!("History search `{}` ", search.pattern),
+            (None, Some(error)) => format!("Search: {error} "),
+            (None, None) => "Search ".to_string(),
+        }
+    }
+    fn get_cursor(&self) -> usize {
+        match self.search_history.search() {
+            Some(_) => self.get_altering_query().len(),
+            None => self.altering_query.cursor(),
+        }
+    }
+    fn get_query_error(&self) -> Option<QueryError> {
+        if self.search_history.search().is_some() {
+            return None;
+        }
+        self.altering_query.text().parse::<SearchQuery>().err()
     }
 }
 
-pub fn get_searchbar<'c, A>(appdata: &A, theme: &Theme) -> Paragraph<'c>
+/// Searchbar to be drawn `width` columns wide
+pub fn get_searchbar<'c, A>(
+    appdata: &A,
+    theme: &Theme,
+    preferences: &SearchbarPreferences,
+    width: u16,
+) -> Paragraph<'c>
 where
     A: SearchbarAppdata,
 {
@@ -40,23 +221,46 @@ where
             .fg(theme.active_color.into())
             .add_modifier(Modifier::ITALIC);
         cursor_style = Style::default()
-            .add_modifier(Modifier::RAPID_BLINK)
+            .add_modifier(Modifier::REVERSED)
             .fg(theme.highlight_color.into());
     } else {
         border_style = Style::default()
             .fg(theme.inactive_color.into())
             .add_modifier(Modifier::ITALIC);
-        cursor_style = Style::default().fg(theme.inactive_color.into());
+        cursor_style = Style::default().fg(theme.base_color.into());
     }
+    let text_style = Style::default().fg(theme.base_color.into());
+    let error_style = Style::default()
+        .fg(theme.highlight_color.into())
+        .add_modifier(Modifier::UNDERLINED | Modifier::BOLD);
 
-    let text: Text = Spans::from(vec![
-        Span::styled(
-            appdata.get_altering_query().to_string(),
-            Style::default().fg(theme.base_color.into()),
-        ),
-        Span::styled(appdata.get_cursor(), cursor_style),
-    ])
-    .into();
+    let query = appdata.get_altering_query();
+    let error = appdata.get_query_error().map(|error| error.span);
+    // text goes inside the borders
+    let [before, under, after] = visible_parts(
+        query,
+        appdata.get_cursor(),
+        width.saturating_sub(2) as usize,
+    );
+    let under_style = match &error {
+        Some(error) if error.contains(&under.start) => cursor_style.patch(error_style),
+        _ => cursor_style,
+    };
+    let under = match under.is_empty() {
+        true => preferences.cursor.to_string(),
+        false => query[under].to_string(),
+    };
+
+    let mut spans = marked_spans(query, before, error.as_ref(), text_style, error_style);
+    spans.push(Span::styled(under, under_style));
+    spans.extend(marked_spans(
+        query,
+        after,
+        error.as_ref(),
+        text_style,
+        error_style,
+    ));
+    let text: Text = Spans::from(spans).into();
 
     let block = Block::default()
         .border_type(tui::widgets::BorderType::Rounded)
@@ -67,3 +271,84 @@ where
 
     Paragraph::new(text).block(block)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editor(text: &str, cursor: usize) -> LineEditor {
+        LineEditor {
+            text: text.to_string(),
+            cursor,
+        }
+    }
+
+    #[test]
+    fn editing_in_middle() {
+        let mut line = LineEditor::default();
+        for ch in "hllo".chars() {
+            line.insert(ch);
+        }
+        line.move_cursor(Motion::Start);
+        line.move_cursor(Motion::Right);
+        line.insert('e');
+        assert_eq!(editor("hello", 2), line);
+
+        line.move_cursor(Motion::End);
+        line.delete(Motion::Left);
+        assert_eq!(editor("hell", 4), line);
+        line.delete(Motion::Right);
+        assert_eq!(editor("hell", 4), line);
+
+        line.set_text("one two  three".to_string());
+        line.move_cursor(Motion::WordLeft);
+        assert_eq!(9, line.cursor());
+        line.move_cursor(Motion::WordLeft);
+        assert_eq!(4, line.cursor());
+        line.move_cursor(Motion::WordRight);
+        assert_eq!(7, line.cursor());
+        line.delete(Motion::WordLeft);
+        assert_eq!(editor("one   three", 4), line);
+        line.delete(Motion::End);
+        assert_eq!(editor("one ", 4), line);
+        line.delete(Motion::Start);
+        assert_eq!(editor("", 0), line);
+    }
+
+    #[test]
+    fn graphemes_stay_whole() {
+        // flag is two chars, `é` is `e` with combining accent
+        let mut line = editor("a🇳🇵e\u{301}漢", 0);
+        let mut stops = vec![line.cursor()];
+        for _ in 0..5 {
+            line.move_cursor(Motion::Right);
+            stops.push(line.cursor());
+        }
+        assert_eq!(vec![0, 1, 9, 12, 15, 15], stops);
+
+        line.move_cursor(Motion::Left);
+        line.delete(Motion::Left);
+        assert_eq!(editor("a🇳🇵漢", 9), line);
+        line.delete(Motion::Left);
+        assert_eq!(editor("a漢", 1), line);
+    }
+
+    #[test]
+    fn long_text_scrolls() {
+        // cursor is shown between `|`
+        let parts = |text: &str, cursor, width| {
+            let [before, under, after] = visible_parts(text, cursor, width);
+            let under = match under.is_empty() {
+                true => " ",
+                false => &text[under],
+            };
+            format!("{}|{under}|{}", &text[before], &text[after])
+        };
+        assert_eq!("abc| |", parts("abc", 3, 10));
+        assert_eq!("a|b|cd", parts("abcdef", 1, 4));
+        assert_eq!("def| |", parts("abcdef", 6, 4));
+        // wide characters take two columns each
+        assert_eq!("漢字| |", parts("漢字漢字", 12, 5));
+        assert_eq!("|漢|字", parts("漢字漢字", 0, 5));
+    }
+}
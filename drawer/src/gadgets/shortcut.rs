@@ -7,18 +7,7 @@ use tui::widgets::BorderType;
 use tui::widgets::Borders;
 use tui::widgets::List;
 use tui::widgets::ListItem;
-use user_config::preferences::{shortcut::Shortcut, theme::Theme};
-
-pub const LIST_ITEMS: [Shortcut; 8] = [
-    Shortcut::Trending,
-    Shortcut::YoutubeCommunity,
-    Shortcut::LikedSongs,
-    Shortcut::MyPlaylist,
-    Shortcut::Downloaded,
-    Shortcut::FollowingArtist,
-    Shortcut::Local,
-    Shortcut::Search,
-];
+use user_config::preferences::{shortcut::ShortcutItem, theme::Theme};
 
 pub trait ShortcutListAppdata {
     fn is_shortcutlist_active(&self) -> bool;
@@ -38,15 +27,19 @@ impl ShortcutListAppdata for AppState {
     }
 }
 
-pub fn get_shortcut_list<'a, A>(appdata: &A, theme: &Theme) -> List<'a>
+/// Sidebar listing `items`
+pub fn get_shortcut_list<'a, A>(appdata: &A, items: &[ShortcutItem], theme: &Theme) -> List<'a>
 where
     A: ShortcutListAppdata,
 {
-    let list_items = LIST_ITEMS
-        .into_iter()
+    let list_items = items
+        .iter()
         .enumerate()
-        .map(|(i, s)| {
-            let item_str = <_ as Into<&'static str>>::into(s);
+        .map(|(i, item)| {
+            let item_str = match &item.icon {
+                Some(icon) => format!("{icon} {}", item.label),
+                None => item.label.clone(),
+            };
             let list_color = if appdata.selected() == Some(i) {
                 theme.highlight_color
             } else {
@@ -0,0 +1,112 @@
+use crate::gadgets::state::AppState;
+use crate::gadgets::window::Window;
+use crate::types::suggestion::Suggestion;
+use tui::layout::Rect;
+use tui::style::{Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, BorderType, Borders, List, ListItem};
+use user_config::preferences::theme::Theme;
+
+pub trait SuggestionAppdata {
+    /// Suggestions to show below searchbar, with index of highlighted one
+    fn suggestions(&self) -> (Vec<Suggestion>, Option<usize>) {
+        (Vec::new(), None)
+    }
+}
+
+impl SuggestionAppdata for AppState {
+    fn suggestions(&self) -> (Vec<Suggestion>, Option<usize>) {
+        // only while typing in searchbar with nothing above it
+        let is_typing = self.active_window == Window::SearchBar
+            && self.popups.is_empty()
+            && self.command_line.is_none()
+            && self.search_history.search().is_none();
+        if !is_typing {
+            return (Vec::new(), None);
+        }
+        let suggestions = self.suggestions.list().into_iter().cloned().collect();
+        (suggestions, self.suggestions.selected())
+    }
+}
+
+/// Place for dropdown of `count` suggestions just below searchbar
+/// None if nothing is suggested or there is no room below it in `area`
+pub fn get_suggestions_rect(count: usize, searchbar: Rect, area: Rect) -> Option<Rect> {
+    let y = searchbar.y + searchbar.height;
+    let room = (area.y + area.height).saturating_sub(y);
+    // borders on both side
+    let height = (count as u16).saturating_add(2).min(room);
+    if count == 0 || height < 3 {
+        return None;
+    }
+    Some(Rect {
+        y,
+        height,
+        ..searchbar
+    })
+}
+
+/// Suggestions fitting in `rows`, scrolled to keep highlighted one visible
+pub fn get_suggestions_list<'a>(
+    suggestions: &[Suggestion],
+    selected: Option<usize>,
+    theme: &Theme,
+    rows: usize,
+) -> List<'a> {
+    let offset = match selected {
+        Some(selected) if rows > 0 && selected >= rows => selected + 1 - rows,
+        _ => 0,
+    };
+    let source_style = Style::default()
+        .fg(theme.inactive_color.into())
+        .add_modifier(Modifier::ITALIC);
+
+    let list_items = suggestions
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .map(|(i, suggestion)| {
+            let color = if selected == Some(i) {
+                theme.highlight_color
+            } else {
+                theme.base_color
+            };
+            ListItem::new(Spans::from(vec![
+                Span::styled(suggestion.text.clone(), Style::default().fg(color.into())),
+                Span::raw("  "),
+                Span::styled(suggestion.source.label(), source_style),
+            ]))
+        })
+        .collect::<Vec<_>>();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(theme.active_color.into()));
+
+    List::new(list_items).block(block)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dropdown_hangs_below_searchbar() {
+        let area = Rect::new(0, 0, 80, 20);
+        let searchbar = Rect::new(10, 2, 40, 3);
+
+        assert_eq!(
+            Some(Rect::new(10, 5, 40, 6)),
+            get_suggestions_rect(4, searchbar, area)
+        );
+        // cut short by bottom of the area
+        assert_eq!(
+            Some(Rect::new(10, 5, 40, 15)),
+            get_suggestions_rect(30, searchbar, area)
+        );
+        assert_eq!(None, get_suggestions_rect(0, searchbar, area));
+        let at_bottom = Rect::new(10, 18, 40, 1);
+        assert_eq!(None, get_suggestions_rect(4, at_bottom, area));
+    }
+}
@@ -3,23 +3,44 @@ use crate::gadgets::searchbar::SearchbarAppdata;
 use crate::types::state::GeometryData;
 use crate::types::state::ShortcutListState;
 use tui::backend::Backend;
+use tui::layout::Rect;
 use tui::terminal::Frame;
+use tui::widgets::Clear;
 use user_config::preferences::theme::Theme;
+use user_config::preferences::WidgetPreferences;
 
+use super::artistpane::get_artistpane_list;
+use super::artistpane::ArtistpaneAppdata;
+use super::commandline::get_commandline;
+use super::commandline::get_commandline_choices;
+use super::commandline::get_picker_rects;
+use super::commandline::get_prompt_rects;
+use super::commandline::CommandlineAppdata;
 use super::gauge::get_gauge;
 use super::gauge::GaugeAppData;
+use super::keyhint::get_keyhint;
+use super::keyhint::get_keyhint_rect;
+use super::keyhint::KeyhintAppdata;
 use super::musicpane::get_musicpane_list;
 use super::musicpane::MusicpaneAppdata;
 use super::panetab::get_panetab;
 use super::panetab::PanetabAppdata;
 use super::playlistpane::get_playlistpane_list;
 use super::playlistpane::PlaylistpaneAppdata;
+use super::popup::get_details;
+use super::popup::PopupAppdata;
 use super::shortcut::get_shortcut_list;
 use super::shortcut::ShortcutListAppdata;
 use super::state::AppState;
+use super::state::ArtistPaneState;
+use super::state::CommandLine;
 use super::state::MusicPaneState;
 use super::state::PanetabState;
 use super::state::PlaylistPaneState;
+use super::state::Popup;
+use super::suggestion::get_suggestions_list;
+use super::suggestion::get_suggestions_rect;
+use super::suggestion::SuggestionAppdata;
 use super::window::PaneWindow;
 
 pub trait Provider<Value> {
@@ -40,13 +61,35 @@ impl Provider<PanetabState> for AppState {
 
 impl Provider<MusicPaneState> for AppState {
     fn provide(&self) -> MusicPaneState {
-        self.music_pane_state.clone()
+        let mut state = self.music_pane_state.clone();
+        // rows above viewport are not given to widget at all
+        let selected = state.get_ref().selected();
+        state
+            .get_mut_ref()
+            .select(selected.map(|s| s.saturating_sub(self.music_viewport.offset)));
+        state
     }
 }
 
 impl Provider<PlaylistPaneState> for AppState {
     fn provide(&self) -> PlaylistPaneState {
-        self.playlist_pane_state.clone()
+        let mut state = self.playlist_pane_state.clone();
+        let selected = state.get_ref().selected();
+        state
+            .get_mut_ref()
+            .select(selected.map(|s| s.saturating_sub(self.playlist_viewport.offset)));
+        state
+    }
+}
+
+impl Provider<ArtistPaneState> for AppState {
+    fn provide(&self) -> ArtistPaneState {
+        let mut state = self.artist_pane_state.clone();
+        let selected = state.get_ref().selected();
+        state
+            .get_mut_ref()
+            .select(selected.map(|s| s.saturating_sub(self.artist_viewport.offset)));
+        state
     }
 }
 
@@ -54,6 +97,7 @@ pub fn draw_all_ui<A, B>(
     frame: &mut Frame<B>,
     appdata: &A,
     theme: &Theme,
+    preferences: &WidgetPreferences,
     geometrics: &GeometryData,
 ) where
     B: Backend,
@@ -62,16 +106,26 @@ pub fn draw_all_ui<A, B>(
         + ShortcutListAppdata
         + MusicpaneAppdata
         + PlaylistpaneAppdata
+        + ArtistpaneAppdata
         + PanetabAppdata
+        + KeyhintAppdata
+        + CommandlineAppdata
+        + PopupAppdata
+        + SuggestionAppdata
         + Provider<ShortcutListState>
         + Provider<PanetabState>
         + Provider<MusicPaneState>
-        + Provider<PlaylistPaneState>,
+        + Provider<PlaylistPaneState>
+        + Provider<ArtistPaneState>,
 {
+    // Gadgets without preferences are not shown at all
     let searchbar_rect = geometrics.searchbar;
-    if searchbar_rect.area() > 1 {
-        let searchbar = get_searchbar(appdata, theme);
-        frame.render_widget(searchbar, searchbar_rect);
+    if let Some(searchbar_preferences) = &preferences.searchbar {
+        if searchbar_rect.area() > 1 {
+            let searchbar =
+                get_searchbar(appdata, theme, searchbar_preferences, searchbar_rect.width);
+            frame.render_widget(searchbar, searchbar_rect);
+        }
     }
 
     let gauge_rect = geometrics.gauge;
@@ -81,14 +135,16 @@ pub fn draw_all_ui<A, B>(
     }
 
     let sidebar_rect = geometrics.shortcuts;
-    if sidebar_rect.area() > 1 {
-        let sidebar = get_shortcut_list(appdata, theme);
-        let mut sidebar_state = <A as Provider<ShortcutListState>>::provide(appdata);
-        frame.render_stateful_widget(sidebar, sidebar_rect, sidebar_state.get_mut_ref())
+    if let Some(shortcut_preferences) = &preferences.shortcuts {
+        if sidebar_rect.area() > 1 {
+            let sidebar = get_shortcut_list(appdata, &shortcut_preferences.items, theme);
+            let mut sidebar_state = <A as Provider<ShortcutListState>>::provide(appdata);
+            frame.render_stateful_widget(sidebar, sidebar_rect, sidebar_state.get_mut_ref())
+        }
     }
 
     let panetab_rect = geometrics.panetab;
-    if panetab_rect.area() > 1 {
+    if preferences.pane_tab.is_some() && panetab_rect.area() > 1 {
         let panetab = get_panetab(appdata, theme);
         frame.render_widget(panetab, panetab_rect)
     }
@@ -98,7 +154,7 @@ pub fn draw_all_ui<A, B>(
     match panetab_state.active_tab {
         PaneWindow::MusicPane => {
             let musicpane_rect = geometrics.musicpane;
-            if musicpane_rect.area() > 1 {
+            if preferences.music_pane.is_some() && musicpane_rect.area() > 1 {
                 let mut musicpane_state = <A as Provider<MusicPaneState>>::provide(appdata);
                 let musicpane = get_musicpane_list(appdata, geometrics, theme);
                 frame.render_stateful_widget(
@@ -111,7 +167,7 @@ pub fn draw_all_ui<A, B>(
 
         PaneWindow::PlaylistPane => {
             let playlistpane_rect = geometrics.playlistpane;
-            if playlistpane_rect.area() > 1 {
+            if preferences.playlist_pane.is_some() && playlistpane_rect.area() > 1 {
                 let mut playlistpane_state = <A as Provider<PlaylistPaneState>>::provide(appdata);
                 let playlistpane = get_playlistpane_list(appdata, geometrics, theme);
                 frame.render_stateful_widget(
@@ -123,7 +179,77 @@ pub fn draw_all_ui<A, B>(
         }
 
         PaneWindow::ArtistPane => {
-            unimplemented!()
+            let artistpane_rect = geometrics.artistpane;
+            if preferences.artist_pane.is_some() && artistpane_rect.area() > 1 {
+                let mut artistpane_state = <A as Provider<ArtistPaneState>>::provide(appdata);
+                let artistpane = get_artistpane_list(appdata, geometrics, theme);
+                frame.render_stateful_widget(
+                    artistpane,
+                    artistpane_rect,
+                    artistpane_state.get_mut_ref(),
+                );
+            }
         }
     }
+
+    // Dropdown hangs over whatever is below searchbar
+    let (suggestions, selected) = appdata.suggestions();
+    if let Some(dropdown_rect) =
+        get_suggestions_rect(suggestions.len(), searchbar_rect, frame.size())
+    {
+        let rows = dropdown_rect.height.saturating_sub(2) as usize;
+        frame.render_widget(Clear, dropdown_rect);
+        frame.render_widget(
+            get_suggestions_list(&suggestions, selected, theme, rows),
+            dropdown_rect,
+        );
+    }
+
+    // Popups go above everything in layout, later ones above earlier ones
+    for popup in appdata.popups() {
+        frame.render_widget(Clear, geometrics.popup);
+        match popup {
+            Popup::Picker(command_line) => {
+                let (input_rect, choices_rect) = get_picker_rects(geometrics.popup);
+                draw_command_line(frame, &command_line, input_rect, choices_rect, theme);
+            }
+            Popup::Details(details) => {
+                frame.render_widget(
+                    get_details(&details, theme, geometrics.popup),
+                    geometrics.popup,
+                );
+            }
+        }
+    }
+
+    // Drawn last so that these stay above everything else
+    if let Some(keyhint_rect) = get_keyhint_rect(appdata, geometrics.musicpane) {
+        frame.render_widget(Clear, keyhint_rect);
+        frame.render_widget(get_keyhint(appdata, theme), keyhint_rect);
+    }
+
+    if let Some(prompt) = appdata.prompt() {
+        let (input_rect, choices_rect) = get_prompt_rects(&prompt, frame.size(), geometrics.gauge);
+        draw_command_line(frame, &prompt, input_rect, choices_rect, theme);
+    }
+}
+
+fn draw_command_line<B: Backend>(
+    frame: &mut Frame<B>,
+    command_line: &CommandLine,
+    input_rect: Rect,
+    choices_rect: Rect,
+    theme: &Theme,
+) {
+    frame.render_widget(Clear, input_rect);
+    frame.render_widget(get_commandline(command_line, theme), input_rect);
+    if choices_rect.area() > 0 {
+        // rows inside borders
+        let rows = choices_rect.height.saturating_sub(2) as usize;
+        frame.render_widget(Clear, choices_rect);
+        frame.render_widget(
+            get_commandline_choices(command_line, theme, rows),
+            choices_rect,
+        );
+    }
 }
@@ -0,0 +1,93 @@
+/// What was asked for from command line
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Args {
+    /// `key.path=value` given with --set, in given order
+    pub overrides: Vec<String>,
+    /// Something to do instead of starting the app
+    pub subcommand: Option<Subcommand>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subcommand {
+    /// `config check`: report problems in config and exit
+    CheckConfig,
+    /// `config cheatsheet`: print every keybinding as markdown and exit
+    Cheatsheet,
+}
+
+/// Parse arguments excluding the binary name
+pub fn parse_args<I>(args: I) -> Result<Args, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut parsed = Args::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--set" {
+            let value = args.next().ok_or("--set expects key.path=value after it")?;
+            parsed.overrides.push(value);
+        } else if let Some(value) = arg.strip_prefix("--set=") {
+            parsed.overrides.push(value.to_string());
+        } else if arg == "config" && parsed.subcommand.is_none() {
+            match args.next().as_deref() {
+                Some("check") => parsed.subcommand = Some(Subcommand::CheckConfig),
+                Some("cheatsheet") => parsed.subcommand = Some(Subcommand::Cheatsheet),
+                Some(other) => return Err(format!("Unknown config subcommand: {other}")),
+                None => return Err("config expects a subcommand: check, cheatsheet".to_string()),
+            }
+        } else {
+            return Err(format!("Unknown argument: {arg}"));
+        }
+    }
+
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn set_overrides() {
+        let parsed = parse_args(args(&[
+            "--set",
+            "theme.base_color=#ffffff",
+            "--set=backend.flavor=piped",
+        ]));
+        assert_eq!(
+            Ok(Args {
+                overrides: args(&["theme.base_color=#ffffff", "backend.flavor=piped"]),
+                subcommand: None,
+            }),
+            parsed
+        );
+
+        assert!(parse_args(args(&["--set"])).is_err());
+        assert!(parse_args(args(&["--verbose"])).is_err());
+    }
+
+    #[test]
+    fn config_check() {
+        assert_eq!(
+            Ok(Args {
+                overrides: args(&["backend.flavor=piped"]),
+                subcommand: Some(Subcommand::CheckConfig),
+            }),
+            parse_args(args(&["config", "check", "--set=backend.flavor=piped"]))
+        );
+
+        assert_eq!(
+            Some(Subcommand::Cheatsheet),
+            parse_args(args(&["config", "cheatsheet"]))
+                .unwrap()
+                .subcommand
+        );
+        assert!(parse_args(args(&["config"])).is_err());
+        assert!(parse_args(args(&["config", "fix"])).is_err());
+    }
+}
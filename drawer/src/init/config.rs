@@ -1,34 +1,343 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use serde_json::{Map, Value};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use user_config::action::MappingIndex;
 use user_config::Config;
 
-fn get_config_path() -> Result<PathBuf, &'static str> {
+/// Environment variables starting with this override config
+/// Eg: YTUI_MUSIC_BACKEND__INSTANCE=https://example.com sets backend.instance
+const ENV_PREFIX: &str = "YTUI_MUSIC_";
+/// Separates keys of path in environment variable name
+const ENV_SEPARATOR: &str = "__";
+
+pub fn get_system_config_path() -> Option<PathBuf> {
+    if cfg!(unix) {
+        Some(PathBuf::from("/etc/ytui_music/config.json"))
+    } else {
+        None
+    }
+}
+
+pub fn get_config_path() -> Result<PathBuf, &'static str> {
     let mut config_dir = dirs::preference_dir().ok_or("Cannot get user preference dir")?;
     config_dir = config_dir.join("ytui_music").join("config.json");
     Ok(config_dir)
 }
 
-pub fn default_config_source() -> Result<BufReader<File>, String> {
-    let config_path = get_config_path().map_err(|e| format!("While getting config path: {e:?}"))?;
-    let file = File::open(config_path).map_err(|e| format!("While opening config file: {e:?}"))?;
-    Ok(BufReader::new(file))
+/// Everything config is built from. Later ones win over earlier ones
+///
+/// Files only need to mention fields they want to change.
+/// Objects are merged key by key while everything else
+/// (including lists like layout.itemRoot) is replaced as a whole
+#[derive(Debug, Default, Clone)]
+pub struct ConfigSources {
+    /// Applies to every user of machine. Skipped if file does not exists
+    pub system_file: Option<PathBuf>,
+    /// Skipped if file does not exists
+    pub user_file: Option<PathBuf>,
+    /// (name, value) of environment variables
+    /// those not starting with ENV_PREFIX are ignored
+    pub env: Vec<(String, String)>,
+    /// `key.path=value` as given with --set
+    pub overrides: Vec<String>,
+}
+
+impl ConfigSources {
+    /// Sources at their usual places with given --set overrides
+    pub fn from_environment(overrides: Vec<String>) -> Self {
+        ConfigSources {
+            system_file: get_system_config_path(),
+            user_file: get_config_path().ok(),
+            env: std::env::vars().collect(),
+            overrides,
+        }
+    }
 }
 
-pub fn get_config<F, R>(config_source: F) -> Result<Config, String>
-where
-    R: BufRead,
-    F: FnOnce() -> Result<R, String>,
-{
-    // TODO:
-    // remove this block
+pub fn get_config(sources: &ConfigSources) -> Result<Config, String> {
+    let mut config = serde_json::to_value(user_config::default_config())
+        .map_err(|e| format!("While converting default config to json: {e:?}"))?;
+
+    for path in [&sources.system_file, &sources.user_file]
+        .into_iter()
+        .flatten()
     {
-        return Ok(user_config::default_config());
+        if let Some(mut layer) = read_layer(path)? {
+            normalize_keyboard(&mut layer)
+                .map_err(|e| format!("While reading keyboard of {}: {e}", path.display()))?;
+            merge(&mut config, layer);
+        }
+    }
+
+    for (name, value) in &sources.env {
+        if let Some(path) = name.strip_prefix(ENV_PREFIX) {
+            let path = path.split(ENV_SEPARATOR).collect::<Vec<_>>();
+            set_path(&mut config, &path, parse_value(value))
+                .map_err(|e| format!("While applying environment variable {name}: {e}"))?;
+        }
+    }
+
+    for assignment in &sources.overrides {
+        let (path, value) = assignment
+            .split_once('=')
+            .ok_or_else(|| format!("Override {assignment} is not in key.path=value form"))?;
+        let path = path.split('.').collect::<Vec<_>>();
+        set_path(&mut config, &path, parse_value(value))
+            .map_err(|e| format!("While applying override {assignment}: {e}"))?;
+    }
+
+    serde_json::from_value(config)
+        .map_err(|e| format!("While building config from all layers: {e}"))
+}
+
+/// Json in file at `path`. None if there is no such file
+fn read_layer(path: &Path) -> Result<Option<Value>, String> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("While reading {}: {e}", path.display())),
+    };
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| format!("While parsing {}: {e}", path.display()))
+}
+
+/// Apply `layer` over `base`
+fn merge(base: &mut Value, layer: Value) {
+    match (base, layer) {
+        (Value::Object(base), Value::Object(layer)) => {
+            for (key, value) in layer {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, layer) => *base = layer,
+    }
+}
+
+/// Write bindings in keyboard of `layer` the way default config has them
+/// so that a binding replaces the same one in earlier layers however it is spelled
+fn normalize_keyboard(layer: &mut Value) -> Result<(), String> {
+    let Some(Value::Object(keyboard)) = layer.get_mut("keyboard") else {
+        return Ok(());
+    };
+    let mut normalized = Map::new();
+    for (index, action) in std::mem::take(keyboard) {
+        if normalized
+            .insert(normalize_index(&index)?, action)
+            .is_some()
+        {
+            return Err(format!("Binding {index} is given more than once"));
+        }
+    }
+    *keyboard = normalized;
+    Ok(())
+}
+
+/// Binding like `<alt><ctrl>x|insert` as it is written by config itself
+fn normalize_index(index: &str) -> Result<String, String> {
+    MappingIndex::try_from(index.to_string()).map(String::from)
+}
+
+/// Value is taken as json if it is one, as plain string otherwise
+/// so that `theme.base_color=#ffffff` works without extra quotes
+fn parse_value(value: &str) -> Value {
+    serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()))
+}
+
+/// Set value at path of keys, creating objects along the way
+///
+/// Keys are matched ignoring case and underscores against those already
+/// present, as environment variables are usually written in upper case
+fn set_path(config: &mut Value, path: &[&str], value: Value) -> Result<(), String> {
+    // Bindings are keys, not names to match loosely
+    if let [section, index] = path {
+        if existing_key(config, section) == "keyboard" {
+            let keyboard = child_of(config, section)?
+                .entry("keyboard")
+                .or_insert_with(|| Value::Object(Map::new()));
+            child_of(keyboard, index)?.insert(normalize_index(index)?, value);
+            return Ok(());
+        }
+    }
+
+    let (last, parents) = path.split_last().ok_or("Empty key path")?;
+    let mut current = config;
+    for key in parents {
+        let key_name = existing_key(current, key);
+        current = child_of(current, key)?
+            .entry(key_name)
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    let key = existing_key(current, last);
+    child_of(current, last)?.insert(key, value);
+    Ok(())
+}
+
+fn child_of<'v>(value: &'v mut Value, key: &str) -> Result<&'v mut Map<String, Value>, String> {
+    value
+        .as_object_mut()
+        .ok_or_else(|| format!("Cannot set {key} inside a value that is not an object"))
+}
+
+fn existing_key(value: &Value, key: &str) -> String {
+    let normalize = |k: &str| k.replace('_', "").to_lowercase();
+    value
+        .as_object()
+        .and_then(|map| map.keys().find(|k| normalize(k) == normalize(key)))
+        .cloned()
+        .unwrap_or_else(|| key.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use user_config::action::KeyboardAction;
+    use user_config::backend::ApiFlavor;
+    use user_config::keyboard::{Key, KeyCode, Modifiers};
+    use user_config::styles::color::RGB;
+
+    fn write_temp(name: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("ytui-config-{}-{name}", std::process::id()));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn nothing_given_is_default() {
+        let sources = ConfigSources {
+            user_file: Some(PathBuf::from("/surely/does/not/exists.json")),
+            ..Default::default()
+        };
+        assert_eq!(Ok(user_config::default_config()), get_config(&sources));
+    }
+
+    #[test]
+    fn layers_override_in_order() {
+        let system_file = write_temp(
+            "system.json",
+            r#"{
+                "backend": { "flavor": "piped", "instance": "https://system.example" },
+                "keyboard": { "<alt><ctrl>x": "Quit" }
+            }"#,
+        );
+        let user_file = write_temp(
+            "user.json",
+            r##"{
+                "theme": { "base_color": "#ffffff" },
+                "keyboard": { "x": "Quit", "q": "Nothing", "<ctrl><alt>x": "PausePlay" },
+                "backend": { "instance": "https://user.example" }
+            }"##,
+        );
+        let sources = ConfigSources {
+            system_file: Some(system_file.clone()),
+            user_file: Some(user_file.clone()),
+            env: vec![
+                (
+                    "YTUI_MUSIC_THEME__ACTIVE_COLOR".to_string(),
+                    "#000000".to_string(),
+                ),
+                ("HOME".to_string(), "/home/someone".to_string()),
+            ],
+            overrides: vec![
+                "backend.instance=https://cli.example".to_string(),
+                "keyboard.G=NextTrack".to_string(),
+                "keyboard.<shift>n=PreviousTrack".to_string(),
+            ],
+        };
+
+        let config = get_config(&sources);
+        fs::remove_file(system_file).unwrap();
+        fs::remove_file(user_file).unwrap();
+        let config = config.unwrap();
+        let default = user_config::default_config();
+
+        assert_eq!(ApiFlavor::Piped, config.backend.flavor);
+        assert_eq!("https://cli.example", config.backend.instance);
+        assert_eq!(RGB(255, 255, 255), config.theme.base_color);
+        assert_eq!(RGB(0, 0, 0), config.theme.active_color);
+        assert_eq!(default.theme.highlight_color, config.theme.highlight_color);
+        assert_eq!(default.layout, config.layout);
+
+        let action = |key| config.keyboard.action_for(&(key, None).into());
+        assert_eq!(
+            Some(KeyboardAction::Quit),
+            action(Key::from(KeyCode::Char('x')))
+        );
+        assert_eq!(
+            Some(KeyboardAction::Nothing),
+            action(Key::from(KeyCode::Char('q')))
+        );
+        assert_eq!(
+            Some(KeyboardAction::PausePlay),
+            action(Key::from(KeyCode::Char(' ')))
+        );
+        // same binding however it is spelled, later layer wins
+        assert_eq!(
+            Some(KeyboardAction::PausePlay),
+            action(Key::new(
+                KeyCode::Char('x'),
+                Modifiers::CTRL.with(Modifiers::ALT)
+            ))
+        );
+        assert_eq!(
+            Some(KeyboardAction::NextTrack),
+            action(Key::from(KeyCode::Char('G')))
+        );
+        assert_eq!(
+            Some(KeyboardAction::PreviousTrack),
+            action(Key::from(KeyCode::Char('N')))
+        );
+        assert_eq!(
+            default
+                .keyboard
+                .action_for(&(Key::from(KeyCode::Char('g')), None).into()),
+            action(Key::from(KeyCode::Char('g')))
+        );
+        assert_eq!(
+            default.keyboard.bindings().count() + 4,
+            config.keyboard.bindings().count()
+        );
     }
 
-    let reader = config_source().map_err(|e| format!("While getting config source: {e:?}"))?;
-    let config = serde_json::from_reader(reader)
-        .map_err(|e| format!("While parsing json from reader: {e:?}"))?;
+    #[test]
+    fn invalid_layers_are_reported() {
+        let broken = write_temp("broken.json", "{ theme: ");
+        let sources = ConfigSources {
+            user_file: Some(broken.clone()),
+            ..Default::default()
+        };
+        let result = get_config(&sources);
+        fs::remove_file(broken).unwrap();
+        assert!(result.unwrap_err().contains("While parsing"));
 
-    Ok(config)
+        let twice = write_temp(
+            "twice.json",
+            r#"{ "keyboard": { "<ctrl><alt>x": "Quit", "<alt><ctrl>x": "Nothing" } }"#,
+        );
+        let sources = ConfigSources {
+            user_file: Some(twice.clone()),
+            ..Default::default()
+        };
+        let result = get_config(&sources);
+        fs::remove_file(twice).unwrap();
+        assert!(result.unwrap_err().contains("more than once"));
+
+        let sources = ConfigSources {
+            overrides: vec!["backend.flavor=spotify".to_string()],
+            ..Default::default()
+        };
+        assert!(get_config(&sources).is_err());
+
+        let sources = ConfigSources {
+            overrides: vec!["backend.instance".to_string()],
+            ..Default::default()
+        };
+        assert!(get_config(&sources).is_err());
+    }
 }
@@ -1,4 +1,3 @@
+pub mod args;
 pub mod config;
 pub mod terminal;
-
-pub use config::default_config_source;
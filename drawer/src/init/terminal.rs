@@ -55,6 +55,7 @@ pub mod init_crossterm {
     };
     use tui::backend::CrosstermBackend;
 
+    #[allow(clippy::type_complexity)]
     pub fn setup_terminal() -> Result<
         (
             // the terminal handle itself
@@ -2,22 +2,65 @@ use gadgets::ui::draw_all_ui;
 use std::collections::HashMap;
 use std::error::Error;
 use types::state::GeometryData;
+use user_config::action::KeyboardMapping;
+use user_config::backend::Backend;
+use user_config::diagnostics::check_keymap;
+use user_config::help::cheatsheet;
 use user_config::reexports::compute_rect_for_item_tree as compute_rect;
+use user_config::reexports::layout_config::ui::UI;
 use user_config::Config;
 
 pub mod event;
 pub mod gadgets;
 pub mod init;
+pub mod player;
+pub mod search;
 pub mod types;
 
-use event::listen_for_event;
-use event::EventSummary;
+use event::{Flow, Message, Services};
+use init::args::Subcommand;
+use init::config::ConfigSources;
+use player::mpv::MpvPlayer;
+use player::{NoPlayer, Player, PlayerEvent};
+use search::local::LocalProvider;
+use search::remote::RemoteProvider;
+use search::{SearchRequest, SearchResponse, Sources};
+use std::sync::mpsc;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::time::{Duration, Instant};
+use types::history::{self, SearchHistory};
 use types::{state::AppState, utils};
-use user_config::action::KeyboardAction;
+
+/// How often to wake up even if nothing have happened
+const TICK_RATE: Duration = Duration::from_secs(1);
+/// How often to check if config files have changed
+const CONFIG_POLL_RATE: Duration = Duration::from_secs(1);
 
 pub fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let config = init::config::get_config(init::default_config_source)
-        .map_err(|e| format!("Unable to get user configuration: {e:?}"))?;
+    let args = init::args::parse_args(std::env::args().skip(1))?;
+    let config_sources = init::config::ConfigSources::from_environment(args.overrides);
+    let config = init::config::get_config(&config_sources)
+        .map_err(|e| format!("Unable to get user configuration: {e}"))?;
+
+    match args.subcommand {
+        Some(Subcommand::CheckConfig) => return check_config(&config),
+        Some(Subcommand::Cheatsheet) => {
+            print!("{}", cheatsheet(&config.keyboard));
+            return Ok(());
+        }
+        None => (),
+    }
+
+    let (player_event_sender, player_events) = mpsc::channel();
+    // Searching and browsing are still of use without mpv
+    let (mut player, player_notice): (Box<dyn Player>, _) =
+        match MpvPlayer::spawn(&player::mpv::default_socket_path(), player_event_sender) {
+            Ok(player) => (Box::new(player), None),
+            Err(e) => (
+                Box::new(NoPlayer),
+                Some(format!("Playback disabled, mpv could not be started: {e}")),
+            ),
+        };
 
     // read config, show option for user to generate new if not previously exists
     // have sub command to check config and to upate the binary
@@ -28,8 +71,15 @@ pub fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Wait if terminal can be rolled back
     // before reporting app error
-    let app_res =
-        run_app(&mut terminal, config).map_err(|e| format!("Application exit with error: {e:#?}"));
+    let app_res = run_app(
+        &mut terminal,
+        config,
+        config_sources,
+        player.as_mut(),
+        player_events,
+        player_notice,
+    )
+    .map_err(|e| format!("Application exit with error: {e:#?}"));
 
     let rollback_res = rollback_terminal(&mut terminal)
         .map_err(|e| format!("While doing rollback terminal: {e:#?}"));
@@ -41,42 +91,182 @@ pub fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Print everything that looks wrong in config
+/// Fails if anything was found so that it can be used from scripts
+fn check_config(config: &Config) -> Result<(), Box<dyn Error>> {
+    let warnings = check_keymap(&config.keyboard);
+    for warning in &warnings {
+        println!("{warning}");
+    }
+    match warnings.len() {
+        0 => {
+            println!("No problems found");
+            Ok(())
+        }
+        count => Err(format!("Found {count} problem(s) in keybindings").into()),
+    }
+}
+
+/// Short note about problems in keymap, pointing to where details are
+fn keymap_notice(keyboard: &KeyboardMapping) -> Option<String> {
+    match check_keymap(keyboard).as_slice() {
+        [] => None,
+        [only] => Some(only.to_string()),
+        warnings => Some(format!(
+            "{} problems in keybindings, run `drawer config check` for details",
+            warnings.len()
+        )),
+    }
+}
+
+/// Start search worker looking in local files and given backend
+fn spawn_searcher(backend: &Backend, responses: Sender<SearchResponse>) -> Sender<SearchRequest> {
+    let sources = Sources {
+        local: LocalProvider,
+        remote: RemoteProvider::new(backend),
+    };
+    search::spawn_worker(sources, responses)
+}
+
 fn run_app<B: tui::backend::Backend>(
     terminal: &mut tui::terminal::Terminal<B>,
     config: Config,
+    config_sources: ConfigSources,
+    player: &mut dyn Player,
+    player_events: Receiver<PlayerEvent>,
+    player_notice: Option<String>,
 ) -> Result<(), Box<dyn Error>> {
-    let Config {
-        layout,
-        theme,
-        keyboard,
-    } = config;
+    // Theme and layout in use may differ from ones in config
+    // once switched with command line
+    let mut config = config;
+    let mut layout = config.layout.clone();
+    let mut theme = config.theme.clone();
+
+    let (sender, messages) = mpsc::channel();
+    event::spawn_terminal_listener(sender.clone());
+    event::spawn_ticker(sender.clone(), TICK_RATE);
+    event::spawn_config_watcher(config_sources, sender.clone(), CONFIG_POLL_RATE);
+    event::forward(player_events, sender.clone(), Message::Player);
 
-    let mut appstate = AppState::default();
+    let (search_response_sender, search_responses) = mpsc::channel();
+    let searcher = spawn_searcher(&config.backend, search_response_sender.clone());
+    event::forward(search_responses, sender, Message::Search);
+
+    let mut services = Services { player, searcher };
+
+    let mut appstate = AppState {
+        notification: player_notice.or_else(|| keymap_notice(&config.keyboard)),
+        ..Default::default()
+    };
+    let history_path = history::get_history_path().map_err(str::to_string);
+    match history_path.and_then(SearchHistory::load) {
+        Ok(search_history) => appstate.search_history = search_history,
+        Err(e) => {
+            let notice = format!("Search history is not kept: {e}");
+            appstate.notification.get_or_insert(notice);
+        }
+    }
     let mut rect_map = HashMap::new();
 
-    let mut recompute_layout =
-        |geometrics: &mut GeometryData, terminal_rect| -> Result<(), String> {
-            compute_rect(&layout.item_root, &mut rect_map, &terminal_rect);
-            *geometrics = utils::consume_and_get_geometry(&mut rect_map)
-                .map_err(|e| format!("While creating geometry from Rect map: {e:#?}"))?;
-            Ok(())
-        };
+    let mut compute_geometry = |layout: &UI, terminal_rect| -> Result<GeometryData, String> {
+        compute_rect(&layout.item_root, &mut rect_map, &terminal_rect);
+        let geometry = utils::consume_and_get_geometry(&mut rect_map)
+            .map_err(|e| format!("While creating geometry from Rect map: {e:#?}"))?;
+        Ok(GeometryData {
+            popup: utils::popup_rect(layout, utils::from_my_rect(terminal_rect)),
+            ..geometry
+        })
+    };
 
-    let mut geometrics = GeometryData::default();
-    recompute_layout(&mut geometrics, utils::into_my_rect(terminal.size()?))?;
+    let mut geometrics = compute_geometry(&layout, utils::into_my_rect(terminal.size()?))?;
+    appstate.fit_viewports(&geometrics);
 
     'ui_renderer: loop {
-        terminal.draw(|frame| draw_all_ui(frame, &appstate, &theme, &geometrics))?;
-        let event_summary = listen_for_event(&keyboard, &appstate);
-
-        match event_summary {
-            EventSummary::Nothing => (),
-            EventSummary::Ignored => (),
-            EventSummary::Resize => {
-                recompute_layout(&mut geometrics, utils::into_my_rect(terminal.size()?))?
+        terminal.draw(|frame| {
+            draw_all_ui(frame, &appstate, &theme, &config.preferences, &geometrics)
+        })?;
+
+        // Block until something happens, then also take
+        // everything else that is already waiting
+        // so that burst of messages cause single redraw
+        // Unfinished key sequence must be looked at once it times out
+        // and suggestions are asked for once typing pauses
+        let first_message = match appstate.next_deadline() {
+            Some(deadline) => {
+                let wait = deadline.saturating_duration_since(Instant::now());
+                match messages.recv_timeout(wait) {
+                    Ok(message) => message,
+                    Err(RecvTimeoutError::Timeout) => Message::Tick,
+                    Err(RecvTimeoutError::Disconnected) => {
+                        return Err("All message sources have been closed".into())
+                    }
+                }
+            }
+            None => messages
+                .recv()
+                .map_err(|_| "All message sources have been closed")?,
+        };
+        for message in std::iter::once(first_message).chain(messages.try_iter()) {
+            match message {
+                Message::Resize => {
+                    geometrics = compute_geometry(&layout, utils::into_my_rect(terminal.size()?))?;
+                    appstate.fit_viewports(&geometrics);
+                }
+                // Everything is swapped together or nothing is,
+                // so that we never draw new layout with old keymap or such
+                // Theme and layout switched to with command line are kept
+                // unless that part of config is what changed
+                Message::Config(Ok(new_config)) => {
+                    let new_layout = if new_config.layout == config.layout {
+                        layout.clone()
+                    } else {
+                        new_config.layout.clone()
+                    };
+                    let terminal_rect = utils::into_my_rect(terminal.size()?);
+                    match compute_geometry(&new_layout, terminal_rect) {
+                        Ok(new_geometrics) => {
+                            geometrics = new_geometrics;
+                            layout = new_layout;
+                            if new_config.theme != config.theme {
+                                theme = new_config.theme.clone();
+                            }
+                            // Old worker ends once it's sender is dropped
+                            if new_config.backend != config.backend {
+                                services.searcher = spawn_searcher(
+                                    &new_config.backend,
+                                    search_response_sender.clone(),
+                                );
+                            }
+                            config = *new_config;
+                            appstate.fit_viewports(&geometrics);
+                            appstate.notification = keymap_notice(&config.keyboard);
+                        }
+                        Err(e) => appstate.notification = Some(format!("Config not reloaded: {e}")),
+                    }
+                    continue;
+                }
+                Message::Config(Err(e)) => {
+                    appstate.notification = Some(format!("Config not reloaded: {e}"));
+                    continue;
+                }
+                _ => (),
+            }
+            match event::reduce(message, &mut appstate, &config, &mut services) {
+                Flow::Continue => (),
+                Flow::Quit => break 'ui_renderer,
+                Flow::UseTheme(new_theme) => theme = new_theme,
+                Flow::UseLayout(new_layout) => {
+                    let terminal_rect = utils::into_my_rect(terminal.size()?);
+                    match compute_geometry(&new_layout, terminal_rect) {
+                        Ok(new_geometrics) => {
+                            geometrics = new_geometrics;
+                            layout = new_layout;
+                            appstate.fit_viewports(&geometrics);
+                        }
+                        Err(e) => appstate.notification = Some(format!("Layout not used: {e}")),
+                    }
+                }
             }
-            EventSummary::Execution(action) if action == KeyboardAction::Quit => break 'ui_renderer,
-            EventSummary::Execution(action) => event::handle_action(action, &mut appstate),
         }
     }
 
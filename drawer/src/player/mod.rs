@@ -0,0 +1,122 @@
+pub mod mpv;
+
+use std::time::Duration;
+
+/// Things that happen inside player on it's own
+/// i.e without being asked for by app
+///
+/// Player implementation is expected to send these
+/// over the channel it was created with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlayerEvent {
+    /// New track is loaded and have started playing
+    TrackStarted,
+    /// Playing track have reached it's end
+    TrackEnded,
+    /// Track was paused (true) or resumed (false)
+    PauseChanged(bool),
+    /// Playing position have moved. Sent at most once a second
+    PositionChanged(Duration),
+    /// Total duration of playing track is known
+    DurationChanged(Duration),
+    /// Volume is now at this percent
+    VolumeChanged(u8),
+    /// Title reported by player for playing track
+    TitleChanged(String),
+}
+
+/// Where to move playing position to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Seek {
+    Forward(Duration),
+    Backward(Duration),
+    Absolute(Duration),
+}
+
+/// Anything that can play music for us
+///
+/// Every method returns Err(description) if player
+/// cannot be reached or refused to do what was asked
+pub trait Player {
+    /// Replace whatever is playing with this url and start playing it
+    fn load(&mut self, url: &str) -> Result<(), String>;
+
+    /// Stop playing and unload whatever is loaded
+    fn stop(&mut self) -> Result<(), String>;
+
+    fn pause(&mut self) -> Result<(), String>;
+
+    fn resume(&mut self) -> Result<(), String>;
+
+    fn is_paused(&mut self) -> Result<bool, String>;
+
+    fn toggle_pause(&mut self) -> Result<(), String> {
+        if self.is_paused()? {
+            self.resume()
+        } else {
+            self.pause()
+        }
+    }
+
+    fn seek(&mut self, seek: Seek) -> Result<(), String>;
+
+    /// Volume in percent, from 0 to 100
+    fn volume(&mut self) -> Result<u8, String>;
+
+    /// Values above 100 are to be clamped to 100
+    fn set_volume(&mut self, volume: u8) -> Result<(), String>;
+
+    /// Returns None if nothing is playing
+    fn position(&mut self) -> Result<Option<Duration>, String>;
+
+    /// Returns None if nothing is playing
+    /// or duration of track is not yet known
+    fn duration(&mut self) -> Result<Option<Duration>, String>;
+}
+
+/// Stands in for player that could not be started
+/// so that everything but playback still works
+/// Whatever is asked of it is ignored
+pub struct NoPlayer;
+
+impl Player for NoPlayer {
+    fn load(&mut self, _url: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn stop(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn pause(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn resume(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn is_paused(&mut self) -> Result<bool, String> {
+        Ok(true)
+    }
+
+    fn seek(&mut self, _seek: Seek) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn volume(&mut self) -> Result<u8, String> {
+        Ok(0)
+    }
+
+    fn set_volume(&mut self, _volume: u8) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn position(&mut self) -> Result<Option<Duration>, String> {
+        Ok(None)
+    }
+
+    fn duration(&mut self) -> Result<Option<Duration>, String> {
+        Ok(None)
+    }
+}
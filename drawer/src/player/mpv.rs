@@ -0,0 +1,473 @@
+use super::{Player, PlayerEvent, Seek};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Write};
+use std::net::Shutdown;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long to wait for mpv to answer a single command
+const REPLY_TIMEOUT: Duration = Duration::from_secs(3);
+/// How long to wait for freshly spawned mpv to create it's ipc socket
+const SPAWN_TIMEOUT: Duration = Duration::from_secs(5);
+/// Error mpv answers with when asked property of track while nothing is loaded
+const PROPERTY_UNAVAILABLE: &str = "property unavailable";
+/// Changes in these properties are reported as PlayerEvent
+const OBSERVED_PROPERTIES: [&str; 5] = ["pause", "time-pos", "duration", "volume", "media-title"];
+
+/// Socket path unique to this process
+pub fn default_socket_path() -> PathBuf {
+    std::env::temp_dir().join(format!("ytui-music-mpv-{}.sock", std::process::id()))
+}
+
+/// Player that drives mpv over it's JSON IPC protocol
+/// See: https://mpv.io/manual/stable/#json-ipc
+pub struct MpvPlayer {
+    socket: UnixStream,
+    replies: Receiver<Value>,
+    last_request_id: u64,
+    /// mpv process, if it was spawned by us
+    /// it is killed when this player is dropped
+    process: Option<(Child, PathBuf)>,
+}
+
+impl MpvPlayer {
+    /// Start a headless mpv that listens on `socket_path` and connect to it
+    pub fn spawn(socket_path: &Path, events: Sender<PlayerEvent>) -> Result<Self, String> {
+        let mut process = Command::new("mpv")
+            .arg("--idle=yes")
+            .arg("--no-video")
+            .arg("--no-terminal")
+            .arg(format!("--input-ipc-server={}", socket_path.display()))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("While spawning mpv: {e:?}"))?;
+
+        // mpv takes a moment before socket is ready
+        let started_at = Instant::now();
+        let socket = loop {
+            match UnixStream::connect(socket_path) {
+                Ok(socket) => break socket,
+                Err(_) if started_at.elapsed() < SPAWN_TIMEOUT => {
+                    thread::sleep(Duration::from_millis(50))
+                }
+                Err(e) => {
+                    let _ = process.kill();
+                    let _ = process.wait();
+                    return Err(format!("While connecting to spawned mpv: {e:?}"));
+                }
+            }
+        };
+
+        let mut player = Self::from_socket(socket, events)?;
+        player.process = Some((process, socket_path.to_path_buf()));
+        Ok(player)
+    }
+
+    /// Connect to already running mpv started with `--input-ipc-server=<socket_path>`
+    pub fn connect(socket_path: &Path, events: Sender<PlayerEvent>) -> Result<Self, String> {
+        let socket = UnixStream::connect(socket_path)
+            .map_err(|e| format!("While connecting to mpv socket: {e:?}"))?;
+        Self::from_socket(socket, events)
+    }
+
+    fn from_socket(socket: UnixStream, events: Sender<PlayerEvent>) -> Result<Self, String> {
+        let reader = socket
+            .try_clone()
+            .map_err(|e| format!("While cloning mpv socket: {e:?}"))?;
+        let (reply_sender, replies) = mpsc::channel();
+        thread::spawn(move || read_messages(reader, reply_sender, events));
+
+        let mut player = MpvPlayer {
+            socket,
+            replies,
+            last_request_id: 0,
+            process: None,
+        };
+        for (id, property) in OBSERVED_PROPERTIES.into_iter().enumerate() {
+            player.command(json!(["observe_property", id + 1, property]))?;
+        }
+
+        Ok(player)
+    }
+
+    /// Send the command and wait for it's reply
+    fn request(&mut self, command: Value) -> Result<Value, String> {
+        self.last_request_id += 1;
+        let request_id = self.last_request_id;
+
+        let mut message = json!({ "command": command, "request_id": request_id }).to_string();
+        message.push('\n');
+        self.socket
+            .write_all(message.as_bytes())
+            .map_err(|e| format!("While writing to mpv socket: {e:?}"))?;
+
+        loop {
+            let reply = self
+                .replies
+                .recv_timeout(REPLY_TIMEOUT)
+                .map_err(|e| match e {
+                    RecvTimeoutError::Timeout => format!("mpv did not reply to {command}"),
+                    RecvTimeoutError::Disconnected => "Connection to mpv is closed".to_string(),
+                })?;
+
+            // reply of some previous command that timed out
+            // can still arrive later. Skip those
+            if reply["request_id"].as_u64() == Some(request_id) {
+                return Ok(reply);
+            }
+        }
+    }
+
+    /// Send the command and return `data` of the reply if it succeed
+    fn command(&mut self, command: Value) -> Result<Value, String> {
+        let mut reply = self.request(command.clone())?;
+        match reply["error"].as_str() {
+            Some("success") => Ok(reply["data"].take()),
+            Some(error) => Err(format!("mpv refused {command}: {error}")),
+            None => Err(format!("mpv replied to {command} without status")),
+        }
+    }
+
+    fn get_property<T: DeserializeOwned>(&mut self, name: &str) -> Result<Option<T>, String> {
+        let command = json!(["get_property", name]);
+        let mut reply = self.request(command.clone())?;
+        match reply["error"].as_str() {
+            Some("success") => serde_json::from_value(reply["data"].take())
+                .map(Some)
+                .map_err(|e| format!("While parsing value of {name}: {e:?}")),
+            Some(PROPERTY_UNAVAILABLE) => Ok(None),
+            Some(error) => Err(format!("mpv refused {command}: {error}")),
+            None => Err(format!("mpv replied to {command} without status")),
+        }
+    }
+
+    fn set_property(&mut self, name: &str, value: Value) -> Result<(), String> {
+        self.command(json!(["set_property", name, value]))
+            .map(|_| ())
+    }
+}
+
+impl Player for MpvPlayer {
+    fn load(&mut self, url: &str) -> Result<(), String> {
+        self.command(json!(["loadfile", url, "replace"]))?;
+        self.resume()
+    }
+
+    fn stop(&mut self) -> Result<(), String> {
+        self.command(json!(["stop"])).map(|_| ())
+    }
+
+    fn pause(&mut self) -> Result<(), String> {
+        self.set_property("pause", json!(true))
+    }
+
+    fn resume(&mut self) -> Result<(), String> {
+        self.set_property("pause", json!(false))
+    }
+
+    fn is_paused(&mut self) -> Result<bool, String> {
+        self.get_property("pause").map(|p| p.unwrap_or(false))
+    }
+
+    fn seek(&mut self, seek: Seek) -> Result<(), String> {
+        let (seconds, mode) = match seek {
+            Seek::Forward(by) => (by.as_secs_f64(), "relative"),
+            Seek::Backward(by) => (-by.as_secs_f64(), "relative"),
+            Seek::Absolute(to) => (to.as_secs_f64(), "absolute"),
+        };
+        self.command(json!(["seek", seconds, mode])).map(|_| ())
+    }
+
+    fn volume(&mut self) -> Result<u8, String> {
+        self.get_property::<f64>("volume")
+            .map(|v| v.map(percent_from_f64).unwrap_or_default())
+    }
+
+    fn set_volume(&mut self, volume: u8) -> Result<(), String> {
+        self.set_property("volume", json!(volume.min(100)))
+    }
+
+    fn position(&mut self) -> Result<Option<Duration>, String> {
+        self.get_property::<f64>("time-pos")
+            .map(|p| p.map(duration_from_f64))
+    }
+
+    fn duration(&mut self) -> Result<Option<Duration>, String> {
+        self.get_property::<f64>("duration")
+            .map(|d| d.map(duration_from_f64))
+    }
+}
+
+impl Drop for MpvPlayer {
+    fn drop(&mut self) {
+        // this also ends the reader thread
+        let _ = self.socket.shutdown(Shutdown::Both);
+
+        if let Some((mut process, socket_path)) = self.process.take() {
+            let _ = process.kill();
+            let _ = process.wait();
+            let _ = std::fs::remove_file(socket_path);
+        }
+    }
+}
+
+fn duration_from_f64(seconds: f64) -> Duration {
+    // mpv may report tiny negative position right after seek
+    Duration::try_from_secs_f64(seconds.max(0.0)).unwrap_or_default()
+}
+
+fn percent_from_f64(percent: f64) -> u8 {
+    percent.clamp(0.0, 100.0).round() as u8
+}
+
+/// Read everything mpv writes to the socket
+/// Events are sent to `events`, everything else is reply to some command
+fn read_messages(socket: UnixStream, replies: Sender<Value>, events: Sender<PlayerEvent>) {
+    let mut last_position_second = None;
+
+    for line in BufReader::new(socket).lines() {
+        let Ok(line) = line else { break };
+        let Ok(message) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+
+        if message.get("event").is_some() {
+            if let Some(event) = into_player_event(&message, &mut last_position_second) {
+                // app not listening for events shall
+                // not stop the replies from arriving
+                let _ = events.send(event);
+            }
+        } else if replies.send(message).is_err() {
+            break;
+        }
+    }
+}
+
+fn into_player_event(
+    message: &Value,
+    last_position_second: &mut Option<u64>,
+) -> Option<PlayerEvent> {
+    match message["event"].as_str()? {
+        "file-loaded" => Some(PlayerEvent::TrackStarted),
+
+        // end-file is also sent with reason `stop` when
+        // track is replaced by another one. That is not an end
+        "end-file" => match message["reason"].as_str()? {
+            "eof" | "error" => Some(PlayerEvent::TrackEnded),
+            _ => None,
+        },
+
+        "property-change" => {
+            let data = &message["data"];
+            match message["name"].as_str()? {
+                "pause" => data.as_bool().map(PlayerEvent::PauseChanged),
+                "duration" => data
+                    .as_f64()
+                    .map(|d| PlayerEvent::DurationChanged(duration_from_f64(d))),
+                "volume" => data
+                    .as_f64()
+                    .map(|v| PlayerEvent::VolumeChanged(percent_from_f64(v))),
+                "media-title" => data
+                    .as_str()
+                    .map(|t| PlayerEvent::TitleChanged(t.to_string())),
+                "time-pos" => {
+                    // time-pos changes on every frame
+                    // only report when whole second changes
+                    let position = duration_from_f64(data.as_f64()?);
+                    if *last_position_second == Some(position.as_secs()) {
+                        None
+                    } else {
+                        *last_position_second = Some(position.as_secs());
+                        Some(PlayerEvent::PositionChanged(position))
+                    }
+                }
+                _ => None,
+            }
+        }
+
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+
+    struct FakeMpv {
+        socket_path: PathBuf,
+        /// every command received, except observe_property
+        commands: Receiver<Value>,
+        /// write raw line to client
+        outgoing: Sender<String>,
+    }
+
+    /// Stand-in for mpv listening on a local socket
+    /// `answer` gets the command and returns (error, data) to reply with
+    fn fake_mpv(name: &str, answer: fn(&Value) -> (&'static str, Value)) -> FakeMpv {
+        let socket_path = std::env::temp_dir().join(format!(
+            "ytui-music-test-{}-{name}.sock",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&socket_path);
+        let listener = UnixListener::bind(&socket_path).unwrap();
+
+        let (command_sender, commands) = mpsc::channel();
+        let (outgoing, outgoing_receiver) = mpsc::channel::<String>();
+        let reply_sender = outgoing.clone();
+
+        thread::spawn(move || {
+            let (socket, _) = listener.accept().unwrap();
+            let mut writer = socket.try_clone().unwrap();
+            thread::spawn(move || {
+                for line in outgoing_receiver {
+                    writer.write_all(format!("{line}\n").as_bytes()).unwrap();
+                }
+            });
+
+            for line in BufReader::new(socket).lines() {
+                let message: Value = serde_json::from_str(&line.unwrap()).unwrap();
+                let command = message["command"].clone();
+                let (error, data) = answer(&command);
+                let reply =
+                    json!({ "request_id": message["request_id"], "error": error, "data": data });
+                reply_sender.send(reply.to_string()).unwrap();
+                if command[0] != "observe_property" {
+                    command_sender.send(command).unwrap();
+                }
+            }
+        });
+
+        FakeMpv {
+            socket_path,
+            commands,
+            outgoing,
+        }
+    }
+
+    fn answer_success(command: &Value) -> (&'static str, Value) {
+        let data = match command[1].as_str() {
+            Some("time-pos") => json!(83.4),
+            Some("duration") => json!(200.0),
+            Some("volume") => json!(40.0),
+            Some("pause") => json!(true),
+            _ => Value::Null,
+        };
+        ("success", data)
+    }
+
+    #[test]
+    fn commands_are_sent_and_answered() {
+        let fake = fake_mpv("commands", answer_success);
+        let (events, _) = mpsc::channel();
+        let mut player = MpvPlayer::connect(&fake.socket_path, events).unwrap();
+
+        assert_eq!(Ok(Some(Duration::from_secs_f64(83.4))), player.position());
+        assert_eq!(Ok(Some(Duration::from_secs(200))), player.duration());
+        assert_eq!(Ok(40), player.volume());
+        for _ in 0..3 {
+            fake.commands.recv().unwrap();
+        }
+
+        player.load("https://example.com/watch?v=xyz").unwrap();
+        assert_eq!(
+            json!(["loadfile", "https://example.com/watch?v=xyz", "replace"]),
+            fake.commands.recv().unwrap()
+        );
+        assert_eq!(
+            json!(["set_property", "pause", false]),
+            fake.commands.recv().unwrap()
+        );
+
+        player
+            .seek(Seek::Backward(Duration::from_secs(10)))
+            .unwrap();
+        assert_eq!(
+            json!(["seek", -10.0, "relative"]),
+            fake.commands.recv().unwrap()
+        );
+
+        player.stop().unwrap();
+        assert_eq!(json!(["stop"]), fake.commands.recv().unwrap());
+
+        player.set_volume(150).unwrap();
+        assert_eq!(
+            json!(["set_property", "volume", 100]),
+            fake.commands.recv().unwrap()
+        );
+
+        // pause is reported as true, so toggling should resume
+        player.toggle_pause().unwrap();
+        assert_eq!(
+            json!(["get_property", "pause"]),
+            fake.commands.recv().unwrap()
+        );
+        assert_eq!(
+            json!(["set_property", "pause", false]),
+            fake.commands.recv().unwrap()
+        );
+
+        let _ = std::fs::remove_file(&fake.socket_path);
+    }
+
+    #[test]
+    fn unavailable_property_is_none() {
+        let fake = fake_mpv("unavailable", |command| {
+            if command[0] == "get_property" {
+                (PROPERTY_UNAVAILABLE, Value::Null)
+            } else {
+                ("success", Value::Null)
+            }
+        });
+        let (events, _) = mpsc::channel();
+        let mut player = MpvPlayer::connect(&fake.socket_path, events).unwrap();
+
+        assert_eq!(Ok(None), player.position());
+        assert_eq!(Ok(None), player.duration());
+        assert_eq!(Ok(false), player.is_paused());
+
+        let _ = std::fs::remove_file(&fake.socket_path);
+    }
+
+    #[test]
+    fn events_are_forwarded() {
+        let fake = fake_mpv("events", answer_success);
+        let (events, event_receiver) = mpsc::channel();
+        let _player = MpvPlayer::connect(&fake.socket_path, events).unwrap();
+
+        let raw_events = [
+            json!({ "event": "file-loaded" }),
+            json!({ "event": "property-change", "id": 2, "name": "time-pos", "data": 2.1 }),
+            // same second as before, should not be reported
+            json!({ "event": "property-change", "id": 2, "name": "time-pos", "data": 2.6 }),
+            json!({ "event": "property-change", "id": 1, "name": "pause", "data": true }),
+            json!({ "event": "property-change", "id": 4, "name": "volume", "data": 55.0 }),
+            json!({ "event": "end-file", "reason": "stop" }),
+            json!({ "event": "end-file", "reason": "eof" }),
+        ];
+        for event in raw_events {
+            fake.outgoing.send(event.to_string()).unwrap();
+        }
+
+        let expected = [
+            PlayerEvent::TrackStarted,
+            PlayerEvent::PositionChanged(Duration::from_secs_f64(2.1)),
+            PlayerEvent::PauseChanged(true),
+            PlayerEvent::VolumeChanged(55),
+            PlayerEvent::TrackEnded,
+        ];
+        for expected_event in expected {
+            let event = event_receiver.recv_timeout(Duration::from_secs(2)).unwrap();
+            assert_eq!(expected_event, event);
+        }
+
+        let _ = std::fs::remove_file(&fake.socket_path);
+    }
+}
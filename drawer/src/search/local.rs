@@ -0,0 +1,145 @@
+use super::SearchProvider;
+use crate::types::query::Query;
+use crate::types::state::QueryResult;
+use crate::types::unit::{ArtistUnit, MusicUnit, PlaylistUnit};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Files with these extensions are taken as music
+const AUDIO_EXTENSIONS: [&str; 8] = ["mp3", "flac", "ogg", "opus", "m4a", "aac", "wav", "webm"];
+
+/// Music files on this machine, found by walking directories
+pub struct LocalProvider;
+
+impl SearchProvider for LocalProvider {
+    fn music(&self, query: &Query) -> Result<QueryResult<MusicUnit>, String> {
+        let list = match query {
+            Query::Local(dir) => music_in(dir)?,
+            _ => Vec::new(),
+        };
+        Ok(QueryResult::new(query, list))
+    }
+
+    fn playlist(&self, query: &Query) -> Result<QueryResult<PlaylistUnit>, String> {
+        Ok(QueryResult::new(query, Vec::new()))
+    }
+
+    fn artist(&self, query: &Query) -> Result<QueryResult<ArtistUnit>, String> {
+        Ok(QueryResult::new(query, Vec::new()))
+    }
+
+    fn playlist_tracks(&self, _playlist_id: &str) -> Result<Vec<MusicUnit>, String> {
+        Ok(Vec::new())
+    }
+}
+
+/// Music in `dir` and directories under it, ordered by path
+/// Directories below it that cannot be read are skipped
+/// Symlinks are followed, but no directory is walked twice
+/// so that a link back up the tree does not loop forever
+fn music_in(dir: &Path) -> Result<Vec<MusicUnit>, String> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    let mut walked = HashSet::new();
+    while let Some(current) = dirs.pop() {
+        if let Ok(real) = fs::canonicalize(&current) {
+            if !walked.insert(real) {
+                continue;
+            }
+        }
+        let entries = match fs::read_dir(&current) {
+            Ok(entries) => entries,
+            Err(e) if current == dir => {
+                return Err(format!("While reading {}: {e}", dir.display()))
+            }
+            Err(_) => continue,
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.is_dir() {
+                dirs.push(path);
+            } else if is_audio(&path) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files.into_iter().map(music_of_file).collect())
+}
+
+fn is_audio(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            AUDIO_EXTENSIONS
+                .iter()
+                .any(|audio| audio.eq_ignore_ascii_case(extension))
+        })
+}
+
+/// File name is all there is to know without reading the tags
+fn music_of_file(path: PathBuf) -> MusicUnit {
+    MusicUnit {
+        title: path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        id: path.display().to_string(),
+        path: Some(path),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn music_files_are_found() {
+        let dir = std::env::temp_dir().join(format!("ytui-local-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("album")).unwrap();
+        for file in ["b.mp3", "cover.jpg", "album/a.FLAC", "notes"] {
+            fs::write(dir.join(file), "").unwrap();
+        }
+
+        let query = Query::Local(dir.clone());
+        let result = LocalProvider.music(&query).unwrap();
+        let titles = result
+            .list
+            .iter()
+            .map(|music| music.title.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["a", "b"], titles);
+        assert_eq!(
+            dir.join("album").join("a.FLAC").display().to_string(),
+            result.list[0].url()
+        );
+        assert!(LocalProvider
+            .music(&Query::Trending)
+            .unwrap()
+            .list
+            .is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(LocalProvider.music(&query).is_err());
+    }
+
+    #[test]
+    fn symlink_cycles_are_walked_once() {
+        use std::os::unix::fs::symlink;
+
+        let dir = std::env::temp_dir().join(format!("ytui-cycle-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("album")).unwrap();
+        fs::write(dir.join("album/a.mp3"), "").unwrap();
+        symlink(&dir, dir.join("album/loop")).unwrap();
+        symlink(dir.join("album"), dir.join("linked")).unwrap();
+
+        let result = LocalProvider.music(&Query::Local(dir.clone())).unwrap();
+        assert_eq!(1, result.list.len());
+        assert_eq!("a", result.list[0].title);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,177 @@
+pub mod local;
+pub mod remote;
+
+use crate::types::query::{Query, ResultKind};
+use crate::types::queue::Enqueue;
+use crate::types::state::{Continuation, QueryResult};
+use crate::types::unit::{ArtistUnit, MusicUnit, PlaylistUnit};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+/// Anything that can answer a Query
+///
+/// A provider only answers for the kind of result a query asks for.
+/// Eg: Query::SearchMusic gives empty result when asked for playlists.
+/// Queries a provider does not know about (like Query::Local
+/// for a remote provider) give empty result as well
+///
+/// Results may come in pages. A result that has more to it carries
+/// a continuation, which is handed back to get the next page
+pub trait SearchProvider {
+    fn music(&self, query: &Query) -> Result<QueryResult<MusicUnit>, String>;
+    fn playlist(&self, query: &Query) -> Result<QueryResult<PlaylistUnit>, String>;
+    fn artist(&self, query: &Query) -> Result<QueryResult<ArtistUnit>, String>;
+    /// Next page of music result. Empty and last by default
+    fn more_music(&self, continuation: &Continuation) -> Result<QueryResult<MusicUnit>, String> {
+        Ok(last_page(continuation))
+    }
+    /// Next page of playlist result. Empty and last by default
+    fn more_playlist(
+        &self,
+        continuation: &Continuation,
+    ) -> Result<QueryResult<PlaylistUnit>, String> {
+        Ok(last_page(continuation))
+    }
+    /// Next page of artist result. Empty and last by default
+    fn more_artist(&self, continuation: &Continuation) -> Result<QueryResult<ArtistUnit>, String> {
+        Ok(last_page(continuation))
+    }
+    /// Every track of playlist with given id, in playlist's own order
+    fn playlist_tracks(&self, playlist_id: &str) -> Result<Vec<MusicUnit>, String>;
+    /// Completions of partly typed search. None by default
+    fn suggestions(&self, _typed: &str) -> Result<Vec<String>, String> {
+        Ok(Vec::new())
+    }
+}
+
+/// Local queries answered by `local` provider, everything else by `remote`
+pub struct Sources<L, R> {
+    pub local: L,
+    pub remote: R,
+}
+
+impl<L: SearchProvider, R: SearchProvider> Sources<L, R> {
+    fn pick(&self, query: &Query) -> &dyn SearchProvider {
+        match query {
+            Query::Local(_) => &self.local,
+            _ => &self.remote,
+        }
+    }
+}
+
+impl<L: SearchProvider, R: SearchProvider> SearchProvider for Sources<L, R> {
+    fn music(&self, query: &Query) -> Result<QueryResult<MusicUnit>, String> {
+        self.pick(query).music(query)
+    }
+    fn playlist(&self, query: &Query) -> Result<QueryResult<PlaylistUnit>, String> {
+        self.pick(query).playlist(query)
+    }
+    fn artist(&self, query: &Query) -> Result<QueryResult<ArtistUnit>, String> {
+        self.pick(query).artist(query)
+    }
+    fn more_music(&self, continuation: &Continuation) -> Result<QueryResult<MusicUnit>, String> {
+        self.pick(&continuation.query).more_music(continuation)
+    }
+    fn more_playlist(
+        &self,
+        continuation: &Continuation,
+    ) -> Result<QueryResult<PlaylistUnit>, String> {
+        self.pick(&continuation.query).more_playlist(continuation)
+    }
+    fn more_artist(&self, continuation: &Continuation) -> Result<QueryResult<ArtistUnit>, String> {
+        self.pick(&continuation.query).more_artist(continuation)
+    }
+    fn playlist_tracks(&self, playlist_id: &str) -> Result<Vec<MusicUnit>, String> {
+        self.remote.playlist_tracks(playlist_id)
+    }
+    fn suggestions(&self, typed: &str) -> Result<Vec<String>, String> {
+        self.remote.suggestions(typed)
+    }
+}
+
+/// Things worker can be asked to fetch
+pub enum SearchRequest {
+    Query(Query),
+    /// Tracks of this playlist, to be put in queue as said
+    PlaylistTracks {
+        id: String,
+        enqueue: Enqueue,
+    },
+    /// Completions of this partly typed search
+    Suggestions(String),
+    /// Page of result of given kind that continuation leads to
+    NextPage(ResultKind, Continuation),
+}
+
+/// Outcome of a query as sent back by the worker
+/// Later pages of a result come back as the same kind of response
+pub enum SearchResponse {
+    Music(Result<QueryResult<MusicUnit>, String>),
+    Playlist(Result<QueryResult<PlaylistUnit>, String>),
+    Artist(Result<QueryResult<ArtistUnit>, String>),
+    PlaylistTracks(Result<Vec<MusicUnit>, String>, Enqueue),
+    /// Suggestions along with what they were asked for
+    Suggestions(String, Result<Vec<String>, String>),
+}
+
+/// Run requests sent to returned sender in background
+/// Each query produces one response of every kind, music being first
+pub fn spawn_worker<P>(provider: P, responses: Sender<SearchResponse>) -> Sender<SearchRequest>
+where
+    P: SearchProvider + Send + 'static,
+{
+    let (request_sender, requests) = mpsc::channel::<SearchRequest>();
+    thread::spawn(move || {
+        for request in requests {
+            // false once there is no one to receive responses
+            let sent = match request {
+                SearchRequest::Query(query) => {
+                    responses
+                        .send(SearchResponse::Music(provider.music(&query)))
+                        .is_ok()
+                        && responses
+                            .send(SearchResponse::Playlist(provider.playlist(&query)))
+                            .is_ok()
+                        && responses
+                            .send(SearchResponse::Artist(provider.artist(&query)))
+                            .is_ok()
+                }
+                SearchRequest::PlaylistTracks { id, enqueue } => responses
+                    .send(SearchResponse::PlaylistTracks(
+                        provider.playlist_tracks(&id),
+                        enqueue,
+                    ))
+                    .is_ok(),
+                SearchRequest::Suggestions(typed) => {
+                    let suggestions = provider.suggestions(&typed);
+                    responses
+                        .send(SearchResponse::Suggestions(typed, suggestions))
+                        .is_ok()
+                }
+                SearchRequest::NextPage(kind, continuation) => {
+                    let response = match kind {
+                        ResultKind::Music => {
+                            SearchResponse::Music(provider.more_music(&continuation))
+                        }
+                        ResultKind::Playlist => {
+                            SearchResponse::Playlist(provider.more_playlist(&continuation))
+                        }
+                        ResultKind::Artist => {
+                            SearchResponse::Artist(provider.more_artist(&continuation))
+                        }
+                    };
+                    responses.send(response).is_ok()
+                }
+            };
+            if !sent {
+                break;
+            }
+        }
+    });
+    request_sender
+}
+
+/// Page with nothing in it after which there is no more
+fn last_page<T>(continuation: &Continuation) -> QueryResult<T> {
+    QueryResult::page(&continuation.query, continuation.page, Vec::new(), None)
+}
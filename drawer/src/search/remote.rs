@@ -0,0 +1,874 @@
+use super::SearchProvider;
+use crate::types::query::{Query, ResultKind, SearchQuery};
+use crate::types::state::{Continuation, QueryResult};
+use crate::types::unit::{ArtistUnit, MusicUnit, PlaylistUnit};
+use crate::types::utils::{parse_track_length, year_of_timestamp};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::time::Duration;
+use user_config::backend::{ApiFlavor, Backend};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Single entry of any kind as returned by instance
+enum Item {
+    Music(MusicUnit),
+    Playlist(PlaylistUnit),
+    Artist(ArtistUnit),
+}
+
+impl Item {
+    fn into_music(self) -> Option<MusicUnit> {
+        match self {
+            Item::Music(music) => Some(music),
+            _ => None,
+        }
+    }
+
+    fn into_playlist(self) -> Option<PlaylistUnit> {
+        match self {
+            Item::Playlist(playlist) => Some(playlist),
+            _ => None,
+        }
+    }
+
+    fn into_artist(self) -> Option<ArtistUnit> {
+        match self {
+            Item::Artist(artist) => Some(artist),
+            _ => None,
+        }
+    }
+}
+
+/// Items of a page along with token to get the next one, if any
+type Page = (Vec<Item>, Option<String>);
+
+/// Search on an Invidious or Piped instance over it's JSON api
+pub struct RemoteProvider {
+    agent: ureq::Agent,
+    instance: String,
+    flavor: ApiFlavor,
+}
+
+impl RemoteProvider {
+    pub fn new(backend: &Backend) -> Self {
+        RemoteProvider {
+            agent: ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build(),
+            instance: backend.instance.trim_end_matches('/').to_string(),
+            flavor: backend.flavor,
+        }
+    }
+
+    fn get<T: DeserializeOwned>(&self, path: &str, params: &[(&str, &str)]) -> Result<T, String> {
+        let url = format!("{}{path}", self.instance);
+        params
+            .iter()
+            .fold(self.agent.get(&url), |request, (param, value)| {
+                request.query(param, value)
+            })
+            .call()
+            .map_err(|e| format!("While requesting {url}: {e}"))?
+            .into_json()
+            .map_err(|e| format!("While parsing response from {url}: {e:?}"))
+    }
+
+    /// Page of result of `kind` and number `page`, either
+    /// the first one or the one that `token` leads to
+    fn result_page<T>(
+        &self,
+        query: &Query,
+        kind: ResultKind,
+        page: usize,
+        token: Option<&str>,
+        pick: fn(Item) -> Option<T>,
+    ) -> Result<QueryResult<T>, String> {
+        let (items, next) = self.fetch(query, kind, token)?;
+        let list = items.into_iter().filter_map(pick).collect();
+        Ok(QueryResult::page(query, page, list, next))
+    }
+
+    fn fetch(&self, query: &Query, kind: ResultKind, token: Option<&str>) -> Result<Page, String> {
+        let search = match (query, kind) {
+            (Query::SearchAll(search), _) => search,
+            (Query::SearchMusic(search), ResultKind::Music) => search,
+            (Query::SearchPlaylist(search), ResultKind::Playlist) => search,
+            // trending and playlists come as a single page
+            (Query::Trending, ResultKind::Music) if token.is_none() => {
+                return Ok((self.fetch_trending()?, None))
+            }
+            (Query::Playlist(id), ResultKind::Music) if token.is_none() => {
+                let tracks = self.playlist_tracks(id)?;
+                return Ok((tracks.into_iter().map(Item::Music).collect(), None));
+            }
+            _ => return Ok((Vec::new(), None)),
+        };
+        let search_text = search.search_text();
+        if !search.wants(kind) || search_text.is_empty() {
+            return Ok((Vec::new(), None));
+        }
+        let keeps = |item: &Item| keeps(search, item);
+
+        match self.flavor {
+            ApiFlavor::Invidious => {
+                let search_type = match kind {
+                    ResultKind::Music => "video",
+                    ResultKind::Playlist => "playlist",
+                    ResultKind::Artist => "channel",
+                };
+                // pages are numbered from 1, token being the number
+                let page = token.unwrap_or("1");
+                let number: usize = page
+                    .parse()
+                    .map_err(|_| format!("Invalid page number {page}"))?;
+                let items: Vec<InvidiousItem> = self.get(
+                    "/api/v1/search",
+                    &[("q", &search_text), ("type", search_type), ("page", page)],
+                )?;
+                // there is no telling if a page is last until an empty one comes
+                let next = (!items.is_empty()).then(|| (number + 1).to_string());
+                let items = items
+                    .into_iter()
+                    .filter_map(InvidiousItem::into_item)
+                    .filter(keeps)
+                    .collect();
+                Ok((items, next))
+            }
+            ApiFlavor::Piped => {
+                let filter = match kind {
+                    ResultKind::Music => "music_songs",
+                    ResultKind::Playlist => "music_playlists",
+                    ResultKind::Artist => "music_artists",
+                };
+                let params = [("q", search_text.as_str()), ("filter", filter)];
+                let page: PipedPage = match token {
+                    Some(token) => self.get(
+                        "/nextpage/search",
+                        &[params[0], params[1], ("nextpage", token)],
+                    )?,
+                    None => self.get("/search", &params)?,
+                };
+                let items = page
+                    .items
+                    .into_iter()
+                    .filter_map(PipedItem::into_item)
+                    .filter(keeps)
+                    .collect();
+                Ok((items, page.nextpage))
+            }
+        }
+    }
+
+    fn fetch_trending(&self) -> Result<Vec<Item>, String> {
+        match self.flavor {
+            ApiFlavor::Invidious => {
+                let items: Vec<InvidiousItem> =
+                    self.get("/api/v1/trending", &[("type", "music")])?;
+                Ok(items
+                    .into_iter()
+                    .filter_map(InvidiousItem::into_item)
+                    .collect())
+            }
+            ApiFlavor::Piped => {
+                let items: Vec<PipedItem> = self.get("/trending", &[("region", "US")])?;
+                Ok(items.into_iter().filter_map(PipedItem::into_item).collect())
+            }
+        }
+    }
+}
+
+impl SearchProvider for RemoteProvider {
+    fn music(&self, query: &Query) -> Result<QueryResult<MusicUnit>, String> {
+        self.result_page(query, ResultKind::Music, 0, None, Item::into_music)
+    }
+
+    fn playlist(&self, query: &Query) -> Result<QueryResult<PlaylistUnit>, String> {
+        self.result_page(query, ResultKind::Playlist, 0, None, Item::into_playlist)
+    }
+
+    fn artist(&self, query: &Query) -> Result<QueryResult<ArtistUnit>, String> {
+        self.result_page(query, ResultKind::Artist, 0, None, Item::into_artist)
+    }
+
+    fn more_music(&self, continuation: &Continuation) -> Result<QueryResult<MusicUnit>, String> {
+        let Continuation { query, page, token } = continuation;
+        self.result_page(
+            query,
+            ResultKind::Music,
+            *page,
+            Some(token),
+            Item::into_music,
+        )
+    }
+
+    fn more_playlist(
+        &self,
+        continuation: &Continuation,
+    ) -> Result<QueryResult<PlaylistUnit>, String> {
+        let Continuation { query, page, token } = continuation;
+        self.result_page(
+            query,
+            ResultKind::Playlist,
+            *page,
+            Some(token),
+            Item::into_playlist,
+        )
+    }
+
+    fn more_artist(&self, continuation: &Continuation) -> Result<QueryResult<ArtistUnit>, String> {
+        let Continuation { query, page, token } = continuation;
+        self.result_page(
+            query,
+            ResultKind::Artist,
+            *page,
+            Some(token),
+            Item::into_artist,
+        )
+    }
+
+    fn playlist_tracks(&self, playlist_id: &str) -> Result<Vec<MusicUnit>, String> {
+        match self.flavor {
+            ApiFlavor::Invidious => {
+                let playlist: InvidiousPlaylist =
+                    self.get(&format!("/api/v1/playlists/{playlist_id}"), &[])?;
+                Ok(playlist
+                    .videos
+                    .into_iter()
+                    .map(|video| MusicUnit {
+                        title: video.title,
+                        artist: video.author,
+                        duration: video.length_seconds.into_duration(),
+                        id: video.video_id,
+                        ..Default::default()
+                    })
+                    .collect())
+            }
+            ApiFlavor::Piped => {
+                let playlist: PipedPlaylist =
+                    self.get(&format!("/playlists/{playlist_id}"), &[])?;
+                Ok(playlist
+                    .related_streams
+                    .into_iter()
+                    .filter_map(|item| match item.into_item() {
+                        Some(Item::Music(music)) => Some(music),
+                        _ => None,
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    fn suggestions(&self, typed: &str) -> Result<Vec<String>, String> {
+        match self.flavor {
+            ApiFlavor::Invidious => {
+                let response: InvidiousSuggestions =
+                    self.get("/api/v1/search/suggestions", &[("q", typed)])?;
+                Ok(response.suggestions)
+            }
+            ApiFlavor::Piped => self.get("/suggestions", &[("query", typed)]),
+        }
+    }
+}
+
+/// If item is let through by filters of search
+fn keeps(search: &SearchQuery, item: &Item) -> bool {
+    match item {
+        Item::Music(music) => search.keeps_music(music),
+        Item::Playlist(playlist) => search.keeps_playlist(playlist),
+        Item::Artist(artist) => search.keeps_artist(artist),
+    }
+}
+
+/// Length of video as told by instance
+/// Usually seconds, but some instances give text like `3:12` or `PT3M12S`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Length {
+    Seconds(f64),
+    Text(String),
+}
+
+impl Length {
+    /// None for live streams (which have length of 0 or -1)
+    /// and for text that could not be read
+    fn into_duration(self) -> Option<Duration> {
+        let duration = match self {
+            Length::Seconds(seconds) if seconds > 0.0 => {
+                Duration::try_from_secs_f64(seconds).ok()?
+            }
+            Length::Seconds(_) => return None,
+            Length::Text(text) => parse_track_length(&text).ok()?,
+        };
+        Some(duration).filter(|duration| !duration.is_zero())
+    }
+}
+
+/// Response of /api/v1/playlists/:id
+#[derive(Deserialize)]
+struct InvidiousPlaylist {
+    videos: Vec<InvidiousPlaylistVideo>,
+}
+
+/// Videos in playlist are not tagged with type like search items are
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InvidiousPlaylistVideo {
+    title: String,
+    video_id: String,
+    author: String,
+    length_seconds: Length,
+}
+
+/// Response of /api/v1/search/suggestions
+#[derive(Deserialize)]
+struct InvidiousSuggestions {
+    suggestions: Vec<String>,
+}
+
+/// Item in response of /api/v1/search and /api/v1/trending
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum InvidiousItem {
+    #[serde(rename_all = "camelCase")]
+    Video {
+        title: String,
+        video_id: String,
+        author: String,
+        length_seconds: Length,
+        view_count: Option<u64>,
+        published_text: Option<String>,
+        /// Unix timestamp in seconds
+        published: Option<i64>,
+        description: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Playlist {
+        title: String,
+        playlist_id: String,
+        author: String,
+        /// -1 or missing if unknown
+        #[serde(default)]
+        video_count: i64,
+    },
+    #[serde(rename_all = "camelCase")]
+    Channel {
+        author: String,
+        author_id: String,
+        /// -1 or missing if unknown
+        #[serde(default)]
+        sub_count: i64,
+    },
+    #[serde(other)]
+    Other,
+}
+
+impl InvidiousItem {
+    fn into_item(self) -> Option<Item> {
+        match self {
+            InvidiousItem::Video {
+                title,
+                video_id,
+                author,
+                length_seconds,
+                view_count,
+                published_text,
+                published,
+                description,
+            } => Some(Item::Music(MusicUnit {
+                title,
+                artist: author,
+                duration: length_seconds.into_duration(),
+                id: video_id,
+                views: view_count,
+                uploaded: published_text,
+                year: published.map(year_of_timestamp),
+                description,
+                path: None,
+            })),
+            InvidiousItem::Playlist {
+                title,
+                playlist_id,
+                author,
+                video_count,
+            } => Some(Item::Playlist(PlaylistUnit {
+                title,
+                creator: author,
+                song_count: video_count.max(0) as usize,
+                id: playlist_id,
+            })),
+            InvidiousItem::Channel {
+                author,
+                author_id,
+                sub_count,
+            } => Some(Item::Artist(ArtistUnit {
+                name: author,
+                subscriber_count: sub_count.max(0) as usize,
+                id: author_id,
+            })),
+            InvidiousItem::Other => None,
+        }
+    }
+}
+
+/// Response of /search and /nextpage/search
+#[derive(Deserialize)]
+struct PipedPage {
+    items: Vec<PipedItem>,
+    /// Token for page after this one. None on last page
+    nextpage: Option<String>,
+}
+
+/// Response of /playlists/:id
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PipedPlaylist {
+    related_streams: Vec<PipedItem>,
+}
+
+/// Item in response of /search and /trending
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum PipedItem {
+    #[serde(rename_all = "camelCase")]
+    Stream {
+        url: String,
+        title: String,
+        uploader_name: Option<String>,
+        /// -1 for live streams
+        duration: Length,
+        /// -1 if unknown
+        #[serde(default)]
+        views: i64,
+        uploaded_date: Option<String>,
+        /// Unix timestamp in milliseconds, -1 if unknown
+        uploaded: Option<i64>,
+        short_description: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Playlist {
+        url: String,
+        name: String,
+        uploader_name: Option<String>,
+        /// -1 if unknown
+        videos: i64,
+    },
+    Channel {
+        url: String,
+        name: String,
+        /// -1 if unknown
+        subscribers: i64,
+    },
+    #[serde(other)]
+    Other,
+}
+
+impl PipedItem {
+    fn into_item(self) -> Option<Item> {
+        match self {
+            PipedItem::Stream {
+                url,
+                title,
+                uploader_name,
+                duration,
+                views,
+                uploaded_date,
+                uploaded,
+                short_description,
+            } => Some(Item::Music(MusicUnit {
+                title,
+                artist: uploader_name.unwrap_or_default(),
+                duration: duration.into_duration(),
+                id: url.strip_prefix("/watch?v=")?.to_string(),
+                views: u64::try_from(views).ok(),
+                uploaded: uploaded_date,
+                year: uploaded
+                    .filter(|uploaded| *uploaded >= 0)
+                    .map(|uploaded| year_of_timestamp(uploaded / 1000)),
+                description: short_description,
+                path: None,
+            })),
+            PipedItem::Playlist {
+                url,
+                name,
+                uploader_name,
+                videos,
+            } => Some(Item::Playlist(PlaylistUnit {
+                title: name,
+                creator: uploader_name.unwrap_or_default(),
+                song_count: videos.max(0) as usize,
+                id: url.strip_prefix("/playlist?list=")?.to_string(),
+            })),
+            PipedItem::Channel {
+                url,
+                name,
+                subscribers,
+            } => Some(Item::Artist(ArtistUnit {
+                name,
+                subscriber_count: subscribers.max(0) as usize,
+                id: url.strip_prefix("/channel/")?.to_string(),
+            })),
+            PipedItem::Other => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::thread;
+
+    /// Serve `responses` in order, one per connection, as json with given status
+    /// Returns base url of server and receiver of requested paths
+    fn mock_server(responses: Vec<(u16, &'static str)>) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let (path_sender, paths) = mpsc::channel();
+
+        thread::spawn(move || {
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                // skip the headers
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap() > 2 {
+                    header.clear();
+                }
+
+                let path = request_line.split_whitespace().nth(1).unwrap().to_string();
+                path_sender.send(path).unwrap();
+
+                let response = format!(
+                    "HTTP/1.1 {status} Whatever\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        (format!("http://{address}"), paths)
+    }
+
+    fn provider(flavor: ApiFlavor, instance: String) -> RemoteProvider {
+        RemoteProvider::new(&Backend {
+            flavor,
+            instance,
+            suggestions: true,
+        })
+    }
+
+    fn search(text: &str) -> SearchQuery {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn invidious_music_search() {
+        let body = r#"[
+            { "type": "video", "title": "Aagya", "videoId": "abc123", "author": "Rachana Dahal", "lengthSeconds": 223, "viewCount": 10, "publishedText": "3 years ago", "published": 1609459200, "description": "Official video" },
+            { "type": "channel", "author": "Someone", "authorId": "UC1", "subCount": 3 },
+            { "type": "video", "title": "Long mix", "videoId": "def456", "author": "Various", "lengthSeconds": "PT1H2M5S" },
+            { "type": "video", "title": "Broken", "videoId": "ghi789", "author": "Various", "lengthSeconds": 1e300 },
+            { "type": "video", "title": "Broken too", "videoId": "jkl012", "author": "Various", "lengthSeconds": "PT1e300S" }
+        ]"#;
+        let (instance, paths) = mock_server(vec![(200, body)]);
+        let provider = provider(ApiFlavor::Invidious, format!("{instance}/"));
+
+        let query = Query::SearchMusic(search("rachana dahal"));
+        let result = provider.music(&query).unwrap();
+
+        let path = paths.recv().unwrap();
+        assert!(path.starts_with("/api/v1/search?"), "{path}");
+        assert!(path.contains("q=rachana"), "{path}");
+        assert!(path.contains("type=video"), "{path}");
+
+        assert_eq!("rachana dahal type:music", result.query.as_str());
+        assert_eq!(4, result.list.len());
+        assert_eq!("Aagya", result.list[0].title);
+        assert_eq!("Rachana Dahal", result.list[0].artist);
+        assert_eq!(Some(Duration::from_secs(223)), result.list[0].duration);
+        assert_eq!("abc123", result.list[0].id);
+        assert_eq!(Some(10), result.list[0].views);
+        assert_eq!(Some("3 years ago"), result.list[0].uploaded.as_deref());
+        assert_eq!(Some(2021), result.list[0].year);
+        assert_eq!(
+            Some("Official video"),
+            result.list[0].description.as_deref()
+        );
+        assert_eq!(Some(Duration::from_secs(3725)), result.list[1].duration);
+        assert_eq!(None, result.list[1].views);
+        // lengths too long to be real are taken as unknown
+        assert_eq!(None, result.list[2].duration);
+        assert_eq!(None, result.list[3].duration);
+    }
+
+    #[test]
+    fn piped_playlist_search() {
+        let body = r#"{
+            "items": [
+                { "type": "playlist", "name": "Bimbakash", "url": "/playlist?list=PL42", "uploaderName": "Bartika Eam Rai", "videos": 17 },
+                { "type": "playlist", "name": "Unknown size", "url": "/playlist?list=PL43", "uploaderName": null, "videos": -1 }
+            ],
+            "nextpage": null
+        }"#;
+        let (instance, paths) = mock_server(vec![(200, body)]);
+        let provider = provider(ApiFlavor::Piped, instance);
+
+        let query = Query::SearchAll(search("bimbakash"));
+        let result = provider.playlist(&query).unwrap();
+
+        let path = paths.recv().unwrap();
+        assert!(path.starts_with("/search?"), "{path}");
+        assert!(path.contains("filter=music_playlists"), "{path}");
+
+        assert_eq!(2, result.list.len());
+        assert_eq!("Bimbakash", result.list[0].title);
+        assert_eq!("Bartika Eam Rai", result.list[0].creator);
+        assert_eq!(17, result.list[0].song_count);
+        assert_eq!("PL42", result.list[0].id);
+        assert_eq!("", result.list[1].creator);
+        assert_eq!(0, result.list[1].song_count);
+    }
+
+    #[test]
+    fn invidious_pages() {
+        let body = r#"[
+            { "type": "video", "title": "Aagya", "videoId": "abc123", "author": "Rachana Dahal", "lengthSeconds": 223 }
+        ]"#;
+        let (instance, paths) = mock_server(vec![(200, body), (200, "[]")]);
+        let provider = provider(ApiFlavor::Invidious, instance);
+
+        let query = Query::SearchAll(search("aagya"));
+        let first = provider.music(&query).unwrap();
+        assert!(paths.recv().unwrap().contains("page=1"));
+        let continuation = first.continuation.unwrap();
+        assert_eq!(query, continuation.query);
+        assert_eq!(1, continuation.page);
+        assert_eq!("2", continuation.token);
+
+        let second = provider.more_music(&continuation).unwrap();
+        assert!(paths.recv().unwrap().contains("page=2"));
+        assert_eq!(1, second.page);
+        assert!(second.list.is_empty());
+        assert_eq!(None, second.continuation);
+    }
+
+    #[test]
+    fn piped_pages() {
+        let first_body = r#"{
+            "items": [
+                { "type": "channel", "name": "Bartika Eam Rai", "url": "/channel/UC42", "subscribers": 120000 }
+            ],
+            "nextpage": "{\"token\":\"abc\"}"
+        }"#;
+        let last_body = r#"{
+            "items": [
+                { "type": "channel", "name": "Bartika Fan", "url": "/channel/UC43", "subscribers": 12 }
+            ],
+            "nextpage": null
+        }"#;
+        let (instance, paths) = mock_server(vec![(200, first_body), (200, last_body)]);
+        let provider = provider(ApiFlavor::Piped, instance);
+
+        let first = provider
+            .artist(&Query::SearchAll(search("bartika")))
+            .unwrap();
+        assert!(paths.recv().unwrap().starts_with("/search?"));
+        let continuation = first.continuation.unwrap();
+        assert_eq!(r#"{"token":"abc"}"#, continuation.token);
+
+        let last = provider.more_artist(&continuation).unwrap();
+        let path = paths.recv().unwrap();
+        assert!(path.starts_with("/nextpage/search?"), "{path}");
+        assert!(path.contains("filter=music_artists"), "{path}");
+        assert!(path.contains("nextpage=%7B"), "{path}");
+        assert_eq!("Bartika Fan", last.list[0].name);
+        assert_eq!(None, last.continuation);
+    }
+
+    #[test]
+    fn piped_artist_search() {
+        let body = r#"{
+            "items": [
+                { "type": "channel", "name": "Bartika Eam Rai", "url": "/channel/UC42", "subscribers": 120000, "verified": true },
+                { "type": "channel", "name": "Hidden count", "url": "/channel/UC43", "subscribers": -1 }
+            ],
+            "nextpage": null
+        }"#;
+        let (instance, paths) = mock_server(vec![(200, body)]);
+        let provider = provider(ApiFlavor::Piped, instance);
+
+        let result = provider
+            .artist(&Query::SearchAll(search("bartika")))
+            .unwrap();
+
+        assert!(paths.recv().unwrap().contains("filter=music_artists"));
+        assert_eq!(2, result.list.len());
+        assert_eq!("Bartika Eam Rai", result.list[0].name);
+        assert_eq!(120000, result.list[0].subscriber_count);
+        assert_eq!("UC42", result.list[0].id);
+        assert_eq!(0, result.list[1].subscriber_count);
+    }
+
+    #[test]
+    fn invidious_unknown_counts() {
+        let body = r#"[
+            { "type": "playlist", "title": "Hidden", "playlistId": "PL1", "author": "Someone", "videoCount": -1 },
+            { "type": "playlist", "title": "Missing", "playlistId": "PL2", "author": "Someone" },
+            { "type": "channel", "author": "Hidden", "authorId": "UC1", "subCount": -1 },
+            { "type": "channel", "author": "Missing", "authorId": "UC2" }
+        ]"#;
+        let (instance, _paths) = mock_server(vec![(200, body), (200, body)]);
+        let provider = provider(ApiFlavor::Invidious, instance);
+        let query = Query::SearchAll(search("hidden"));
+
+        let playlists = provider.playlist(&query).unwrap();
+        assert_eq!(2, playlists.list.len());
+        assert!(playlists.list.iter().all(|p| p.song_count == 0));
+
+        let artists = provider.artist(&query).unwrap();
+        assert_eq!(2, artists.list.len());
+        assert!(artists.list.iter().all(|a| a.subscriber_count == 0));
+    }
+
+    #[test]
+    fn piped_trending() {
+        let body = r#"[
+            { "type": "stream", "url": "/watch?v=tr1", "title": "Trending one", "uploaderName": "Uploader", "duration": 61, "views": -1, "uploadedDate": "2 days ago" },
+            { "type": "stream", "url": "/watch?v=live", "title": "Live now", "uploaderName": "Uploader", "duration": -1 },
+            { "type": "stream", "url": "/watch?v=tr2", "title": "As text", "uploaderName": "Uploader", "duration": "04:05" }
+        ]"#;
+        let (instance, paths) = mock_server(vec![(200, body)]);
+        let provider = provider(ApiFlavor::Piped, instance);
+
+        let result = provider.music(&Query::Trending).unwrap();
+
+        assert!(paths.recv().unwrap().starts_with("/trending?region="));
+        assert_eq!(3, result.list.len());
+        assert_eq!("tr1", result.list[0].id);
+        assert_eq!(Some(Duration::from_secs(61)), result.list[0].duration);
+        assert_eq!(None, result.list[0].views);
+        assert_eq!(Some("2 days ago"), result.list[0].uploaded.as_deref());
+        assert_eq!(None, result.list[1].duration);
+        assert_eq!(Some(Duration::from_secs(245)), result.list[2].duration);
+    }
+
+    #[test]
+    fn playlist_tracks_of_both_flavors() {
+        let invidious_body = r#"{
+            "title": "Bimbakash",
+            "playlistId": "PL42",
+            "videos": [
+                { "title": "First", "videoId": "v1", "author": "Bartika Eam Rai", "lengthSeconds": 200, "index": 0 },
+                { "title": "Second", "videoId": "v2", "author": "Bartika Eam Rai", "lengthSeconds": 190, "index": 1 }
+            ]
+        }"#;
+        let piped_body = r#"{
+            "name": "Bimbakash",
+            "relatedStreams": [
+                { "type": "stream", "url": "/watch?v=v1", "title": "First", "uploaderName": "Bartika Eam Rai", "duration": 200 }
+            ],
+            "nextpage": null
+        }"#;
+        let (instance, paths) = mock_server(vec![(200, invidious_body), (200, piped_body)]);
+
+        let tracks = provider(ApiFlavor::Invidious, instance.clone())
+            .playlist_tracks("PL42")
+            .unwrap();
+        assert_eq!("/api/v1/playlists/PL42", paths.recv().unwrap());
+        assert_eq!(
+            vec!["v1", "v2"],
+            tracks.iter().map(|t| t.id.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!(Some(Duration::from_secs(200)), tracks[0].duration);
+
+        let tracks = provider(ApiFlavor::Piped, instance)
+            .playlist_tracks("PL42")
+            .unwrap();
+        assert_eq!("/playlists/PL42", paths.recv().unwrap());
+        assert_eq!(1, tracks.len());
+        assert_eq!("First", tracks[0].title);
+    }
+
+    #[test]
+    fn unrelated_query_is_not_requested() {
+        // Nothing is served, any request would fail
+        let provider = provider(ApiFlavor::Invidious, "http://127.0.0.1:9".to_string());
+
+        let query = Query::SearchMusic(search("only music"));
+        assert!(provider.playlist(&query).unwrap().list.is_empty());
+        assert!(provider.artist(&query).unwrap().list.is_empty());
+        let local = Query::Local("/music".into());
+        assert!(provider.music(&local).unwrap().list.is_empty());
+        let playlist = Query::Playlist("PL42".to_string());
+        assert!(provider.artist(&playlist).unwrap().list.is_empty());
+
+        let query = Query::SearchAll(search("only type:artist"));
+        assert!(provider.music(&query).unwrap().list.is_empty());
+        // filters alone have nothing to search for
+        let query = Query::SearchAll(search("duration:<5m -live"));
+        assert!(provider.music(&query).unwrap().list.is_empty());
+    }
+
+    #[test]
+    fn filters_pick_from_results() {
+        let body = r#"{
+            "items": [
+                { "type": "stream", "url": "/watch?v=v1", "title": "Tal Ma", "uploaderName": "Bartika Eam Rai", "duration": 240, "uploaded": 1609459200000 },
+                { "type": "stream", "url": "/watch?v=v2", "title": "Tal Ma (Live)", "uploaderName": "Bartika Eam Rai", "duration": 250, "uploaded": 1609459200000 },
+                { "type": "stream", "url": "/watch?v=v3", "title": "Tal Ma", "uploaderName": "Bartika Eam Rai", "duration": 240, "uploaded": 1262304000000 },
+                { "type": "stream", "url": "/watch?v=v4", "title": "Tal Ma", "uploaderName": "Bartika Eam Rai", "duration": 240, "uploaded": -1 }
+            ],
+            "nextpage": null
+        }"#;
+        let (instance, paths) = mock_server(vec![(200, body)]);
+        let provider = provider(ApiFlavor::Piped, instance);
+
+        let query = Query::SearchAll(search(r#""tal ma" -live year:>2015"#));
+        let result = provider.music(&query).unwrap();
+
+        let path = paths.recv().unwrap();
+        assert!(path.contains("q=%22tal+ma%22") || path.contains("q=%22tal%20ma%22"));
+        assert!(!path.contains("live"), "{path}");
+        assert_eq!(
+            vec!["v1"],
+            result
+                .list
+                .iter()
+                .map(|m| m.id.as_str())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn suggestions_of_both_flavors() {
+        let invidious_body =
+            r#"{ "query": "bart", "suggestions": ["bartika eam rai", "bartender"] }"#;
+        let piped_body = r#"["bartika eam rai"]"#;
+        let (instance, paths) = mock_server(vec![(200, invidious_body), (200, piped_body)]);
+
+        let suggestions = provider(ApiFlavor::Invidious, instance.clone())
+            .suggestions("bart")
+            .unwrap();
+        assert_eq!("/api/v1/search/suggestions?q=bart", paths.recv().unwrap());
+        assert_eq!(vec!["bartika eam rai", "bartender"], suggestions);
+
+        let suggestions = provider(ApiFlavor::Piped, instance)
+            .suggestions("bart")
+            .unwrap();
+        assert_eq!("/suggestions?query=bart", paths.recv().unwrap());
+        assert_eq!(vec!["bartika eam rai"], suggestions);
+    }
+
+    #[test]
+    fn server_error_is_reported() {
+        let (instance, _paths) = mock_server(vec![(500, r#"{"error": "boom"}"#)]);
+        let provider = provider(ApiFlavor::Invidious, instance);
+
+        let query = Query::SearchAll(search("anything"));
+        assert!(provider.music(&query).is_err());
+    }
+}
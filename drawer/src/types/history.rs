@@ -0,0 +1,237 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use user_config::action::MoveDirection;
+
+/// Oldest searches are forgotten past this many
+const HISTORY_LIMIT: usize = 1000;
+
+pub fn get_history_path() -> Result<PathBuf, &'static str> {
+    let data_dir = dirs::data_dir().ok_or("Cannot get user data dir")?;
+    Ok(data_dir.join("ytui_music").join("search_history"))
+}
+
+/// Incremental search through history, newest match first
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReverseSearch {
+    pub pattern: String,
+    /// Index of matching entry. None if nothing matches
+    pub found: Option<usize>,
+}
+
+/// Past searches, kept in a file with one query per line
+#[derive(Debug, Clone, Default)]
+pub struct SearchHistory {
+    /// Oldest first, without duplicates
+    entries: Vec<String>,
+    /// Where entries are saved. Only kept in memory if None
+    path: Option<PathBuf>,
+    /// Entry shown while going through history with up/down
+    recalled: Option<usize>,
+    /// What was typed before going through history
+    draft: String,
+    search: Option<ReverseSearch>,
+}
+
+impl SearchHistory {
+    /// History saved in `path`. Empty if file does not exists yet
+    pub fn load(path: PathBuf) -> Result<Self, String> {
+        let entries = match fs::read_to_string(&path) {
+            Ok(content) => content.lines().map(str::to_string).collect(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(format!("While reading {}: {e}", path.display())),
+        };
+        Ok(SearchHistory {
+            entries,
+            path: Some(path),
+            ..Default::default()
+        })
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Add query as newest entry and save history
+    /// Same query searched before is moved rather than repeated
+    pub fn record(&mut self, query: &str) -> Result<(), String> {
+        self.recalled = None;
+        // a line in file is an entry
+        let query = query.replace('\n', " ");
+        let query = query.trim();
+        if query.is_empty() {
+            return Ok(());
+        }
+        self.entries.retain(|entry| entry != query);
+        self.entries.push(query.to_string());
+        let excess = self.entries.len().saturating_sub(HISTORY_LIMIT);
+        self.entries.drain(..excess);
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("While creating {}: {e}", dir.display()))?;
+        }
+        let mut content = self.entries.join("\n");
+        content.push('\n');
+        fs::write(path, content).map_err(|e| format!("While saving {}: {e}", path.display()))
+    }
+
+    /// Go to older (Up) or newer (Down) entry from one being shown
+    /// or to the oldest (First) or back to what was typed (Last)
+    /// Returns what searchbar should now have, None if nothing changes
+    ///
+    /// `typed` is kept aside when leaving it and is given back
+    /// when going past the newest entry
+    pub fn recall(&mut self, direction: MoveDirection, typed: &str) -> Option<String> {
+        let last = self.entries.len().checked_sub(1)?;
+        let recalled = match (direction, self.recalled) {
+            (MoveDirection::Up, None) => Some(last),
+            (MoveDirection::Up, Some(i)) if i > 0 => Some(i - 1),
+            (MoveDirection::Down, Some(i)) if i < last => Some(i + 1),
+            (MoveDirection::Down | MoveDirection::Last, Some(_)) => None,
+            (MoveDirection::First, _) => Some(0),
+            _ => return None,
+        };
+        if self.recalled.is_none() {
+            self.draft = typed.to_string();
+        }
+        self.recalled = recalled;
+        match recalled {
+            Some(i) => Some(self.entries[i].clone()),
+            None => Some(std::mem::take(&mut self.draft)),
+        }
+    }
+
+    /// Reverse search, while it is on
+    pub fn search(&self) -> Option<&ReverseSearch> {
+        self.search.as_ref()
+    }
+
+    /// Entry found by reverse search
+    pub fn found(&self) -> Option<&str> {
+        let found = self.search.as_ref()?.found?;
+        Some(&self.entries[found])
+    }
+
+    /// Start reverse search, or look for next older
+    /// match if it is already on
+    pub fn search_older(&mut self) {
+        let (pattern, before) = match &self.search {
+            Some(search) => (
+                search.pattern.clone(),
+                search.found.unwrap_or(self.entries.len()),
+            ),
+            None => (String::new(), self.entries.len()),
+        };
+        match (self.find(&pattern, before), &mut self.search) {
+            (Some(older), Some(search)) => search.found = Some(older),
+            // nothing older, stays on current match
+            (None, Some(_)) => (),
+            (found, None) => self.search = Some(ReverseSearch { pattern, found }),
+        }
+    }
+
+    /// Change pattern of reverse search, looking again from newest entry
+    pub fn edit_search(&mut self, edit: impl FnOnce(&mut String)) {
+        let Some(mut search) = self.search.take() else {
+            return;
+        };
+        edit(&mut search.pattern);
+        search.found = self.find(&search.pattern, self.entries.len());
+        self.search = Some(search);
+    }
+
+    /// Stop reverse search, giving what was found
+    pub fn end_search(&mut self) -> Option<String> {
+        let found = self.found().map(str::to_string);
+        self.search = None;
+        found
+    }
+
+    /// Newest entry before index `before` containing pattern, ignoring case
+    fn find(&self, pattern: &str, before: usize) -> Option<usize> {
+        let pattern = pattern.to_lowercase();
+        self.entries[..before]
+            .iter()
+            .rposition(|entry| entry.to_lowercase().contains(&pattern))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(entries: &[&str]) -> SearchHistory {
+        SearchHistory {
+            entries: entries.iter().map(|e| e.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn kept_in_file() {
+        let path = std::env::temp_dir()
+            .join(format!("ytui-history-{}", std::process::id()))
+            .join("search_history");
+        let _ = fs::remove_file(&path);
+
+        let mut saved = SearchHistory::load(path.clone()).unwrap();
+        assert!(saved.entries().is_empty());
+        for query in ["aagya", "  bimbakash ", "", "aagya"] {
+            saved.record(query).unwrap();
+        }
+        assert_eq!(["bimbakash", "aagya"], saved.entries());
+
+        let loaded = SearchHistory::load(path.clone()).unwrap();
+        assert_eq!(saved.entries(), loaded.entries());
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn up_and_down_recall() {
+        let mut history = history(&["first", "second", "third"]);
+        let mut press = |direction| history.recall(direction, "typed");
+
+        assert_eq!(None, press(MoveDirection::Down));
+        assert_eq!(Some("third".to_string()), press(MoveDirection::Up));
+        assert_eq!(Some("second".to_string()), press(MoveDirection::Up));
+        assert_eq!(Some("first".to_string()), press(MoveDirection::Up));
+        assert_eq!(None, press(MoveDirection::Up));
+        assert_eq!(Some("second".to_string()), press(MoveDirection::Down));
+        assert_eq!(Some("third".to_string()), press(MoveDirection::Down));
+        // past the newest is what was typed
+        assert_eq!(Some("typed".to_string()), press(MoveDirection::Down));
+        assert_eq!(None, press(MoveDirection::Down));
+    }
+
+    #[test]
+    fn reverse_search() {
+        let mut history = history(&["Aagya", "bimbakash", "aagya live", "kabira"]);
+        history.search_older();
+        assert_eq!(Some("kabira"), history.found());
+
+        history.edit_search(|pattern| pattern.push_str("AAG"));
+        assert_eq!(Some("aagya live"), history.found());
+        history.search_older();
+        assert_eq!(Some("Aagya"), history.found());
+        // nothing older, stays on last match
+        history.search_older();
+        assert_eq!(Some("Aagya"), history.found());
+
+        history.edit_search(|pattern| pattern.push('x'));
+        assert_eq!(None, history.found());
+        history.edit_search(|pattern| {
+            pattern.pop();
+        });
+        assert_eq!(Some("aagya live"), history.found());
+
+        assert_eq!(Some("aagya live".to_string()), history.end_search());
+        assert!(history.search().is_none());
+    }
+}
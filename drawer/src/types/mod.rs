@@ -1,5 +1,8 @@
+pub mod history;
 pub mod query;
+pub mod queue;
 pub mod state;
+pub mod suggestion;
 pub mod unit;
 pub mod utils;
 pub mod window;
@@ -1,10 +1,21 @@
+use crate::types::unit::{ArtistUnit, MusicUnit, PlaylistUnit};
+use crate::types::utils::parse_track_length;
 use std::borrow::Cow;
+use std::fmt;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Query {
-    SearchAll(String),
-    SearchMusic(String),
-    SearchPlaylist(String),
-    Local,
+    SearchAll(SearchQuery),
+    SearchMusic(SearchQuery),
+    SearchPlaylist(SearchQuery),
+    /// Music files in this directory
+    Local(PathBuf),
+    /// Tracks of playlist with this id
+    Playlist(String),
     Trending,
     FollowingArtist,
     SavedPlaylist,
@@ -12,29 +23,601 @@ pub enum Query {
     Nothing,
 }
 
+/// Text that tells queries apart, shown in pane titles
+/// Searches print their parsed form, so it is same however they were typed
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct FinalQuery(Cow<'static, str>);
 
 impl Query {
     pub fn get_final_query(self) -> FinalQuery {
         match self {
-            Query::SearchAll(mut search_query) => {
-                search_query.push_str(":all");
-                FinalQuery(search_query.into())
+            Query::SearchAll(search_query) => FinalQuery(search_query.to_string().into()),
+            Query::SearchMusic(search_query) => {
+                FinalQuery(format!("{search_query} type:music").into())
             }
-            Query::SearchMusic(mut search_query) => {
-                search_query.push_str(":music");
-                FinalQuery(search_query.into())
-            }
-            Query::SearchPlaylist(mut search_query) => {
-                search_query.push_str(":playlist");
-                FinalQuery(search_query.into())
+            Query::SearchPlaylist(search_query) => {
+                FinalQuery(format!("{search_query} type:playlist").into())
             }
             Query::Trending => FinalQuery(":trending".into()),
             Query::LikedMusic => FinalQuery(":liked_music".into()),
             Query::SavedPlaylist => FinalQuery(":saved_playlist".into()),
             Query::FollowingArtist => FinalQuery(":following_artist".into()),
-            Query::Local => FinalQuery(":local".into()),
-            Query::Nothing => FinalQuery(":nothing".into()),
+            Query::Local(dir) => FinalQuery(format!(":local {}", dir.display()).into()),
+            Query::Playlist(id) => FinalQuery(format!(":playlist {id}").into()),
+            Query::Nothing => FinalQuery("".into()),
+        }
+    }
+}
+
+impl FinalQuery {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// If results of query come from this machine
+    pub fn is_local(&self) -> bool {
+        self.as_str().starts_with(":local ")
+    }
+
+    /// Title of pane showing results of this query
+    pub fn pane_title(&self, pane: &str) -> String {
+        match self.as_str() {
+            "" => format!("{pane} "),
+            query => format!("{pane} ({query}) "),
+        }
+    }
+}
+
+/// Kind of result a search can give
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResultKind {
+    Music,
+    Playlist,
+    Artist,
+}
+
+/// Search text with filters, as typed in searchbar
+///
+/// Eg: `"tal ma" artist:bartika -live duration:<5m year:2020..2022`
+/// Words and quoted phrases are searched for and filters then pick
+/// from the results. Term starting with `-` leaves out what it matches
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct SearchQuery {
+    pub terms: Vec<Term>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Term {
+    /// Leave out results that match
+    pub negated: bool,
+    pub filter: Filter,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Filter {
+    // aagya
+    Word(String),
+    // "tal ma"
+    Phrase(String),
+    // artist:bartika or artist:"bartika eam"
+    Artist(String),
+    // type:music, type:playlist or type:artist
+    Kind(ResultKind),
+    // duration:<5m, duration:>1h or duration:3m..5m30s
+    Duration(Bounds<Duration>),
+    // year:2020, year:<2000 or year:2020..2022
+    Year(Bounds<i32>),
+}
+
+/// Values a filter lets through
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Bounds<T> {
+    // <x
+    Below(T),
+    // >x
+    Above(T),
+    // a..b, with both a and b
+    Between(T, T),
+    // x
+    Exactly(T),
+}
+
+impl<T: PartialOrd> Bounds<T> {
+    pub fn contains(&self, value: &T) -> bool {
+        match self {
+            Bounds::Below(max) => value < max,
+            Bounds::Above(min) => value > min,
+            Bounds::Between(min, max) => min <= value && value <= max,
+            Bounds::Exactly(exact) => value == exact,
+        }
+    }
+}
+
+/// Why searchbar text is not a query, and which part of it is wrong
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError {
+    /// Byte range of the text at fault
+    pub span: Range<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// What filters look at in a single result
+struct Facts<'a> {
+    kind: ResultKind,
+    title: &'a str,
+    artist: &'a str,
+    duration: Option<Duration>,
+    year: Option<i32>,
+}
+
+impl SearchQuery {
+    /// Text to ask backend for. Filters other than artist are applied
+    /// on results, and so are left out of it
+    pub fn search_text(&self) -> String {
+        self.terms
+            .iter()
+            .filter(|term| !term.negated)
+            .filter_map(|term| match &term.filter {
+                Filter::Word(word) => Some(word.clone()),
+                Filter::Phrase(phrase) => Some(format!("\"{phrase}\"")),
+                Filter::Artist(artist) => Some(artist.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// If results of this kind should be searched at all
+    pub fn wants(&self, kind: ResultKind) -> bool {
+        let kinds = self.terms.iter().filter_map(|term| match term.filter {
+            Filter::Kind(wanted) => Some((term.negated, wanted)),
+            _ => None,
+        });
+        let mut asked = kinds.clone().filter(|(negated, _)| !negated).peekable();
+        let is_asked = asked.peek().is_none() || asked.any(|(_, wanted)| wanted == kind);
+        is_asked && !kinds.clone().any(|(negated, left)| negated && left == kind)
+    }
+
+    pub fn keeps_music(&self, music: &MusicUnit) -> bool {
+        self.keeps(Facts {
+            kind: ResultKind::Music,
+            title: &music.title,
+            artist: &music.artist,
+            duration: music.duration,
+            year: music.year,
+        })
+    }
+
+    pub fn keeps_playlist(&self, playlist: &PlaylistUnit) -> bool {
+        self.keeps(Facts {
+            kind: ResultKind::Playlist,
+            title: &playlist.title,
+            artist: &playlist.creator,
+            duration: None,
+            year: None,
+        })
+    }
+
+    pub fn keeps_artist(&self, artist: &ArtistUnit) -> bool {
+        self.keeps(Facts {
+            kind: ResultKind::Artist,
+            title: &artist.name,
+            artist: &artist.name,
+            duration: None,
+            year: None,
+        })
+    }
+
+    fn keeps(&self, facts: Facts) -> bool {
+        let contains = |text: &str, part: &str| text.to_lowercase().contains(&part.to_lowercase());
+        let is_music = facts.kind == ResultKind::Music;
+        self.terms.iter().all(|term| {
+            let matches = match &term.filter {
+                // backend is trusted to give results about searched words,
+                // they only pick results when leaving them out
+                Filter::Word(text) | Filter::Phrase(text) if term.negated => {
+                    contains(facts.title, text) || contains(facts.artist, text)
+                }
+                Filter::Word(_) | Filter::Phrase(_) | Filter::Kind(_) => return true,
+                Filter::Artist(artist) => contains(facts.artist, artist),
+                // only music have length and upload date
+                Filter::Duration(_) | Filter::Year(_) if !is_music => return true,
+                Filter::Duration(bounds) => facts.duration.is_some_and(|d| bounds.contains(&d)),
+                Filter::Year(bounds) => facts.year.is_some_and(|y| bounds.contains(&y)),
+            };
+            matches != term.negated
+        })
+    }
+}
+
+impl FromStr for SearchQuery {
+    type Err = QueryError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut terms = Vec::new();
+        let mut at = 0;
+        loop {
+            at += text[at..].len() - text[at..].trim_start().len();
+            if at == text.len() {
+                break;
+            }
+            let start = at;
+            let negated = text[at..].starts_with('-');
+            if negated {
+                at += 1;
+            }
+
+            let (filter, end) = if text[at..].starts_with('"') {
+                let (phrase, end) = quoted(text, at)?;
+                (Filter::Phrase(phrase.to_string()), end)
+            } else {
+                let end = word_end(text, at);
+                match text[at..end].split_once(':') {
+                    _ if at == end => {
+                        return Err(error(start..at, "Nothing to leave out after `-`"))
+                    }
+                    Some((key, _)) => {
+                        let value_at = at + key.len() + 1;
+                        let (value, end) = match text[value_at..].starts_with('"') {
+                            true => quoted(text, value_at)?,
+                            false => (bare(text, value_at..end)?, end),
+                        };
+                        let key_span = at..at + key.len();
+                        if value.is_empty() && end == value_at {
+                            let message = format!("Value of `{key}` is missing");
+                            return Err(error(key_span.start..value_at, &message));
+                        }
+                        (parse_filter(key, key_span, value, value_at..end)?, end)
+                    }
+                    None => (Filter::Word(bare(text, at..end)?.to_string()), end),
+                }
+            };
+            at = end;
+            if !text[at..].starts_with(char::is_whitespace) && at < text.len() {
+                let span = at..word_end(text, at);
+                return Err(error(span, "Space is needed between terms"));
+            }
+            terms.push(Term { negated, filter });
+        }
+        Ok(SearchQuery { terms })
+    }
+}
+
+impl fmt::Display for SearchQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, term) in self.terms.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{term}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negated {
+            f.write_str("-")?;
+        }
+        match &self.filter {
+            Filter::Word(word) => f.write_str(word),
+            Filter::Phrase(phrase) => write!(f, "\"{phrase}\""),
+            Filter::Artist(artist) if artist.contains(char::is_whitespace) => {
+                write!(f, "artist:\"{artist}\"")
+            }
+            Filter::Artist(artist) => write!(f, "artist:{artist}"),
+            Filter::Kind(kind) => write!(f, "type:{kind}"),
+            Filter::Duration(bounds) => {
+                write!(f, "duration:{}", bounds.to_text(duration_in_units))
+            }
+            Filter::Year(bounds) => write!(f, "year:{}", bounds.to_text(i32::to_string)),
+        }
+    }
+}
+
+impl fmt::Display for ResultKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ResultKind::Music => "music",
+            ResultKind::Playlist => "playlist",
+            ResultKind::Artist => "artist",
+        })
+    }
+}
+
+impl<T> Bounds<T> {
+    fn to_text(&self, text_of: impl Fn(&T) -> String) -> String {
+        match self {
+            Bounds::Below(max) => format!("<{}", text_of(max)),
+            Bounds::Above(min) => format!(">{}", text_of(min)),
+            Bounds::Between(min, max) => format!("{}..{}", text_of(min), text_of(max)),
+            Bounds::Exactly(exact) => text_of(exact),
+        }
+    }
+
+    /// Read `<x`, `>x`, `a..b` or `x` with `parse` reading each value
+    fn parse(
+        value: &str,
+        span: Range<usize>,
+        parse: impl Fn(&str) -> Option<T>,
+        expected: &str,
+    ) -> Result<Self, QueryError>
+    where
+        T: PartialOrd,
+    {
+        let parse = |part: &str| parse(part).ok_or_else(|| error(span.clone(), expected));
+        if let Some(max) = value.strip_prefix('<') {
+            Ok(Bounds::Below(parse(max)?))
+        } else if let Some(min) = value.strip_prefix('>') {
+            Ok(Bounds::Above(parse(min)?))
+        } else if let Some((min, max)) = value.split_once("..") {
+            let (min, max) = (parse(min)?, parse(max)?);
+            match min <= max {
+                true => Ok(Bounds::Between(min, max)),
+                false => Err(error(span, "Range ends before it starts")),
+            }
+        } else {
+            Ok(Bounds::Exactly(parse(value)?))
         }
     }
 }
+
+fn parse_filter(
+    key: &str,
+    key_span: Range<usize>,
+    value: &str,
+    value_span: Range<usize>,
+) -> Result<Filter, QueryError> {
+    match key.to_lowercase().as_str() {
+        "artist" => Ok(Filter::Artist(value.to_string())),
+        "type" => match value.to_lowercase().as_str() {
+            "music" => Ok(Filter::Kind(ResultKind::Music)),
+            "playlist" => Ok(Filter::Kind(ResultKind::Playlist)),
+            "artist" => Ok(Filter::Kind(ResultKind::Artist)),
+            _ => Err(error(
+                value_span,
+                "Type is one of music, playlist or artist",
+            )),
+        },
+        "duration" => {
+            let expected = "Duration like <5m, >1h or 3m..4m30s was expected";
+            match Bounds::parse(value, value_span.clone(), parse_duration, expected)? {
+                // length in exact seconds would hardly match anything
+                Bounds::Exactly(_) => Err(error(value_span, expected)),
+                bounds => Ok(Filter::Duration(bounds)),
+            }
+        }
+        "year" => {
+            let expected = "Year like 2020, <2000 or 2020..2022 was expected";
+            let parse_year = |year: &str| year.parse().ok();
+            Ok(Filter::Year(Bounds::parse(
+                value, value_span, parse_year, expected,
+            )?))
+        }
+        "" => Err(error(
+            key_span.start..key_span.end + 1,
+            "Filter name is missing",
+        )),
+        _ => Err(error(
+            key_span,
+            &format!("Unknown filter `{key}`. Filters are artist, type, duration and year"),
+        )),
+    }
+}
+
+/// Length like `1h30m`, `90s` or `3:30`
+fn parse_duration(text: &str) -> Option<Duration> {
+    if text.contains(':') {
+        return parse_track_length(text).ok();
+    }
+    let mut seconds: u64 = 0;
+    let mut number = String::new();
+    for ch in text.chars() {
+        let unit = match ch {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => {
+                number.push(ch);
+                continue;
+            }
+        };
+        let part = number.parse::<u64>().ok()?.checked_mul(unit)?;
+        seconds = seconds.checked_add(part)?;
+        number.clear();
+    }
+    // a number without unit is left over
+    match number.is_empty() && !text.is_empty() {
+        true => Some(Duration::from_secs(seconds)),
+        false => None,
+    }
+}
+
+/// Length in the units it is read in. Eg: `1h2m3s`
+fn duration_in_units(duration: &Duration) -> String {
+    let seconds = duration.as_secs();
+    let parts = [
+        (seconds / 3600, 'h'),
+        (seconds / 60 % 60, 'm'),
+        (seconds % 60, 's'),
+    ];
+    let text = parts
+        .iter()
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| format!("{value}{unit}"))
+        .collect::<String>();
+    match text.is_empty() {
+        true => "0s".to_string(),
+        false => text,
+    }
+}
+
+fn error(span: Range<usize>, message: &str) -> QueryError {
+    QueryError {
+        span,
+        message: message.to_string(),
+    }
+}
+
+/// Byte offset where word starting at `at` ends
+fn word_end(text: &str, at: usize) -> usize {
+    text[at..]
+        .find(char::is_whitespace)
+        .map_or(text.len(), |end| at + end)
+}
+
+/// Unquoted text in `span`, which can not have quotes in it
+fn bare(text: &str, span: Range<usize>) -> Result<&str, QueryError> {
+    let word = &text[span.clone()];
+    match word.contains('"') {
+        true => Err(error(span, "Quotes go around whole words")),
+        false => Ok(word),
+    }
+}
+
+/// Text between quote at `at` and the one closing it,
+/// with byte offset after the closing quote
+fn quoted(text: &str, at: usize) -> Result<(&str, usize), QueryError> {
+    let Some(length) = text[at + 1..].find('"') else {
+        return Err(error(at..text.len(), "Quote is not closed"));
+    };
+    let end = at + 1 + length + 1;
+    match &text[at + 1..end - 1] {
+        "" => Err(error(at..end, "Nothing in quotes")),
+        inside => Ok((inside, end)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(text: &str) -> SearchQuery {
+        text.parse().unwrap()
+    }
+
+    fn music(title: &str, artist: &str, seconds: u64, year: i32) -> MusicUnit {
+        MusicUnit {
+            title: title.to_string(),
+            artist: artist.to_string(),
+            duration: Some(Duration::from_secs(seconds)),
+            year: Some(year),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn terms_are_parsed() {
+        let term = |negated, filter| Term { negated, filter };
+        assert_eq!(
+            vec![
+                term(false, Filter::Phrase("tal ma".to_string())),
+                term(false, Filter::Artist("bartika eam".to_string())),
+                term(true, Filter::Word("live".to_string())),
+                term(
+                    false,
+                    Filter::Duration(Bounds::Below(Duration::from_secs(300)))
+                ),
+                term(false, Filter::Kind(ResultKind::Playlist)),
+                term(false, Filter::Year(Bounds::Between(2020, 2022))),
+                term(
+                    true,
+                    Filter::Duration(Bounds::Between(
+                        Duration::from_secs(90),
+                        Duration::from_secs(3810)
+                    ))
+                ),
+            ],
+            parsed(
+                r#"  "tal ma" artist:"bartika eam"  -live duration:<5m type:Playlist year:2020..2022 -duration:90s..1:03:30"#
+            )
+            .terms
+        );
+        assert_eq!(SearchQuery::default(), parsed("   "));
+    }
+
+    #[test]
+    fn printing_round_trips() {
+        for text in [
+            r#"aagya "tal ma" -"mero desh" artist:"bartika eam" -artist:kutumba"#,
+            "bimbakash duration:>1h2m3s -duration:<0s year:2020 -year:<1999 type:music",
+            "kabira -type:artist year:2020..2022 duration:1m..2m30s",
+        ] {
+            let query = parsed(text);
+            assert_eq!(text, query.to_string());
+            assert_eq!(query, parsed(&query.to_string()));
+        }
+        // printed form is same however it was typed
+        let query = parsed("a   duration:90s..3:00 -type:Artist");
+        assert_eq!("a duration:1m30s..3m -type:artist", query.to_string());
+        assert_eq!(query, parsed(&query.to_string()));
+    }
+
+    #[test]
+    fn errors_point_at_fault() {
+        fn fault(text: &str) -> (&str, String) {
+            let error = text.parse::<SearchQuery>().unwrap_err();
+            (&text[error.span], error.message)
+        }
+        assert_eq!("\"tal ma", fault("aagya \"tal ma").0);
+        assert_eq!("\"\"", fault("aagya \"\"").0);
+        assert_eq!("-", fault("aagya - b").0);
+        assert_eq!("kind", fault("a kind:music").0);
+        assert_eq!("song", fault("a type:song").0);
+        assert_eq!("artist:", fault("artist: bartika").0);
+        assert_eq!(":", fault("a :b").0);
+        assert_eq!("<5", fault("duration:<5 a").0);
+        assert_eq!("5m", fault("duration:5m").0);
+        assert_eq!(
+            "<99999999999999999h",
+            fault("duration:<99999999999999999h").0
+        );
+        assert_eq!(
+            "<999999999999999999:00",
+            fault("duration:<999999999999999999:00").0
+        );
+        assert_eq!("2022..2020", fault("year:2022..2020").0);
+        assert_eq!("b", fault("\"a\"b").0);
+        assert_eq!("a\"b", fault("x a\"b").0);
+        assert_eq!(
+            "Unknown filter `kind`. Filters are artist, type, duration and year",
+            fault("kind:music").1
+        );
+    }
+
+    #[test]
+    fn filters_pick_results() {
+        let query = parsed("tal artist:bartika -live duration:<5m year:2020..2022");
+        assert_eq!("tal bartika", query.search_text());
+        assert!(query.keeps_music(&music("Tal Ma", "Bartika Eam", 240, 2021)));
+        assert!(!query.keeps_music(&music("Tal Ma (LIVE)", "Bartika Eam", 240, 2021)));
+        assert!(!query.keeps_music(&music("Tal Ma", "Bartika Eam", 300, 2021)));
+        assert!(!query.keeps_music(&music("Tal Ma", "Bartika Eam", 240, 2019)));
+        assert!(!query.keeps_music(&music("Tal Ma", "Sushant", 240, 2021)));
+        assert!(!query.keeps_music(&MusicUnit {
+            duration: None,
+            ..music("Tal Ma", "Bartika Eam", 240, 2021)
+        }));
+
+        // length and date do not matter for playlist
+        let playlist = PlaylistUnit {
+            title: "Tal ma and more".to_string(),
+            creator: "bartika".to_string(),
+            ..Default::default()
+        };
+        assert!(query.keeps_playlist(&playlist));
+
+        assert!(query.wants(ResultKind::Artist));
+        let query = parsed("a type:music type:playlist -type:playlist");
+        assert!(query.wants(ResultKind::Music));
+        assert!(!query.wants(ResultKind::Playlist));
+        assert!(!query.wants(ResultKind::Artist));
+    }
+}
@@ -0,0 +1,384 @@
+use crate::types::unit::MusicUnit;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// What to do when playing track ends on it's own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepeatMode {
+    /// Play till the end of queue, then stop
+    Off,
+    /// Start from beginning after reaching end of queue
+    All,
+    /// Keep playing the same track
+    One,
+    /// Stop once current track ends
+    StopAfterCurrent,
+}
+
+impl RepeatMode {
+    /// Mode to switch to on RepeatSwitch
+    pub fn next(self) -> Self {
+        match self {
+            RepeatMode::Off => RepeatMode::All,
+            RepeatMode::All => RepeatMode::One,
+            RepeatMode::One => RepeatMode::StopAfterCurrent,
+            RepeatMode::StopAfterCurrent => RepeatMode::Off,
+        }
+    }
+}
+
+impl From<RepeatMode> for &'static str {
+    fn from(mode: RepeatMode) -> Self {
+        match mode {
+            RepeatMode::Off => "no repeat",
+            RepeatMode::All => "repeat all",
+            RepeatMode::One => "repeat one",
+            RepeatMode::StopAfterCurrent => "stop after current",
+        }
+    }
+}
+
+/// How new tracks shall be put in queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Enqueue {
+    /// Drop everything in queue and start playing new tracks
+    Replace,
+    /// Keep them after everything that is already in queue
+    Append,
+}
+
+/// Why are we moving to next track
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Advance {
+    /// Playing track have ended. RepeatMode decides what's next
+    TrackEnded,
+    /// User asked for next track. Only RepeatMode::All is considered
+    /// so that next track in queue is always reachable.
+    /// Nothing changes if there is no next track
+    UserRequested,
+}
+
+/// List of tracks to be played and the order to play them in
+#[derive(Clone)]
+pub struct Queue {
+    tracks: Vec<MusicUnit>,
+    /// Order in which tracks are played, as index to `tracks`
+    order: Vec<usize>,
+    /// Position in `order` of playing track
+    current: Option<usize>,
+    /// Index to `tracks` of previously played tracks. Most recent last
+    history: Vec<usize>,
+    shuffle: bool,
+    repeat: RepeatMode,
+    rng_state: u64,
+}
+
+impl Default for Queue {
+    fn default() -> Self {
+        Queue {
+            tracks: Vec::new(),
+            order: Vec::new(),
+            current: None,
+            history: Vec::new(),
+            shuffle: false,
+            repeat: RepeatMode::Off,
+            rng_state: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl Queue {
+    /// Queue with it's shuffle order derived from given seed
+    pub fn with_seed(seed: u64) -> Self {
+        Queue {
+            rng_state: seed,
+            ..Default::default()
+        }
+    }
+
+    pub fn tracks(&self) -> &[MusicUnit] {
+        &self.tracks
+    }
+
+    pub fn is_shuffled(&self) -> bool {
+        self.shuffle
+    }
+
+    pub fn repeat_mode(&self) -> RepeatMode {
+        self.repeat
+    }
+
+    pub fn current(&self) -> Option<&MusicUnit> {
+        self.current
+            .map(|position| &self.tracks[self.order[position]])
+    }
+
+    pub fn clear(&mut self) {
+        self.tracks.clear();
+        self.order.clear();
+        self.history.clear();
+        self.current = None;
+    }
+
+    /// Put these tracks into queue
+    /// On Enqueue::Replace, track at `start` becomes current
+    /// On Enqueue::Append, current track is kept unless nothing was playing
+    pub fn add(&mut self, tracks: Vec<MusicUnit>, start: usize, how: Enqueue) {
+        if how == Enqueue::Replace {
+            self.clear();
+        }
+        if tracks.is_empty() {
+            return;
+        }
+
+        let first_new = self.tracks.len();
+        self.tracks.extend(tracks);
+        let new_indexes = first_new..self.tracks.len();
+        let start = first_new + start.min(new_indexes.len() - 1);
+
+        if self.shuffle {
+            // keep already decided order as it is and
+            // scatter new tracks among upcoming ones
+            for index in new_indexes {
+                let upcoming_from = self.current.map(|c| c + 1).unwrap_or(0);
+                let at = upcoming_from + self.random_below(self.order.len() - upcoming_from + 1);
+                self.order.insert(at, index);
+            }
+        } else {
+            self.order.extend(new_indexes);
+        }
+
+        if self.current.is_none() {
+            if self.shuffle {
+                // start must be played first even in shuffled order
+                let position = self.position_of(start);
+                let start = self.order.remove(position);
+                self.order.insert(0, start);
+                self.current = Some(0);
+            } else {
+                self.current = Some(self.position_of(start));
+            }
+        }
+    }
+
+    /// Move to next track, returns the track to play if any
+    pub fn advance(&mut self, why: Advance) -> Option<&MusicUnit> {
+        let current = self.current?;
+        let next = match (why, self.repeat) {
+            (Advance::TrackEnded, RepeatMode::One) => Some(current),
+            (Advance::TrackEnded, RepeatMode::StopAfterCurrent) => None,
+            (_, RepeatMode::All) => Some((current + 1) % self.order.len()),
+            _ => Some(current + 1).filter(|next| *next < self.order.len()),
+        };
+
+        match next {
+            Some(next) => {
+                if next != current {
+                    self.history.push(self.order[current]);
+                }
+                self.current = Some(next);
+                self.current()
+            }
+            None if why == Advance::UserRequested => None,
+            None => {
+                // Queue is done playing but keep it around so
+                // that previous can pick up from here
+                self.history.push(self.order[current]);
+                self.current = None;
+                None
+            }
+        }
+    }
+
+    /// Go back to previously played track, returns it if any
+    pub fn previous(&mut self) -> Option<&MusicUnit> {
+        match self.history.pop() {
+            Some(track) => self.current = Some(self.position_of(track)),
+            None => self.current = Some(self.current?.checked_sub(1)?),
+        }
+        self.current()
+    }
+
+    pub fn switch_repeat(&mut self) -> RepeatMode {
+        self.repeat = self.repeat.next();
+        self.repeat
+    }
+
+    /// Shuffle upcoming tracks or bring back the original order
+    /// Currently playing track stays playing either way
+    pub fn toggle_shuffle(&mut self) -> bool {
+        let current_track = self.current.map(|position| self.order[position]);
+        self.shuffle = !self.shuffle;
+
+        if self.shuffle {
+            // current track goes first, everything else in random order
+            let mut rest = (0..self.tracks.len())
+                .filter(|index| Some(*index) != current_track)
+                .collect::<Vec<_>>();
+            for i in (1..rest.len()).rev() {
+                let j = self.random_below(i + 1);
+                rest.swap(i, j);
+            }
+            self.order = current_track.into_iter().chain(rest).collect();
+        } else {
+            self.order = (0..self.tracks.len()).collect();
+        }
+
+        self.current = current_track.map(|track| self.position_of(track));
+        self.shuffle
+    }
+
+    fn position_of(&self, track: usize) -> usize {
+        self.order
+            .iter()
+            .position(|index| *index == track)
+            .expect("Every track is in play order")
+    }
+
+    /// xorshift64*, good enough to shuffle music
+    fn random_below(&mut self, bound: usize) -> usize {
+        let mut x = self.rng_state.max(1);
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.rng_state = x;
+        (x.wrapping_mul(0x2545_F491_4F6C_DD1D) % bound as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracks(ids: &[&str]) -> Vec<MusicUnit> {
+        ids.iter()
+            .map(|id| MusicUnit {
+                title: format!("title of {id}"),
+                artist: String::new(),
+                duration: None,
+                id: id.to_string(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn current_id(queue: &Queue) -> Option<&str> {
+        queue.current().map(|track| track.id.as_str())
+    }
+
+    fn play_order(queue: &mut Queue) -> Vec<String> {
+        let mut played = queue
+            .current()
+            .map(|t| t.id.clone())
+            .into_iter()
+            .collect::<Vec<_>>();
+        while let Some(track) = queue.advance(Advance::UserRequested) {
+            played.push(track.id.clone());
+        }
+        played
+    }
+
+    #[test]
+    fn replace_and_advance() {
+        let mut queue = Queue::with_seed(7);
+        queue.add(tracks(&["a", "b", "c"]), 1, Enqueue::Replace);
+        assert_eq!(Some("b"), current_id(&queue));
+
+        assert_eq!(
+            Some("c"),
+            queue.advance(Advance::TrackEnded).map(|t| t.id.as_str())
+        );
+        assert!(queue.advance(Advance::TrackEnded).is_none());
+        assert_eq!(None, current_id(&queue));
+
+        // previous after queue have ended picks up last played track
+        assert_eq!(Some("c"), queue.previous().map(|t| t.id.as_str()));
+        assert_eq!(Some("b"), queue.previous().map(|t| t.id.as_str()));
+        assert_eq!(Some("a"), queue.previous().map(|t| t.id.as_str()));
+        assert!(queue.previous().is_none());
+    }
+
+    #[test]
+    fn append_keeps_current() {
+        let mut queue = Queue::with_seed(7);
+        queue.add(tracks(&["a"]), 0, Enqueue::Append);
+        assert_eq!(Some("a"), current_id(&queue));
+        queue.add(tracks(&["b", "c"]), 1, Enqueue::Append);
+        assert_eq!(Some("a"), current_id(&queue));
+        assert_eq!(vec!["a", "b", "c"], play_order(&mut queue));
+    }
+
+    #[test]
+    fn repeat_modes() {
+        let mut queue = Queue::with_seed(7);
+        queue.add(tracks(&["a", "b"]), 0, Enqueue::Replace);
+
+        assert_eq!(RepeatMode::All, queue.switch_repeat());
+        queue.advance(Advance::TrackEnded);
+        assert_eq!(
+            Some("a"),
+            queue.advance(Advance::TrackEnded).map(|t| t.id.as_str())
+        );
+
+        assert_eq!(RepeatMode::One, queue.switch_repeat());
+        assert_eq!(
+            Some("a"),
+            queue.advance(Advance::TrackEnded).map(|t| t.id.as_str())
+        );
+        // user can still skip the repeating track
+        assert_eq!(
+            Some("b"),
+            queue.advance(Advance::UserRequested).map(|t| t.id.as_str())
+        );
+
+        assert_eq!(RepeatMode::StopAfterCurrent, queue.switch_repeat());
+        assert!(queue.advance(Advance::TrackEnded).is_none());
+
+        assert_eq!(RepeatMode::Off, queue.switch_repeat());
+    }
+
+    #[test]
+    fn shuffle_is_stable_and_undoable() {
+        let ids = ["a", "b", "c", "d", "e", "f", "g", "h"];
+        let mut queue = Queue::with_seed(42);
+        queue.add(tracks(&ids), 2, Enqueue::Replace);
+
+        assert!(queue.toggle_shuffle());
+        assert_eq!(Some("c"), current_id(&queue));
+
+        let mut first_round = queue.clone();
+        let shuffled = play_order(&mut first_round);
+        let mut sorted = shuffled.clone();
+        sorted.sort();
+        assert_eq!(ids.to_vec(), sorted);
+        assert_eq!("c", shuffled[0]);
+        assert_ne!(ids[2..].to_vec(), shuffled[..6].to_vec());
+
+        // repeating the queue plays same shuffled order again
+        queue.switch_repeat();
+        let mut second_round = vec![];
+        for _ in 0..ids.len() {
+            second_round.push(queue.advance(Advance::TrackEnded).unwrap().id.clone());
+        }
+        assert_eq!(shuffled[1..], second_round[..ids.len() - 1]);
+        assert_eq!(Some("c"), current_id(&queue));
+
+        // undo brings back original order from current track
+        assert!(!queue.toggle_shuffle());
+        queue.switch_repeat();
+        queue.switch_repeat();
+        queue.switch_repeat();
+        assert_eq!(vec!["c", "d", "e", "f", "g", "h"], play_order(&mut queue));
+    }
+
+    #[test]
+    fn shuffle_starts_with_chosen_track() {
+        let mut queue = Queue::with_seed(3);
+        queue.toggle_shuffle();
+        queue.add(tracks(&["a", "b", "c", "d"]), 3, Enqueue::Replace);
+        assert_eq!(Some("d"), current_id(&queue));
+        assert_eq!(4, play_order(&mut queue).len());
+    }
+}
@@ -1,4 +1,8 @@
+use crate::gadgets::searchbar::LineEditor;
+use crate::types::history::SearchHistory;
 use crate::types::query::FinalQuery;
+use crate::types::queue::Queue;
+use crate::types::suggestion::Suggestions;
 use crate::types::unit::{ArtistUnit, MusicUnit, PlaylistUnit};
 use crate::types::window::PaneWindow;
 use crate::types::window::Window;
@@ -8,6 +12,11 @@ use tui::widgets::ListState;
 use tui::widgets::TableState;
 
 use super::query::Query;
+use super::utils;
+use std::time::{Duration, Instant};
+use user_config::action::{KeyboardAction, MoveDirection};
+use user_config::keyboard::Key;
+use user_config::mode::Mode;
 
 macro_rules! make_wrapper {
     ($name: ident ($inner: ident)) => {
@@ -31,31 +40,308 @@ macro_rules! make_wrapper {
 
 pub trait PlayerInfo {
     fn playing_track_title(&self) -> String;
-    // TODO: return more Duration specific type rather than String
-    fn playing_track_duration(&self) -> String;
-    fn playing_track_completed(&self) -> String;
+    /// Total length of playing track, if known
+    fn playing_track_duration(&self) -> Option<Duration>;
+    /// How much of playing track is played, if known
+    fn playing_track_completed(&self) -> Option<Duration>;
 }
 
 impl PlayerInfo for AppState {
     fn playing_track_title(&self) -> String {
-        "Some title".to_string()
+        self.player_state
+            .title
+            .clone()
+            .unwrap_or_else(|| "Nothing is playing".to_string())
     }
-    fn playing_track_duration(&self) -> String {
-        "Some duration".to_string()
+    fn playing_track_duration(&self) -> Option<Duration> {
+        self.player_state.duration
     }
-    fn playing_track_completed(&self) -> String {
-        "Some duration".to_string()
+    fn playing_track_completed(&self) -> Option<Duration> {
+        self.player_state.position
     }
 }
 
+/// What player have last reported about itself
+#[derive(Clone, Default)]
+pub struct PlayerState {
+    pub title: Option<String>,
+    pub position: Option<Duration>,
+    pub duration: Option<Duration>,
+    pub paused: bool,
+    pub volume: u8,
+}
+
+/// What is needed to fetch next page of a result
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Continuation {
+    pub query: Query,
+    /// Number of the page to be fetched
+    pub page: usize,
+    /// As given by backend along with previous page
+    pub token: String,
+}
+
 pub struct QueryResult<T> {
     pub query: FinalQuery,
     pub list: Vec<T>,
+    /// Which page of result this is, first one being 0
+    pub page: usize,
+    /// Where rest of the result is. None if there is no more of it
+    pub continuation: Option<Continuation>,
+    /// If next page is being fetched
+    pub loading: bool,
+}
+
+impl<T> QueryResult<T> {
+    pub fn new(query: &Query, list: Vec<T>) -> Self {
+        QueryResult::page(query, 0, list, None)
+    }
+
+    /// Page number `page` of result, followed by
+    /// page that `token` leads to if there is one
+    pub fn page(query: &Query, page: usize, list: Vec<T>, token: Option<String>) -> Self {
+        QueryResult {
+            query: query.clone().get_final_query(),
+            list,
+            page,
+            continuation: token.map(|token| Continuation {
+                query: query.clone(),
+                page: page + 1,
+                token,
+            }),
+            loading: false,
+        }
+    }
+
+    /// Where to fetch next page from, marking it as being fetched
+    /// None if there is no next page or it is already coming
+    pub fn start_loading(&mut self) -> Option<Continuation> {
+        if self.loading {
+            return None;
+        }
+        let continuation = self.continuation.clone()?;
+        self.loading = true;
+        Some(continuation)
+    }
+
+    /// Put next page after what is already here, returning number of rows added
+    /// Pages of other query or those not next in line are dropped
+    pub fn append(&mut self, next: QueryResult<T>) -> usize {
+        let is_next = self.query == next.query
+            && self.continuation.as_ref().map(|c| c.page) == Some(next.page);
+        if !is_next {
+            return 0;
+        }
+        let added = next.list.len();
+        self.list.extend(next.list);
+        self.page = next.page;
+        self.continuation = next.continuation;
+        self.loading = false;
+        added
+    }
 }
 
 make_wrapper!(ShortcutListState(ListState));
 make_wrapper!(MusicPaneState(TableState));
 make_wrapper!(PlaylistPaneState(TableState));
+make_wrapper!(ArtistPaneState(TableState));
+
+/// Part of a list that fits in it's pane
+///
+/// Offset of TableState is private in tui and the state is
+/// cloned on every draw, so we keep track of scrolling ourself
+/// and only hand over the rows that are visible
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Viewport {
+    /// Index of first visible row
+    pub offset: usize,
+    /// Number of rows that fit. 0 if not known yet
+    pub height: usize,
+}
+
+impl Viewport {
+    /// Rows in a table drawn inside this rect
+    /// i.e without borders and header
+    pub fn with_height_of(rect: Rect) -> Self {
+        Viewport {
+            offset: 0,
+            height: rect.height.saturating_sub(3) as usize,
+        }
+    }
+
+    /// Rows to move on page up/down
+    pub fn page(&self) -> usize {
+        self.height.max(1)
+    }
+
+    /// Scroll as little as possible to have `selected` visible
+    pub fn follow(&mut self, selected: Option<usize>) {
+        match selected {
+            None => self.offset = 0,
+            Some(selected) if selected < self.offset => self.offset = selected,
+            Some(selected) if selected >= self.offset + self.page() => {
+                self.offset = selected + 1 - self.page();
+            }
+            Some(_) => (),
+        }
+    }
+}
+
+/// Keys typed so far of a binding that needs more of them
+#[derive(Clone)]
+pub struct PendingKeys {
+    pub keys: Vec<Key>,
+    /// Waiting for next key stops at this point
+    pub expires_at: Instant,
+    /// What could follow and what it will do
+    pub continuations: Vec<(Vec<Key>, KeyboardAction)>,
+}
+
+/// What command line was opened for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandLineKind {
+    /// Command typed after `:`
+    Prompt,
+    /// Action picked from list of every action
+    Palette,
+    /// Bindings of keymap, filtered by what is typed
+    Help,
+}
+
+/// Line being typed at bottom of screen, or in palette
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandLine {
+    pub kind: CommandLineKind,
+    pub input: String,
+    /// What is listed along with the line as (text, hint)
+    /// Completions for prompt, matching actions for palette
+    pub choices: Vec<(String, String)>,
+    pub selected: Option<usize>,
+}
+
+impl CommandLine {
+    pub fn new(kind: CommandLineKind) -> Self {
+        CommandLine {
+            kind,
+            input: String::new(),
+            choices: Vec::new(),
+            selected: None,
+        }
+    }
+}
+
+/// Everything known about a music or playlist
+/// including what the table columns cut short
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Details {
+    /// (label, value) in order they are shown. Value may have many lines
+    pub fields: Vec<(&'static str, String)>,
+    /// Number of rows scrolled past
+    pub scroll: usize,
+}
+
+impl Details {
+    pub fn of_music(music: &MusicUnit) -> Self {
+        let fields = [
+            ("Title", Some(music.title.clone())),
+            ("Channel", Some(music.artist.clone())),
+            ("Duration", music.duration.map(utils::format_duration)),
+            ("Views", music.views.map(utils::format_count)),
+            ("Uploaded", music.uploaded.clone()),
+            ("Url", Some(music.url())),
+            ("Description", music.description.clone()),
+        ];
+        Details::with_fields(fields)
+    }
+
+    pub fn of_playlist(playlist: &PlaylistUnit) -> Self {
+        let fields = [
+            ("Title", Some(playlist.title.clone())),
+            ("Creator", Some(playlist.creator.clone())),
+            ("Tracks", Some(playlist.song_count.to_string())),
+            ("Url", Some(playlist.url())),
+        ];
+        Details::with_fields(fields)
+    }
+
+    /// Fields that are not known or are empty are left out
+    fn with_fields<const N: usize>(fields: [(&'static str, Option<String>); N]) -> Self {
+        let fields = fields
+            .into_iter()
+            .filter_map(|(label, value)| Some((label, value.filter(|v| !v.is_empty())?)))
+            .collect();
+        Details { fields, scroll: 0 }
+    }
+
+    /// Rows of text when shown `width` columns wide as (label, text)
+    /// Values are wrapped after the labels, which are only
+    /// on first row of each field and padded to same width
+    pub fn rows(&self, width: usize) -> Vec<(String, String)> {
+        let label_width = self
+            .fields
+            .iter()
+            .map(|(label, _)| label.len() + 2)
+            .max()
+            .unwrap_or_default();
+        let text_width = width.saturating_sub(label_width).max(1);
+
+        let mut rows = Vec::new();
+        for (label, value) in &self.fields {
+            let texts = value
+                .lines()
+                .flat_map(|line| utils::wrap_text(line, text_width))
+                .collect::<Vec<_>>();
+            for (i, text) in texts.into_iter().enumerate() {
+                let label = if i == 0 { *label } else { "" };
+                rows.push((format!("{label:label_width$}"), text));
+            }
+        }
+        rows
+    }
+
+    /// Scroll as asked when shown in `width` x `height` area
+    /// Last row is kept at bottom when scrolled to the end
+    pub fn scroll(&mut self, direction: MoveDirection, width: usize, height: usize) {
+        let last = self.rows(width).len().saturating_sub(height);
+        let page = height.max(1);
+        self.scroll = match direction {
+            MoveDirection::Up => self.scroll.saturating_sub(1),
+            MoveDirection::Down => self.scroll + 1,
+            MoveDirection::PageUp => self.scroll.saturating_sub(page),
+            MoveDirection::PageDown => self.scroll + page,
+            MoveDirection::First => 0,
+            MoveDirection::Last => last,
+            MoveDirection::Left | MoveDirection::Right => self.scroll,
+        }
+        .min(last);
+    }
+}
+
+/// Drawn above everything else, taking all input while open
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Popup {
+    /// Palette or help, filtered by what is typed in it
+    Picker(CommandLine),
+    /// Details of a music or playlist
+    Details(Details),
+}
+
+impl Popup {
+    /// Mode that input is taken in while this is the top popup
+    pub fn mode(&self) -> Mode {
+        match self {
+            Popup::Picker(_) => Mode::Command,
+            Popup::Details(_) => Mode::Normal,
+        }
+    }
+}
+
+/// Popup along with window that was focused when it opened
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PopupLayer {
+    pub popup: Popup,
+    pub return_to: Window,
+}
 
 #[derive(Clone)]
 pub struct PanetabState {
@@ -68,43 +354,223 @@ pub struct PanetabState {
 pub struct AppState {
     /// Every result shown must be the outcome of some query
     /// For example,
-    pub altering_query: String,
+    pub altering_query: LineEditor,
+    /// Past searches, recalled in searchbar
+    pub search_history: SearchHistory,
+    /// Completions of what is typed, shown below searchbar
+    pub suggestions: Suggestions,
     pub music_result: QueryResult<MusicUnit>,
     pub artist_result: QueryResult<ArtistUnit>,
     pub playlist_result: QueryResult<PlaylistUnit>,
+    /// Query last sent to fill the panes. First page
+    /// of any other query is late and not shown
+    pub sent_query: Option<FinalQuery>,
     pub active_window: Window,
+    /// Which keybindings are in effect
+    pub mode: Mode,
     pub panetab_state: PanetabState,
     pub shortcut_list_state: ShortcutListState,
     pub music_pane_state: MusicPaneState,
     pub playlist_pane_state: PlaylistPaneState,
+    pub artist_pane_state: ArtistPaneState,
+    pub music_viewport: Viewport,
+    pub playlist_viewport: Viewport,
+    pub artist_viewport: Viewport,
+    pub player_state: PlayerState,
+    /// Unfinished key sequence, if any
+    pub pending_keys: Option<PendingKeys>,
+    /// Count typed before an action, as in `5j`
+    pub count: Option<u32>,
+    /// What to play next
+    pub queue: Queue,
+    /// `:` prompt, while open
+    pub command_line: Option<CommandLine>,
+    /// Open popups, last one is on top and has the focus
+    pub popups: Vec<PopupLayer>,
+    /// Where popups are drawn, updated along with viewports
+    pub popup_area: Rect,
+    /// Message to be shown to user until next action
+    /// For example: error reported by player
+    pub notification: Option<String>,
 }
 
 /// Default Appstate when application is freshly started
 impl Default for AppState {
     fn default() -> Self {
         AppState {
-            altering_query: String::new(),
-            music_result: QueryResult {
-                query: Query::Nothing.get_final_query(),
-                list: [].into(),
-            },
-            artist_result: QueryResult {
-                query: Query::Nothing.get_final_query(),
-                list: [].into(),
-            },
-            playlist_result: QueryResult {
-                query: Query::Nothing.get_final_query(),
-                list: [].into(),
-            },
+            altering_query: LineEditor::default(),
+            search_history: SearchHistory::default(),
+            suggestions: Suggestions::default(),
+            music_result: QueryResult::new(&Query::Nothing, Vec::new()),
+            artist_result: QueryResult::new(&Query::Nothing, Vec::new()),
+            playlist_result: QueryResult::new(&Query::Nothing, Vec::new()),
+            sent_query: None,
             active_window: Window::SearchBar,
+            mode: Mode::Insert,
             panetab_state: PanetabState {
                 active_tab: PaneWindow::MusicPane,
             },
             shortcut_list_state: ShortcutListState(Default::default()),
             music_pane_state: MusicPaneState(Default::default()),
             playlist_pane_state: PlaylistPaneState(Default::default()),
+            artist_pane_state: ArtistPaneState(Default::default()),
+            music_viewport: Viewport::default(),
+            playlist_viewport: Viewport::default(),
+            artist_viewport: Viewport::default(),
+            player_state: PlayerState::default(),
+            pending_keys: None,
+            count: None,
+            queue: Queue::default(),
+            command_line: None,
+            popups: Vec::new(),
+            popup_area: Rect::default(),
+            notification: None,
+        }
+    }
+}
+
+impl AppState {
+    /// Make this window the active one, switching to mode it takes input in
+    /// Nothing changes while a popup is open, as it keeps the focus
+    pub fn focus(&mut self, window: Window) {
+        if !self.popups.is_empty() {
+            return;
+        }
+        self.active_window = window;
+        self.refresh_mode();
+        // suggestions are only for searchbar
+        if window != Window::SearchBar {
+            self.suggestions = Suggestions::default();
+        }
+    }
+
+    /// Soonest time something is to be done even if no message arrives
+    pub fn next_deadline(&self) -> Option<Instant> {
+        let pending_keys = self.pending_keys.as_ref().map(|pending| pending.expires_at);
+        [pending_keys, self.suggestions.fetch_at()]
+            .into_iter()
+            .flatten()
+            .min()
+    }
+
+    /// Take input in mode of whatever is in front
+    fn refresh_mode(&mut self) {
+        self.mode = match (&self.command_line, self.popups.last()) {
+            (Some(_), _) => Mode::Command,
+            (None, Some(layer)) => layer.popup.mode(),
+            (None, None) => Mode::of_window(self.active_window),
+        };
+    }
+
+    /// Put popup on top of everything, giving it the focus
+    pub fn open_popup(&mut self, popup: Popup) {
+        self.popups.push(PopupLayer {
+            popup,
+            return_to: self.active_window,
+        });
+        self.active_window = Window::Popup;
+        self.refresh_mode();
+    }
+
+    /// Close top popup, giving focus back to what had it before
+    pub fn close_popup(&mut self) -> Option<Popup> {
+        let layer = self.popups.pop()?;
+        self.active_window = layer.return_to;
+        self.refresh_mode();
+        Some(layer.popup)
+    }
+
+    /// Start taking input in command line
+    /// Palette and help open as popup while prompt stays at bottom
+    pub fn open_command_line(&mut self, command_line: CommandLine) {
+        match command_line.kind {
+            CommandLineKind::Prompt => {
+                self.command_line = Some(command_line);
+                self.refresh_mode();
+            }
+            CommandLineKind::Palette | CommandLineKind::Help => {
+                self.open_popup(Popup::Picker(command_line));
+            }
+        }
+    }
+
+    /// Command line that typing goes into:
+    /// the prompt if open, otherwise picker on top of popups
+    pub fn active_command_line(&mut self) -> Option<&mut CommandLine> {
+        if self.command_line.is_some() {
+            return self.command_line.as_mut();
+        }
+        match self.popups.last_mut() {
+            Some(PopupLayer {
+                popup: Popup::Picker(command_line),
+                ..
+            }) => Some(command_line),
+            _ => None,
+        }
+    }
+
+    /// Stop taking input in active command line, returning it
+    pub fn close_command_line(&mut self) -> Option<CommandLine> {
+        if let Some(command_line) = self.command_line.take() {
+            self.refresh_mode();
+            return Some(command_line);
+        }
+        let picker_on_top = matches!(
+            self.popups.last(),
+            Some(PopupLayer {
+                popup: Popup::Picker(_),
+                ..
+            })
+        );
+        if !picker_on_top {
+            return None;
+        }
+        match self.close_popup() {
+            Some(Popup::Picker(command_line)) => Some(command_line),
+            _ => None,
         }
     }
+
+    /// Forget results of all panes along with what was selected in them
+    pub fn clear_results(&mut self) {
+        self.music_result = QueryResult::new(&Query::Nothing, Vec::new());
+        self.playlist_result = QueryResult::new(&Query::Nothing, Vec::new());
+        self.artist_result = QueryResult::new(&Query::Nothing, Vec::new());
+        self.sent_query = None;
+        self.music_pane_state.get_mut_ref().select(None);
+        self.playlist_pane_state.get_mut_ref().select(None);
+        self.artist_pane_state.get_mut_ref().select(None);
+        self.music_viewport.follow(None);
+        self.playlist_viewport.follow(None);
+        self.artist_viewport.follow(None);
+    }
+
+    /// Resize viewports to newly computed geometry
+    /// keeping the selections visible
+    pub fn fit_viewports(&mut self, geometry: &GeometryData) {
+        let panes = [
+            (
+                &mut self.music_viewport,
+                geometry.musicpane,
+                self.music_pane_state.get_ref().selected(),
+            ),
+            (
+                &mut self.playlist_viewport,
+                geometry.playlistpane,
+                self.playlist_pane_state.get_ref().selected(),
+            ),
+            (
+                &mut self.artist_viewport,
+                geometry.artistpane,
+                self.artist_pane_state.get_ref().selected(),
+            ),
+        ];
+        for (viewport, rect, selected) in panes {
+            viewport.height = Viewport::with_height_of(rect).height;
+            viewport.follow(selected);
+        }
+        self.popup_area = geometry.popup;
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -123,8 +589,11 @@ pub struct GeometryData {
     pub musicpane: Rect,
     pub playlistpane: Rect,
     pub artistpane: Rect,
+    /// Where popups are drawn
+    pub popup: Rect,
     pub musicpane_division: PaneDivision<3>,
     pub playlistpane_division: PaneDivision<3>,
+    pub artistpane_division: PaneDivision<2>,
 }
 
 impl Default for GeometryData {
@@ -138,6 +607,7 @@ impl Default for GeometryData {
             musicpane: default_rect,
             playlistpane: default_rect,
             artistpane: default_rect,
+            popup: default_rect,
             musicpane_division: PaneDivision {
                 splits: [Constraint::Length(0); 3],
                 spacing: 0,
@@ -146,6 +616,143 @@ impl Default for GeometryData {
                 splits: [Constraint::Length(0); 3],
                 spacing: 0,
             },
+            artistpane_division: PaneDivision {
+                splits: [Constraint::Length(0); 2],
+                spacing: 0,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn music(id: &str) -> MusicUnit {
+        MusicUnit {
+            title: format!("title of {id}"),
+            id: id.to_string(),
+            ..Default::default()
         }
     }
+
+    fn page(
+        query: &Query,
+        number: usize,
+        ids: &[&str],
+        token: Option<&str>,
+    ) -> QueryResult<MusicUnit> {
+        let list = ids.iter().map(|id| music(id)).collect();
+        QueryResult::page(query, number, list, token.map(str::to_string))
+    }
+
+    #[test]
+    fn pages_are_appended_in_line() {
+        let query = Query::SearchAll("aagya".parse().unwrap());
+        let mut result = page(&query, 0, &["a", "b"], Some("2"));
+
+        let continuation = result.start_loading().unwrap();
+        assert_eq!(1, continuation.page);
+        assert_eq!("2", continuation.token);
+        // not handed out again while it is coming
+        assert_eq!(None, result.start_loading());
+
+        // pages that are late, out of line or of other query are dropped
+        assert_eq!(0, result.append(page(&query, 3, &["x"], None)));
+        let other = Query::SearchAll("kabira".parse().unwrap());
+        assert_eq!(0, result.append(page(&other, 1, &["y"], None)));
+        assert_eq!(2, result.list.len());
+        assert!(result.loading);
+
+        assert_eq!(1, result.append(page(&query, 1, &["c"], None)));
+        assert_eq!(3, result.list.len());
+        assert_eq!(1, result.page);
+        assert!(!result.loading);
+        // nothing more after last page
+        assert_eq!(None, result.start_loading());
+    }
+
+    #[test]
+    fn clearing_results_drops_selection() {
+        let mut appstate = AppState::default();
+        appstate.music_result.list.push(music("a"));
+        appstate.music_pane_state.get_mut_ref().select(Some(0));
+        appstate.music_viewport.offset = 1;
+        appstate.sent_query = Some(Query::Trending.get_final_query());
+
+        appstate.clear_results();
+        assert!(appstate.music_result.list.is_empty());
+        assert_eq!(None, appstate.music_pane_state.get_ref().selected());
+        assert_eq!(0, appstate.music_viewport.offset);
+        assert_eq!(None, appstate.sent_query);
+    }
+
+    #[test]
+    fn details_wrap_and_scroll() {
+        let mut details = Details::of_music(&MusicUnit {
+            views: Some(1234),
+            description: Some("line one\nline two".to_string()),
+            ..music("id1")
+        });
+        let labels = details
+            .fields
+            .iter()
+            .map(|(label, _)| *label)
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["Title", "Views", "Url", "Description"], labels);
+
+        // Url is wrapped rather than cut short
+        let rows = details.rows(28);
+        assert_eq!(7, rows.len());
+        assert_eq!("Url          ", rows[2].0);
+        assert_eq!(
+            "https://www.youtube.com/watch?v=id1",
+            rows[2..5]
+                .iter()
+                .map(|(_, text)| text.as_str())
+                .collect::<String>()
+        );
+
+        // last row stays at bottom of 4 rows high area
+        details.scroll(MoveDirection::Down, 28, 4);
+        assert_eq!(1, details.scroll);
+        details.scroll(MoveDirection::Last, 28, 4);
+        assert_eq!(3, details.scroll);
+        details.scroll(MoveDirection::PageDown, 28, 4);
+        assert_eq!(3, details.scroll);
+        details.scroll(MoveDirection::Up, 28, 4);
+        assert_eq!(2, details.scroll);
+    }
+
+    #[test]
+    fn popups_stack_and_give_focus_back() {
+        let mut appstate = AppState::default();
+        appstate.focus(Window::PaneWindow);
+
+        appstate.open_command_line(CommandLine::new(CommandLineKind::Palette));
+        appstate.open_command_line(CommandLine::new(CommandLineKind::Help));
+        assert_eq!(2, appstate.popups.len());
+        assert_eq!(Mode::Command, appstate.mode);
+        // popup keeps the focus
+        appstate.focus(Window::SearchBar);
+        assert_eq!(Window::Popup, appstate.active_window);
+
+        // closed one at a time
+        let help = appstate.close_command_line().unwrap();
+        assert_eq!(CommandLineKind::Help, help.kind);
+        assert_eq!(Window::Popup, appstate.active_window);
+        assert!(appstate.close_popup().is_some());
+        assert_eq!(
+            (Window::PaneWindow, Mode::Normal),
+            (appstate.active_window, appstate.mode)
+        );
+        assert_eq!(None, appstate.close_popup());
+
+        // prompt is not a popup
+        appstate.open_command_line(CommandLine::new(CommandLineKind::Prompt));
+        assert!(appstate.popups.is_empty());
+        assert_eq!(Mode::Command, appstate.mode);
+        assert!(appstate.close_command_line().is_some());
+        assert_eq!(Mode::Normal, appstate.mode);
+    }
 }
@@ -0,0 +1,260 @@
+use crate::types::history::SearchHistory;
+use crate::types::unit::MusicUnit;
+use std::time::{Duration, Instant};
+use user_config::action::MoveDirection;
+
+/// How long typing must pause before backend is asked for suggestions
+pub const SUGGESTION_DELAY: Duration = Duration::from_millis(300);
+/// Most suggestions shown at once
+pub const MAX_SUGGESTIONS: usize = 8;
+
+/// Where a suggestion came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuggestionSource {
+    // searched before
+    History,
+    // title or artist of music already fetched
+    Library,
+    // suggested by backend
+    Backend,
+}
+
+impl SuggestionSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            SuggestionSource::History => "history",
+            SuggestionSource::Library => "library",
+            SuggestionSource::Backend => "suggested",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    pub text: String,
+    pub source: SuggestionSource,
+}
+
+/// Completions of what is typed in searchbar, shown below it
+#[derive(Debug, Clone, Default)]
+pub struct Suggestions {
+    /// Text that suggestions are for
+    typed: String,
+    local: Vec<Suggestion>,
+    backend: Vec<Suggestion>,
+    /// Highlighted suggestion. None while typed text is being edited
+    selected: Option<usize>,
+    /// When to ask backend for suggestions of typed text
+    fetch_at: Option<Instant>,
+}
+
+impl Suggestions {
+    /// Suggestions for `typed` from history and music that is already
+    /// known, asking backend too once typing have paused if `ask_backend`
+    pub fn new(
+        typed: &str,
+        history: &SearchHistory,
+        library: &[&MusicUnit],
+        ask_backend: bool,
+        now: Instant,
+    ) -> Self {
+        let typed = typed.trim();
+        if typed.is_empty() {
+            return Suggestions::default();
+        }
+        let lowercase = typed.to_lowercase();
+        let completes = |text: &str| {
+            let text = text.to_lowercase();
+            text != lowercase && text.contains(&lowercase)
+        };
+
+        let from_history = history
+            .entries()
+            .iter()
+            .rev()
+            .filter(|entry| completes(entry))
+            .map(|entry| (entry.as_str(), SuggestionSource::History));
+        let from_library = library
+            .iter()
+            .flat_map(|music| [music.title.as_str(), music.artist.as_str()])
+            .filter(|text| completes(text))
+            .map(|text| (text, SuggestionSource::Library));
+
+        let mut local: Vec<Suggestion> = Vec::new();
+        for (text, source) in from_history.chain(from_library) {
+            if local.len() == MAX_SUGGESTIONS {
+                break;
+            }
+            if !local
+                .iter()
+                .any(|known| known.text.eq_ignore_ascii_case(text))
+            {
+                local.push(Suggestion {
+                    text: text.to_string(),
+                    source,
+                });
+            }
+        }
+
+        Suggestions {
+            typed: typed.to_string(),
+            local,
+            fetch_at: ask_backend.then_some(now + SUGGESTION_DELAY),
+            ..Default::default()
+        }
+    }
+
+    /// Local suggestions first, then those from backend
+    pub fn list(&self) -> Vec<&Suggestion> {
+        self.local
+            .iter()
+            .chain(self.backend.iter().filter(|suggestion| {
+                !self
+                    .local
+                    .iter()
+                    .any(|local| local.text.eq_ignore_ascii_case(&suggestion.text))
+            }))
+            .take(MAX_SUGGESTIONS)
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.local.is_empty() && self.backend.is_empty()
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    /// When backend is to be asked, if it is to be
+    pub fn fetch_at(&self) -> Option<Instant> {
+        self.fetch_at
+    }
+
+    /// Text to ask backend suggestions for, once it is time to
+    pub fn take_due_fetch(&mut self, now: Instant) -> Option<String> {
+        if self.fetch_at? > now {
+            return None;
+        }
+        self.fetch_at = None;
+        Some(self.typed.clone())
+    }
+
+    /// Use what backend suggested for `typed`
+    /// Late arrivals for text that is no longer typed are dropped
+    pub fn receive(&mut self, typed: &str, suggestions: Vec<String>) {
+        if typed != self.typed {
+            return;
+        }
+        self.backend = suggestions
+            .into_iter()
+            .filter(|text| !text.eq_ignore_ascii_case(typed))
+            .map(|text| Suggestion {
+                text,
+                source: SuggestionSource::Backend,
+            })
+            .collect();
+    }
+
+    /// Highlight next (Down) or previous (Up) suggestion, or first or last one
+    /// Returns what searchbar should now have: the highlighted suggestion
+    /// or typed text when going above the first one
+    pub fn select(&mut self, direction: MoveDirection) -> Option<String> {
+        let last = self.list().len().checked_sub(1)?;
+        self.selected = match (direction, self.selected) {
+            (MoveDirection::Down, None) => Some(0),
+            (MoveDirection::Down, Some(i)) => Some((i + 1).min(last)),
+            (MoveDirection::Up, Some(0) | None) => None,
+            (MoveDirection::Up, Some(i)) => Some(i - 1),
+            (MoveDirection::First, _) => Some(0),
+            (MoveDirection::Last, _) => Some(last),
+            _ => self.selected,
+        };
+        match self.selected {
+            Some(i) => Some(self.list()[i].text.clone()),
+            None => Some(self.typed.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(suggestions: &Suggestions) -> Vec<(&str, SuggestionSource)> {
+        suggestions
+            .list()
+            .into_iter()
+            .map(|suggestion| (suggestion.text.as_str(), suggestion.source))
+            .collect()
+    }
+
+    #[test]
+    fn sources_are_merged() {
+        let mut history = SearchHistory::default();
+        for entry in ["bartika eam rai", "aagya", "bartika live"] {
+            history.record(entry).unwrap();
+        }
+        let music = MusicUnit {
+            title: "Tal Ma".to_string(),
+            artist: "Bartika Eam Rai".to_string(),
+            ..Default::default()
+        };
+        let now = Instant::now();
+        let mut suggestions = Suggestions::new(" bart", &history, &[&music], true, now);
+
+        use SuggestionSource::*;
+        assert_eq!(
+            vec![("bartika live", History), ("bartika eam rai", History)],
+            texts(&suggestions)
+        );
+
+        // backend is asked only once typing pauses
+        assert_eq!(None, suggestions.take_due_fetch(now));
+        assert_eq!(
+            Some("bart".to_string()),
+            suggestions.take_due_fetch(now + SUGGESTION_DELAY)
+        );
+        assert_eq!(None, suggestions.fetch_at());
+
+        suggestions.receive("bar", vec!["too late".to_string()]);
+        suggestions.receive(
+            "bart",
+            vec!["Bartika Live".to_string(), "bartender".to_string()],
+        );
+        assert_eq!(
+            vec![
+                ("bartika live", History),
+                ("bartika eam rai", History),
+                ("bartender", Backend)
+            ],
+            texts(&suggestions)
+        );
+
+        let suggestions = Suggestions::new("tal", &history, &[&music], false, now);
+        assert_eq!(vec![("Tal Ma", Library)], texts(&suggestions));
+        assert_eq!(None, suggestions.fetch_at());
+        assert!(Suggestions::new("  ", &history, &[&music], true, now).is_empty());
+    }
+
+    #[test]
+    fn arrows_go_through_suggestions() {
+        let mut history = SearchHistory::default();
+        for entry in ["abc", "abd"] {
+            history.record(entry).unwrap();
+        }
+        let mut suggestions = Suggestions::new("ab", &history, &[], false, Instant::now());
+
+        let mut press = |direction| suggestions.select(direction).unwrap();
+        assert_eq!("abd", press(MoveDirection::Down));
+        assert_eq!("abc", press(MoveDirection::Down));
+        assert_eq!("abc", press(MoveDirection::Down));
+        assert_eq!("abd", press(MoveDirection::Up));
+        // back to what was typed
+        assert_eq!("ab", press(MoveDirection::Up));
+        assert_eq!("abc", press(MoveDirection::Last));
+        assert_eq!(Some(1), suggestions.selected());
+
+        assert_eq!(None, Suggestions::default().select(MoveDirection::Down));
+    }
+}
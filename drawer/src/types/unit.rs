@@ -1,14 +1,54 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Clone, Default)]
 pub struct MusicUnit {
     pub title: String,
     pub artist: String,
-    // Todo:
-    // Use duration specific time rather than String
-    pub duration: String,
+    /// None for live streams or if source does not tell
+    pub duration: Option<Duration>,
+    /// Identifier of this music in the source it was fetched from
+    pub id: String,
+    /// Number of times it was viewed, if source tells
+    pub views: Option<u64>,
+    /// When it was uploaded, as told by source. Eg: `3 years ago`
+    pub uploaded: Option<String>,
+    /// Year it was uploaded in, if source tells
+    pub year: Option<i32>,
+    pub description: Option<String>,
+    /// File it is in, for music on this machine
+    pub path: Option<PathBuf>,
+}
+
+impl MusicUnit {
+    /// Url that player can play this music from
+    pub fn url(&self) -> String {
+        match &self.path {
+            Some(path) => path.display().to_string(),
+            None => format!("https://www.youtube.com/watch?v={}", self.id),
+        }
+    }
 }
 
+#[derive(Clone, Default)]
 pub struct PlaylistUnit {
     pub title: String,
     pub creator: String,
     pub song_count: usize,
+    /// Identifier of this playlist in the source it was fetched from
+    pub id: String,
+}
+
+impl PlaylistUnit {
+    /// Url of this playlist in browser
+    pub fn url(&self) -> String {
+        format!("https://www.youtube.com/playlist?list={}", self.id)
+    }
+}
+
+pub struct ArtistUnit {
+    pub name: String,
+    pub subscriber_count: usize,
+    /// Identifier of this artist in the source it was fetched from
+    pub id: String,
 }
-pub struct ArtistUnit;
@@ -1,7 +1,9 @@
 use std::collections::HashMap;
+use std::time::Duration;
 
 use tui::layout::Constraint;
 use tui::layout::Rect as TuiRect;
+use user_config::reexports::layout_config::ui::UI;
 use user_config::reexports::Identifier as ItemIdentifier;
 use user_config::reexports::Rect as MyRect;
 
@@ -37,6 +39,153 @@ pub fn into_my_rect(tui_rect: TuiRect) -> MyRect {
     }
 }
 
+/// Format as mm:ss, or as h:mm:ss if an hour or longer
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes:02}:{seconds:02}")
+    }
+}
+
+/// Like format_duration, but `--:--` when duration is not known
+pub fn format_maybe_duration(duration: Option<Duration>) -> String {
+    duration
+        .map(format_duration)
+        .unwrap_or_else(|| "--:--".to_string())
+}
+
+/// Year (in UTC) of unix timestamp given in seconds
+pub fn year_of_timestamp(seconds: i64) -> i32 {
+    // days since 0000-03-01, so that leap day is last day of year
+    let days = seconds.div_euclid(86400) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    // january and february are in next year
+    let year = year_of_era + era * 400 + i64::from(month_from_march >= 10);
+    year as i32
+}
+
+/// Read length of track as written by backends:
+/// `mm:ss`, `h:mm:ss` or ISO-8601 as in `PT3M12S`
+pub fn parse_track_length(text: &str) -> Result<Duration, String> {
+    let text = text.trim();
+    if let Some(iso) = text.strip_prefix("PT") {
+        return parse_iso_duration(iso).ok_or_else(|| format!("Invalid duration: {text}"));
+    }
+
+    let parts = text
+        .split(':')
+        .map(str::parse::<u64>)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("While parsing duration {text}: {e}"))?;
+    let seconds = match parts.as_slice() {
+        [minutes, seconds] if *seconds < 60 => minutes
+            .checked_mul(60)
+            .and_then(|minutes| minutes.checked_add(*seconds)),
+        [hours, minutes, seconds] if *minutes < 60 && *seconds < 60 => hours
+            .checked_mul(3600)
+            .and_then(|hours| hours.checked_add(minutes * 60 + seconds)),
+        _ => None,
+    };
+    seconds
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("Invalid duration: {text}"))
+}
+
+/// Part of ISO-8601 duration after `PT`, like `1H3M12.5S`
+fn parse_iso_duration(iso: &str) -> Option<Duration> {
+    let mut seconds = 0.0;
+    let mut number = String::new();
+    for ch in iso.chars() {
+        let unit = match ch {
+            'H' => 3600.0,
+            'M' => 60.0,
+            'S' => 1.0,
+            _ => {
+                number.push(ch);
+                continue;
+            }
+        };
+        let value = number
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite() && *value >= 0.0)?;
+        seconds += value * unit;
+        number.clear();
+    }
+    if !number.is_empty() || iso.is_empty() {
+        return None;
+    }
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+/// Group digits by thousands as in 1,234,567
+pub fn format_count(count: u64) -> String {
+    let digits = count.to_string();
+    let mut formatted = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+    formatted
+}
+
+/// Break single line of text into rows of at most `width` characters
+/// Breaks are made at spaces where possible, words longer
+/// than a row (like links) are split so that nothing is lost
+pub fn wrap_text(line: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut rows = Vec::new();
+    let mut row = String::new();
+    for word in line.split(' ') {
+        let row_len = row.chars().count();
+        let word_len = word.chars().count();
+        if row_len > 0 && row_len + 1 + word_len > width {
+            rows.push(std::mem::take(&mut row));
+        } else if row_len > 0 {
+            row.push(' ');
+        }
+        let mut chars = word.chars().peekable();
+        while chars.peek().is_some() {
+            let room = width - row.chars().count();
+            if room == 0 {
+                rows.push(std::mem::take(&mut row));
+                continue;
+            }
+            row.extend(chars.by_ref().take(room));
+        }
+    }
+    rows.push(row);
+    rows
+}
+
+/// Where popups go: centered in `area`, sized with popupWidth and popupHeight of layout
+pub fn popup_rect(layout: &UI, area: TuiRect) -> TuiRect {
+    let width = layout
+        .popup_width
+        .get_absolute(area.width, 0)
+        .min(area.width);
+    let height = layout
+        .popup_height
+        .get_absolute(area.height, 0)
+        .min(area.height);
+    TuiRect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
 pub fn consume_and_get_geometry(
     rect_map: &mut HashMap<ItemIdentifier, MyRect>,
 ) -> Result<GeometryData, &'static str> {
@@ -81,6 +230,10 @@ pub fn consume_and_get_geometry(
         spacing: 1,
         splits: [Constraint::Length(10); 3],
     };
+    let artistpane_division = super::state::PaneDivision {
+        spacing: 1,
+        splits: [Constraint::Length(10); 2],
+    };
 
     Ok(GeometryData {
         searchbar,
@@ -90,8 +243,11 @@ pub fn consume_and_get_geometry(
         musicpane: result_pane,
         playlistpane: result_pane,
         artistpane: result_pane,
+        // depends on layout rather than on any item of it
+        popup: TuiRect::default(),
         musicpane_division,
         playlistpane_division,
+        artistpane_division,
     })
 }
 
@@ -100,6 +256,7 @@ mod tests {
     use super::*;
     use crate::gadgets::state::PaneDivision;
     use tui::layout::Constraint;
+    use user_config::reexports::layout_config::length::Length;
 
     fn my_rect_with_x(x: u16) -> MyRect {
         MyRect {
@@ -140,6 +297,7 @@ mod tests {
             musicpane: tui_rect_with_x(5),
             playlistpane: tui_rect_with_x(5),
             artistpane: tui_rect_with_x(5),
+            popup: TuiRect::default(),
             musicpane_division: PaneDivision {
                 spacing: 1,
                 splits: [Constraint::Length(10); 3],
@@ -148,9 +306,93 @@ mod tests {
                 spacing: 1,
                 splits: [Constraint::Length(10); 3],
             },
+            artistpane_division: PaneDivision {
+                spacing: 1,
+                splits: [Constraint::Length(10); 2],
+            },
         };
 
         assert_eq!(Ok(expected_geometry_data), result_geometry);
         assert_eq!(0, map.iter().len());
     }
+
+    #[test]
+    fn popup_is_centered() {
+        let mut layout = user_config::default_config().layout;
+        let area = TuiRect::new(0, 0, 100, 40);
+        assert_eq!(TuiRect::new(10, 4, 80, 32), popup_rect(&layout, area));
+
+        layout.popup_width = Length::AtLeast(120);
+        layout.popup_height = Length::Absolute(10);
+        assert_eq!(TuiRect::new(0, 15, 100, 10), popup_rect(&layout, area));
+    }
+
+    #[test]
+    fn check_format_duration() {
+        assert_eq!("00:00", format_duration(Duration::ZERO));
+        assert_eq!("03:07", format_duration(Duration::from_secs_f64(187.9)));
+        assert_eq!("59:59", format_duration(Duration::from_secs(3599)));
+        assert_eq!("1:00:00", format_duration(Duration::from_secs(3600)));
+        assert_eq!("12:03:04", format_duration(Duration::from_secs(43384)));
+        assert_eq!("--:--", format_maybe_duration(None));
+    }
+
+    #[test]
+    fn check_parse_track_length() {
+        let secs = |s| Ok(Duration::from_secs(s));
+        assert_eq!(secs(223), parse_track_length("03:43"));
+        assert_eq!(secs(4500), parse_track_length("75:00"));
+        assert_eq!(secs(3725), parse_track_length("1:02:05"));
+        assert_eq!(secs(192), parse_track_length("PT3M12S"));
+        assert_eq!(secs(3601), parse_track_length("PT1H1S"));
+        assert_eq!(
+            Ok(Duration::from_millis(1500)),
+            parse_track_length("PT1.5S")
+        );
+
+        for invalid in [
+            "",
+            "3",
+            "1:60",
+            "1:60:00",
+            "a:bc",
+            "PT",
+            "PT3",
+            "PT-3S",
+            "PT3X",
+            "PT1e300S",
+            "PT1e308H1e308H",
+            "999999999999999999:00",
+            "9999999999999999:00:00",
+        ] {
+            assert!(parse_track_length(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn check_year_of_timestamp() {
+        assert_eq!(1970, year_of_timestamp(0));
+        assert_eq!(1969, year_of_timestamp(-1));
+        // 2020-12-31T23:59:59 and a second later
+        assert_eq!(2020, year_of_timestamp(1_609_459_199));
+        assert_eq!(2021, year_of_timestamp(1_609_459_200));
+        // 2024-02-29
+        assert_eq!(2024, year_of_timestamp(1_709_164_800));
+    }
+
+    #[test]
+    fn counts_and_wrapping() {
+        assert_eq!("0", format_count(0));
+        assert_eq!("999", format_count(999));
+        assert_eq!("1,000", format_count(1000));
+        assert_eq!("12,345,678", format_count(12345678));
+
+        assert_eq!(vec![""], wrap_text("", 5));
+        assert_eq!(vec!["a bc", "def", "g"], wrap_text("a bc def g", 4));
+        // nothing of long words is lost
+        assert_eq!(
+            vec!["see", "https:", "//x.y/", "abc"],
+            wrap_text("see https://x.y/abc", 6)
+        );
+    }
 }
@@ -0,0 +1,87 @@
+mod common;
+use common::*;
+use drawer::gadgets::{
+    artistpane::{self, ArtistpaneGeometry},
+    unit::ArtistUnit,
+};
+use tui::{layout::Rect, widgets::TableState};
+
+struct ExampleGeometry;
+struct ExampleArtistpaneAppdata {
+    artist_list: Vec<ArtistUnit>,
+}
+
+impl ExampleArtistpaneAppdata {
+    fn new_filled() -> Self {
+        let rows = [
+            ("Bartika Eam Rai", 120_000),
+            ("Rachana Dahal", 89_000),
+            ("Sajjan Raj Vaidya", 1_250_000),
+            (
+                "Some band with a very long name that will not fit in the column",
+                12,
+            ),
+        ]
+        .into_iter()
+        .collect::<Vec<_>>();
+        let artist_list = rows
+            .clone()
+            .into_iter()
+            .chain(rows.clone())
+            .chain(rows.clone())
+            .map(|(name, subscriber_count)| ArtistUnit {
+                name: name.to_string(),
+                subscriber_count,
+                id: String::new(),
+            })
+            .collect::<Vec<ArtistUnit>>();
+
+        Self { artist_list }
+    }
+}
+
+impl ArtistpaneGeometry for ExampleGeometry {
+    fn column_division(&self) -> &[Constraint] {
+        &[Constraint::Percentage(70), Constraint::Percentage(30)]
+    }
+    fn column_spacing(&self) -> u16 {
+        2
+    }
+}
+
+impl artistpane::ArtistpaneAppdata for ExampleArtistpaneAppdata {
+    fn is_artistpane_active(&self) -> bool {
+        true
+    }
+
+    fn selected(&self) -> Option<usize> {
+        Some(2)
+    }
+
+    fn artist_list(&self) -> &[ArtistUnit] {
+        &self.artist_list
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    entrypoint(draw_artistpane)
+}
+
+fn draw_artistpane<B: Backend>(f: &mut Frame<B>) {
+    let theme = get_default_theme();
+    let artistpane = artistpane::get_artistpane_list(
+        &ExampleArtistpaneAppdata::new_filled(),
+        &ExampleGeometry,
+        &theme,
+    );
+    let place = Rect {
+        x: 15,
+        y: 7,
+        height: f.size().height - (7 + 4),
+        width: f.size().width - (15 + 10),
+    };
+    let mut table_state = TableState::default();
+    table_state.select(Some(2));
+
+    f.render_stateful_widget(artistpane, place, &mut table_state);
+}
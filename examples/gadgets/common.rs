@@ -1,3 +1,5 @@
+#![allow(unused_imports, dead_code)]
+
 pub use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
     execute,
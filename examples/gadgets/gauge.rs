@@ -1,6 +1,7 @@
 mod common;
 use common::*;
 use drawer::gadgets::gauge;
+use std::time::Duration;
 use tui::layout::Rect;
 
 struct ExampleGaugeAppdata;
@@ -9,11 +10,11 @@ impl gauge::GaugeAppData for ExampleGaugeAppdata {
     fn is_gauge_active(&self) -> bool {
         false
     }
-    fn music_total_duration(&self) -> String {
-        "05:35".to_string()
+    fn music_total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs(335))
     }
-    fn played_music_duration(&self) -> String {
-        "03:54".to_string()
+    fn played_music_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs(234))
     }
     fn music_title(&self) -> String {
         "Bimbakash (Maya Jastai) - From album Bimbakash - Bartika Eam Rai".to_string()
@@ -4,6 +4,7 @@ use drawer::gadgets::{
     musicpane::{self, MusicpaneGeometry},
     unit::MusicUnit,
 };
+use drawer::types::utils;
 use tui::{layout::Rect, widgets::TableState};
 
 struct ExampleGeometry;
@@ -37,14 +38,16 @@ impl ExampleMusicpaneAppdata {
         let music_list = rows
             .clone()
             .into_iter()
-            .chain(rows.clone().into_iter())
-            .chain(rows.clone().into_iter())
-            .chain(rows.clone().into_iter())
-            .chain(rows.clone().into_iter())
+            .chain(rows.clone())
+            .chain(rows.clone())
+            .chain(rows.clone())
+            .chain(rows.clone())
             .map(|[title, artist, duration]| MusicUnit {
                 title: title.to_string(),
                 artist: artist.to_string(),
-                duration: duration.to_string(),
+                duration: utils::parse_track_length(duration).ok(),
+                id: String::new(),
+                ..Default::default()
             })
             .collect::<Vec<MusicUnit>>();
 
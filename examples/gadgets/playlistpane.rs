@@ -33,14 +33,15 @@ impl ExamplePlaylistpaneAppdata {
         let playlist_list = rows
             .clone()
             .into_iter()
-            .chain(rows.clone().into_iter())
-            .chain(rows.clone().into_iter())
-            .chain(rows.clone().into_iter())
-            .chain(rows.clone().into_iter())
+            .chain(rows.clone())
+            .chain(rows.clone())
+            .chain(rows.clone())
+            .chain(rows.clone())
             .map(|(title, creator, count)| PlaylistUnit {
                 song_count: count,
                 title: title.to_string(),
                 creator: creator.to_string(),
+                id: String::new(),
             })
             .collect::<Vec<PlaylistUnit>>();
 
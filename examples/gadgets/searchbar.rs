@@ -2,6 +2,7 @@ mod common;
 use common::*;
 use drawer::gadgets::searchbar;
 use tui::layout::Rect;
+use user_config::preferences::searchbar::SearchbarPreferences;
 
 struct ExampleSearchbarAppdata;
 
@@ -21,13 +22,18 @@ fn main() -> Result<(), Box<dyn Error>> {
 
 fn draw_searchbar<B: Backend>(f: &mut Frame<B>) {
     let theme = get_default_theme();
-    let searchbar = searchbar::get_searchbar(&ExampleSearchbarAppdata, &theme);
     let place = Rect {
         x: 0,
         y: 0,
         height: 3,
         width: f.size().width,
     };
+    let searchbar = searchbar::get_searchbar(
+        &ExampleSearchbarAppdata,
+        &theme,
+        &SearchbarPreferences::default(),
+        place.width,
+    );
 
     f.render_widget(searchbar, place);
 }
@@ -2,6 +2,7 @@ mod common;
 use common::*;
 use drawer::gadgets::shortcut;
 use tui::layout::Rect;
+use user_config::preferences::shortcut::ShortcutPreferences;
 
 struct ExampleShortcutAppdata;
 
@@ -21,7 +22,8 @@ fn main() -> Result<(), Box<dyn Error>> {
 
 fn draw_searchbar<B: Backend>(f: &mut Frame<B>) {
     let theme = get_default_theme();
-    let shortcut = shortcut::get_shortcut_list(&ExampleShortcutAppdata, &theme);
+    let items = ShortcutPreferences::default().items;
+    let shortcut = shortcut::get_shortcut_list(&ExampleShortcutAppdata, &items, &theme);
     let place = Rect {
         x: 0,
         y: 3,
@@ -1,28 +1,37 @@
 mod common;
 use common::*;
+use drawer::gadgets::artistpane::ArtistpaneAppdata;
+use drawer::gadgets::commandline::CommandlineAppdata;
 use drawer::gadgets::gauge::GaugeAppData;
+use drawer::gadgets::keyhint::KeyhintAppdata;
 use drawer::gadgets::musicpane::MusicpaneAppdata;
 use drawer::gadgets::panetab::get_preferred_width as panetab_preferred_width;
 use drawer::gadgets::panetab::PanetabAppdata;
 use drawer::gadgets::playlistpane::PlaylistpaneAppdata;
+use drawer::gadgets::popup::PopupAppdata;
 use drawer::gadgets::searchbar::SearchbarAppdata;
 use drawer::gadgets::shortcut::ShortcutListAppdata;
+use drawer::gadgets::state::ArtistPaneState;
 use drawer::gadgets::state::GeometryData;
 use drawer::gadgets::state::MusicPaneState;
 use drawer::gadgets::state::PaneDivision;
 use drawer::gadgets::state::PanetabState;
 use drawer::gadgets::state::PlaylistPaneState;
 use drawer::gadgets::state::ShortcutListState;
+use drawer::gadgets::suggestion::SuggestionAppdata;
 use drawer::gadgets::ui::draw_all_ui;
 use drawer::gadgets::ui::Provider;
+use drawer::gadgets::unit::ArtistUnit;
 use drawer::gadgets::unit::MusicUnit;
 use drawer::gadgets::unit::PlaylistUnit;
 use drawer::types::window::PaneWindow;
+use std::time::Duration;
 use tui::layout::Constraint;
 use tui::layout::Rect;
 use tui::widgets::ListState;
 use tui::widgets::TableState;
 use user_config::preferences::theme::Theme;
+use user_config::preferences::WidgetPreferences;
 use user_config::styles::color::RGB;
 
 struct ExampleAppdata {
@@ -51,14 +60,15 @@ impl ExampleAppdata {
         let playlist_list = rows
             .clone()
             .into_iter()
-            .chain(rows.clone().into_iter())
-            .chain(rows.clone().into_iter())
-            .chain(rows.clone().into_iter())
-            .chain(rows.clone().into_iter())
+            .chain(rows.clone())
+            .chain(rows.clone())
+            .chain(rows.clone())
+            .chain(rows.clone())
             .map(|(title, creator, count)| PlaylistUnit {
                 song_count: count,
                 title: title.to_string(),
                 creator: creator.to_string(),
+                id: String::new(),
             })
             .collect::<Vec<PlaylistUnit>>();
 
@@ -79,11 +89,11 @@ impl GaugeAppData for ExampleAppdata {
     fn is_gauge_active(&self) -> bool {
         false
     }
-    fn played_music_duration(&self) -> String {
-        "04:56".to_string()
+    fn played_music_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs(296))
     }
-    fn music_total_duration(&self) -> String {
-        "08:03".to_string()
+    fn music_total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs(483))
     }
     fn music_title(&self) -> String {
         "Kabira -[Slowed+Reverb]- Ya Jawaani Hai Deewani ||Last Hope".to_string()
@@ -132,6 +142,26 @@ impl PlaylistpaneAppdata for ExampleAppdata {
     }
 }
 
+impl ArtistpaneAppdata for ExampleAppdata {
+    fn is_artistpane_active(&self) -> bool {
+        false
+    }
+    fn artist_list(&self) -> &[ArtistUnit] {
+        &[]
+    }
+    fn selected(&self) -> Option<usize> {
+        None
+    }
+}
+
+impl KeyhintAppdata for ExampleAppdata {}
+
+impl CommandlineAppdata for ExampleAppdata {}
+
+impl PopupAppdata for ExampleAppdata {}
+
+impl SuggestionAppdata for ExampleAppdata {}
+
 impl Provider<ShortcutListState> for ExampleAppdata {
     fn provide(&self) -> ShortcutListState {
         let mut list_state = ListState::default();
@@ -162,6 +192,12 @@ impl Provider<PlaylistPaneState> for ExampleAppdata {
     }
 }
 
+impl Provider<ArtistPaneState> for ExampleAppdata {
+    fn provide(&self) -> ArtistPaneState {
+        ArtistPaneState(TableState::default())
+    }
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     entrypoint(draw_playlistpane)
 }
@@ -233,6 +269,11 @@ fn draw_playlistpane<B: tui::backend::Backend>(f: &mut tui::Frame<B>) {
         ],
     };
 
+    let artistpane_division = PaneDivision::<2> {
+        spacing: 1,
+        splits: [Constraint::Percentage(70), Constraint::Percentage(30)],
+    };
+
     let geometry = GeometryData {
         artistpane: artistpane_rect,
         playlistpane: playlistpane_rect,
@@ -241,8 +282,11 @@ fn draw_playlistpane<B: tui::backend::Backend>(f: &mut tui::Frame<B>) {
         searchbar: searchbar_rect,
         shortcuts: shortcuts_rect,
         panetab: panetab_rect,
+        // nothing is shown in popup
+        popup: Rect::default(),
         musicpane_division,
         playlistpane_division,
+        artistpane_division,
     };
 
     let theme = Theme {
@@ -254,5 +298,11 @@ fn draw_playlistpane<B: tui::backend::Backend>(f: &mut tui::Frame<B>) {
 
     let appdata = ExampleAppdata::get_filled();
 
-    draw_all_ui(f, &appdata, &theme, &geometry)
+    draw_all_ui(
+        f,
+        &appdata,
+        &theme,
+        &WidgetPreferences::default(),
+        &geometry,
+    )
 }
@@ -49,7 +49,7 @@ mod tests {
         };
         let to_string = |v: Direction| {
             let json_string = serde_json::to_string(&v);
-            json_string.map(|s| (&s[1..s.len() - 1]).to_string())
+            json_string.map(|s| s[1..s.len() - 1].to_string())
         };
 
         assert_eq!(Vertical, from_str("vertical").unwrap());
@@ -50,7 +50,10 @@ impl TryFrom<String> for Identifier {
     type Error = &'static str;
 
     fn try_from(src: String) -> Result<Self, Self::Error> {
-        let is_custom = src
+        // Gadget placed under specific container is written as
+        // `Container->gadget`, so only look at the last part
+        let own_name = src.rsplit("->").next().unwrap_or_default();
+        let is_custom = own_name
             .chars()
             .next()
             .ok_or("Empty identifier is not valid")?
@@ -103,11 +106,15 @@ mod test {
         };
         let to_string = |v: Identifier| {
             let json_string = serde_json::to_string(&v);
-            json_string.map(|s| (&s[1..s.len() - 1]).to_string())
+            json_string.map(|s| s[1..s.len() - 1].to_string())
         };
 
         assert_eq!(Gadget("cUstom".into()), from_str("cUstom").unwrap());
         assert_eq!(Gadget("a_name".into()), from_str("a_name").unwrap());
+        assert_eq!(
+            Gadget("TopArea->searchbar".into()),
+            from_str("TopArea->searchbar").unwrap()
+        );
         for container in ["TopArea", "Constainer", "Apple"] {
             assert_eq!(
                 Container(container.to_string()),
@@ -81,7 +81,7 @@ mod tests {
         };
         let to_string = |v: Length| {
             let json_string = serde_json::to_string(&v);
-            json_string.map(|s| (&s[1..s.len() - 1]).to_string())
+            json_string.map(|s| s[1..s.len() - 1].to_string())
         };
 
         assert_eq!(Absolute(0), from_str("0a").unwrap());
@@ -15,7 +15,10 @@ pub enum Window {
 impl std::fmt::Display for Window {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let window =
-            serde_json::to_string(self).expect("to_string call to simple enum should not fail");
+            serde_json::to_value(self).expect("to_value call to simple enum should not fail");
+        let window = window
+            .as_str()
+            .expect("unit variant is always serialized as string");
         write!(f, "{window}")
     }
 }
@@ -56,7 +59,7 @@ impl Window {
     }
 }
 
-#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum PaneWindow {
     MusicPane = 0,
     PlaylistPane = 1,
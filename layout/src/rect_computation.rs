@@ -10,10 +10,10 @@ enum NoSiblingRect {
     IsEldestChild,
 }
 
-fn get_sibling_rect<'a, 'b, 's>(
-    me: &'b ItemTree,
+fn get_sibling_rect<'s>(
+    me: &ItemTree,
     size_map: &'s mut HashMap<Identifier, Rect>,
-    terminal_rect: &'a Rect,
+    terminal_rect: &Rect,
 ) -> Result<&'s Rect, NoSiblingRect> {
     let parent = me.parent.as_ref().ok_or(NoSiblingRect::IsOrphan)?;
 
@@ -150,7 +150,7 @@ mod tests {
     use user_config::Config;
 
     fn ensure_boundry_check(item_root: ItemTree, filled_size_map: &HashMap<Identifier, Rect>) {
-        let tree_as_vec: Vec<_> = item_root.try_into().unwrap();
+        let tree_as_vec: Vec<_> = item_root.into();
         tree_as_vec.iter().for_each(|item| {
             let (my_height, my_width) = filled_size_map
                 .get(&item.identifier)
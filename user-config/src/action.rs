@@ -1,7 +1,10 @@
 use crate::keyboard::Key;
-use layout_config::window::Window;
+use crate::mode::Mode;
+use layout_config::window::{PaneWindow, Window};
 use serde::{Deserialize, Serialize};
-use std::{borrow::Cow, collections::HashMap};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
 
 #[repr(transparent)]
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
@@ -10,53 +13,103 @@ pub struct KeyboardMapping(HashMap<MappingIndex, KeyboardAction>);
 
 // In JSON representation this should be represented as:
 // Examples:
-// (Down, Some(MusicList))->MoveDown <=> "Down|MusicList": "MoveDown"
-// (Up, Some(MusicList))->MoveUp <=> "Up|MusicList": "MoveUp"
-// (Up, None)->PreviousWindow <=> "Up": "PreviousWindow"
+// ([Down], Some(MusicList), Normal)->MoveDown <=> "Down|MusicList": "MoveDown"
+// ([Up], Some(MusicList), Normal)->MoveUp <=> "Up|MusicList": "MoveUp"
+// ([Up], None, Normal)->PreviousWindow <=> "Up": "PreviousWindow"
+// ([g, g], Some(PaneWindow), Normal)->GotoTop <=> "gg|PaneWindow": "GotoTop"
+// ([Esc], None, Insert)->GotoNextWindow <=> "<esc>|insert": "GotoNextWindow"
+// ([Esc], Some(SearchBar), Insert)->Nothing <=> "<esc>|SearchBar|insert": "Nothing"
 //
-// Window name is to be seperated by single `|`
-// if nothing is behind last `|` or no `|` then take Option to be None
+// Window and mode are to be seperated by single `|`
+// Mode is always the last one and is Normal if not given
+// if no window is given then take Option to be None
+// Keys before them are pressed one after another
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(try_from = "String")]
 #[serde(into = "String")]
-pub struct MappingIndex(Key, Option<Window>);
+pub struct MappingIndex(Vec<Key>, Option<Window>, Mode);
 
 impl TryFrom<String> for MappingIndex {
     type Error = String;
 
     fn try_from(mapping_index: String) -> Result<Self, Self::Error> {
-        if let Some((key_str, window_str)) = mapping_index.rsplit_once('|') {
-            let key = serde_json::from_str::<Key>(key_str)
-                .map_err(|_| format!("Invalid key in mapping index: {mapping_index}"))?;
-            let window = serde_json::from_str::<Window>(window_str)
-                .map_err(|_| format!("Invalid window in mapping index: {mapping_index}"))?;
+        let (rest, mode) = match mapping_index.rsplit_once('|') {
+            Some((rest, mode_str)) => match serde_json::from_value::<Mode>(mode_str.into()) {
+                Ok(mode) => (rest, mode),
+                Err(_) => (mapping_index.as_str(), Mode::Normal),
+            },
+            None => (mapping_index.as_str(), Mode::Normal),
+        };
+
+        if let Some((keys_str, window_str)) = rest.rsplit_once('|') {
+            let keys = Key::parse_sequence(keys_str).map_err(|e| {
+                format!("Invalid key in mapping index: {mapping_index}. Error: {e}")
+            })?;
+            let window = serde_json::from_value::<Window>(window_str.into())
+                .map_err(|_| format!("Invalid window or mode in mapping index: {mapping_index}"))?;
 
-            Ok(MappingIndex(key, Some(window)))
+            Ok(MappingIndex(keys, Some(window), mode))
         } else {
-            let key: Key = Cow::<'static, str>::Owned(mapping_index.clone())
-                .try_into()
+            let keys = Key::parse_sequence(rest)
                 .map_err(|e| format!("Invalid mapping index: {mapping_index}. Error: {e:?}"))?;
 
-            Ok(MappingIndex(key, None))
+            Ok(MappingIndex(keys, None, mode))
         }
     }
 }
 
 impl From<MappingIndex> for String {
-    fn from(MappingIndex(key, window): MappingIndex) -> Self {
-        let key_str: std::borrow::Cow<'static, str> = key.into();
+    fn from(MappingIndex(keys, window, mode): MappingIndex) -> Self {
+        let keys_str = Key::sequence_to_string(&keys);
         let window_str = match window {
             None => String::new(),
             Some(window) => format!("|{window}"),
         };
+        let mode_str = match mode {
+            Mode::Normal => String::new(),
+            mode => format!("|{mode}"),
+        };
+
+        format!("{keys_str}{window_str}{mode_str}")
+    }
+}
+
+impl MappingIndex {
+    pub fn keys(&self) -> &[Key] {
+        &self.0
+    }
+
+    /// Window binding is limited to. None if it applies to every window
+    pub fn window(&self) -> Option<Window> {
+        self.1
+    }
 
-        format!("{key_str}{window_str}")
+    pub fn mode(&self) -> Mode {
+        self.2
+    }
+}
+
+impl std::fmt::Display for MappingIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", String::from(self.clone()))
     }
 }
 
 impl From<(Key, Option<layout_config::window::Window>)> for MappingIndex {
     fn from((key, window): (Key, Option<layout_config::window::Window>)) -> Self {
-        MappingIndex(key, window)
+        MappingIndex(vec![key], window, Mode::Normal)
+    }
+}
+
+impl From<(Vec<Key>, Option<layout_config::window::Window>)> for MappingIndex {
+    fn from((keys, window): (Vec<Key>, Option<layout_config::window::Window>)) -> Self {
+        MappingIndex(keys, window, Mode::Normal)
+    }
+}
+
+impl From<(Vec<Key>, Option<layout_config::window::Window>, Mode)> for MappingIndex {
+    fn from((keys, window, mode): (Vec<Key>, Option<layout_config::window::Window>, Mode)) -> Self {
+        MappingIndex(keys, window, mode)
     }
 }
 
@@ -74,6 +127,74 @@ impl KeyboardMapping {
     pub fn action_for(&self, key: &MappingIndex) -> Option<KeyboardAction> {
         self.0.get(key).cloned()
     }
+
+    /// Every binding in no particular order
+    pub fn bindings(&self) -> impl Iterator<Item = (&MappingIndex, KeyboardAction)> {
+        self.0.iter().map(|(index, action)| (index, *action))
+    }
+
+    /// Action bound to exactly these keys in given window and mode
+    /// Binding specific to window wins over global one
+    /// Bindings of other modes are never considered
+    pub fn resolve(&self, keys: &[Key], window: Window, mode: Mode) -> Option<KeyboardAction> {
+        self.action_for(&(keys.to_vec(), Some(window), mode).into())
+            .or_else(|| self.action_for(&(keys.to_vec(), None, mode).into()))
+    }
+
+    /// Every binding of given action, as written in config
+    /// sorted so that listing is same every time
+    pub fn bindings_of(&self, action: KeyboardAction) -> Vec<String> {
+        let mut bindings = self
+            .0
+            .iter()
+            .filter(|(_, bound)| **bound == action)
+            .map(|(index, _)| String::from(index.clone()))
+            .collect::<Vec<_>>();
+        bindings.sort();
+        bindings
+    }
+
+    /// Every distinct action that have some binding
+    pub fn bound_actions(&self) -> Vec<KeyboardAction> {
+        let mut actions = Vec::new();
+        for action in self.0.values() {
+            if !actions.contains(action) {
+                actions.push(*action);
+            }
+        }
+        actions
+    }
+
+    /// Bindings that start with `prefix` and are longer than it
+    /// as (remaining keys, action), sorted by remaining keys
+    pub fn continuations(
+        &self,
+        prefix: &[Key],
+        window: Window,
+        mode: Mode,
+    ) -> Vec<(Vec<Key>, KeyboardAction)> {
+        let mut found = self
+            .0
+            .keys()
+            .filter(|MappingIndex(keys, for_window, for_mode)| {
+                keys.len() > prefix.len()
+                    && keys.starts_with(prefix)
+                    && (for_window.is_none() || *for_window == Some(window))
+                    && *for_mode == mode
+            })
+            .map(|MappingIndex(keys, ..)| keys.clone())
+            .collect::<Vec<_>>();
+        found.sort_by_key(|keys| Key::sequence_to_string(keys));
+        found.dedup();
+
+        found
+            .into_iter()
+            .filter_map(|keys| {
+                let action = self.resolve(&keys, window, mode)?;
+                Some((keys[prefix.len()..].to_vec(), action))
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
@@ -82,10 +203,49 @@ pub enum MoveDirection {
     Left,
     Down,
     Right,
+    PageUp,
+    PageDown,
+    First,
+    Last,
+}
+
+/// Where cursor goes in a line being typed
+/// Deleting with a motion removes text between cursor and where it would go
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+pub enum Motion {
+    Left,
+    Right,
+    /// Start of word before cursor
+    WordLeft,
+    /// End of word after cursor
+    WordRight,
+    /// Start of line
+    Start,
+    /// End of line
+    End,
+}
+
+/// A point in playing track
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum TrackPosition {
+    /// Time from the start of track. Written as `1m30s`
+    Time(Duration),
+    /// Percent of whole track. Written as `25%`
+    Percent(u8),
 }
 
+/// Seek step when none is given in config
+pub const DEFAULT_SEEK_STEP: Duration = Duration::from_secs(5);
+/// Volume step when none is given in config
+pub const DEFAULT_VOLUME_STEP: u8 = 5;
+
 /// Possible set of actions that can be performed from keyboard
+///
+/// In config these are written as `"Name"` or `"Name(argument)"`
+/// For example: `"Quit"`, `"SeekForward(10s)"`, `"GotoPane(Playlist)"`
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(try_from = "serde_json::Value")]
+#[serde(into = "String")]
 pub enum KeyboardAction {
     // Goto the searchbar
     StartSearching,
@@ -97,15 +257,20 @@ pub enum KeyboardAction {
     GotoPrviousWindow,
     // Select/ Enter/ Submit
     Execute,
+    // Add highlighted item to the end of queue
+    AddToQueue,
     // Toggle shuffle on/off
     ShuffleToggle,
     // Toggle repeat one/ repeat playleat/ stop after current i.e no repeat
     RepeatSwitch,
-    // bring volume down
-    VolumeUp,
-    // bring volume up
-    VolumeDown,
+    // bring volume up by this percent
+    VolumeUp(u8),
+    // bring volume down by this percent
+    VolumeDown(u8),
+    // Set volume to this percent
+    SetVolume(u8),
     // expand details of item
+    // same as ShowDetails for now
     Expand,
     // Close any popup/ get out of insert mode in searchbar
     Escape,
@@ -120,21 +285,36 @@ pub enum KeyboardAction {
     PreviousTrack,
     // Clear the results of current query
     ClearResult,
-    // Show details of currenlty hilighted item
+    // Show details of currenlty hilighted item in a popup
     ShowDetails,
     // about this binary
     // and user config
     AppDetails,
     // A action but really isn't
     Nothing,
-    // Seek current track in forward direction
-    SeekForward,
-    // Seek current track in backward direction
-    SeekBackward,
+    // Seek current track in forward direction by this much
+    SeekForward(Duration),
+    // Seek current track in backward direction by this much
+    SeekBackward(Duration),
+    // Move to this point in current track
+    Seek(TrackPosition),
     // Insert this character to search query
     PushSearchQuery(char),
-    // Remove last character from search query
+    // Remove character before cursor from search query
     PopSearchQuery,
+    // Move cursor in search query
+    MoveSearchCursor(Motion),
+    // Remove from cursor to where motion would move it in search query
+    DeleteSearchQuery(Motion),
+    // Put older (Up) or newer (Down) search from history in search query
+    MoveInSearchHistory(MoveDirection),
+    // Search history for what is typed next, older match on each press
+    ReverseSearchHistory,
+    // Highlight next (Down) or previous (Up) suggestion under searchbar
+    // Goes through search history instead when nothing is suggested
+    MoveInSuggestions(MoveDirection),
+    // Show this tab of PaneWindow
+    GotoPane(PaneWindow),
     // Move in PaneWindow
     MoveInPaneWindow(MoveDirection),
     // Move in Shortcut list
@@ -145,4 +325,584 @@ pub enum KeyboardAction {
     MoveInPlaylistList(MoveDirection),
     // Move in Artist tab list
     MoveInArtistList(MoveDirection),
+    // Select next item in active list starting with this character
+    JumpToLetter(char),
+    // Remove everything from queue
+    ClearQueue,
+    // Open `:` prompt to type a command in
+    OpenCommandLine,
+    // Open list of every action to pick from
+    OpenPalette,
+    // Insert this character to command line
+    PushCommandLine(char),
+    // Remove last character from command line
+    PopCommandLine,
+    // Complete command or argument being typed
+    CompleteCommandLine,
+    // Move in completions or palette entries
+    MoveInCommandLine(MoveDirection),
+    // Run what is typed in command line or picked in palette
+    ExecuteCommandLine,
+    // Close command line or palette without doing anything
+    CloseCommandLine,
+    // Scroll text of popup on top
+    MoveInPopup(MoveDirection),
+}
+
+/// Name of every action, as written in config
+pub const ACTION_NAMES: &[&str] = &[
+    "StartSearching",
+    "PausePlay",
+    "GotoNextWindow",
+    "GotoPrviousWindow",
+    "Execute",
+    "AddToQueue",
+    "ShuffleToggle",
+    "RepeatSwitch",
+    "VolumeUp",
+    "VolumeDown",
+    "SetVolume",
+    "Expand",
+    "Escape",
+    "Quit",
+    "ForceQuit",
+    "NextTrack",
+    "PreviousTrack",
+    "ClearResult",
+    "ShowDetails",
+    "AppDetails",
+    "Nothing",
+    "SeekForward",
+    "SeekBackward",
+    "Seek",
+    "PushSearchQuery",
+    "PopSearchQuery",
+    "MoveSearchCursor",
+    "DeleteSearchQuery",
+    "MoveInSearchHistory",
+    "ReverseSearchHistory",
+    "MoveInSuggestions",
+    "GotoPane",
+    "MoveInPaneWindow",
+    "MoveInShortcuts",
+    "MoveInMusicList",
+    "MoveInPlaylistList",
+    "MoveInArtistList",
+    "JumpToLetter",
+    "ClearQueue",
+    "OpenCommandLine",
+    "OpenPalette",
+    "PushCommandLine",
+    "PopCommandLine",
+    "CompleteCommandLine",
+    "MoveInCommandLine",
+    "ExecuteCommandLine",
+    "CloseCommandLine",
+    "MoveInPopup",
+];
+
+impl KeyboardAction {
+    /// Actions that are repeated when a count is typed before them, as in `5j`
+    pub fn is_movement(&self) -> bool {
+        matches!(
+            self,
+            KeyboardAction::MoveInPaneWindow(_)
+                | KeyboardAction::MoveInShortcuts(_)
+                | KeyboardAction::MoveInMusicList(_)
+                | KeyboardAction::MoveInPlaylistList(_)
+                | KeyboardAction::MoveInArtistList(_)
+                | KeyboardAction::MoveInPopup(_)
+        )
+    }
+
+    /// Whether action of this name cannot be given without an argument
+    pub fn needs_argument(name: &str) -> bool {
+        matches!(
+            name,
+            "SetVolume"
+                | "Seek"
+                | "GotoPane"
+                | "PushSearchQuery"
+                | "MoveSearchCursor"
+                | "DeleteSearchQuery"
+                | "JumpToLetter"
+                | "PushCommandLine"
+                | "MoveInCommandLine"
+                | "MoveInPaneWindow"
+                | "MoveInShortcuts"
+                | "MoveInMusicList"
+                | "MoveInPlaylistList"
+                | "MoveInArtistList"
+                | "MoveInPopup"
+                | "MoveInSearchHistory"
+                | "MoveInSuggestions"
+        )
+    }
+}
+
+impl FromStr for KeyboardAction {
+    type Err = String;
+
+    fn from_str(action: &str) -> Result<Self, Self::Err> {
+        let (name, argument) = match action.trim().split_once('(') {
+            Some((name, rest)) => {
+                let argument = rest
+                    .strip_suffix(')')
+                    .ok_or_else(|| format!("Missing `)` in action: {action}"))?;
+                (name.trim(), Some(argument))
+            }
+            None => (action.trim(), None),
+        };
+        let plain = |plain_action| match argument {
+            None => Ok(plain_action),
+            Some(_) => Err(format!("Action {name} does not take any argument")),
+        };
+        let required = || argument.ok_or_else(|| format!("Action {name} needs an argument"));
+        let direction = || {
+            serde_json::from_value::<MoveDirection>(required()?.into())
+                .map_err(|_| format!("Invalid direction in action: {action}"))
+        };
+        let motion = || {
+            serde_json::from_value::<Motion>(required()?.into())
+                .map_err(|_| format!("Invalid motion in action: {action}"))
+        };
+
+        match name {
+            "StartSearching" => plain(KeyboardAction::StartSearching),
+            "PausePlay" => plain(KeyboardAction::PausePlay),
+            "GotoNextWindow" => plain(KeyboardAction::GotoNextWindow),
+            "GotoPrviousWindow" => plain(KeyboardAction::GotoPrviousWindow),
+            "Execute" => plain(KeyboardAction::Execute),
+            "AddToQueue" => plain(KeyboardAction::AddToQueue),
+            "ShuffleToggle" => plain(KeyboardAction::ShuffleToggle),
+            "RepeatSwitch" => plain(KeyboardAction::RepeatSwitch),
+            "Expand" => plain(KeyboardAction::Expand),
+            "Escape" => plain(KeyboardAction::Escape),
+            "Quit" => plain(KeyboardAction::Quit),
+            "ForceQuit" => plain(KeyboardAction::ForceQuit),
+            "NextTrack" => plain(KeyboardAction::NextTrack),
+            "PreviousTrack" => plain(KeyboardAction::PreviousTrack),
+            "ClearResult" => plain(KeyboardAction::ClearResult),
+            "ShowDetails" => plain(KeyboardAction::ShowDetails),
+            "AppDetails" => plain(KeyboardAction::AppDetails),
+            "Nothing" => plain(KeyboardAction::Nothing),
+            "PopSearchQuery" => plain(KeyboardAction::PopSearchQuery),
+            "ReverseSearchHistory" => plain(KeyboardAction::ReverseSearchHistory),
+            "ClearQueue" => plain(KeyboardAction::ClearQueue),
+            "OpenCommandLine" => plain(KeyboardAction::OpenCommandLine),
+            "OpenPalette" => plain(KeyboardAction::OpenPalette),
+            "PopCommandLine" => plain(KeyboardAction::PopCommandLine),
+            "CompleteCommandLine" => plain(KeyboardAction::CompleteCommandLine),
+            "ExecuteCommandLine" => plain(KeyboardAction::ExecuteCommandLine),
+            "CloseCommandLine" => plain(KeyboardAction::CloseCommandLine),
+            "VolumeUp" => Ok(KeyboardAction::VolumeUp(
+                argument
+                    .map(parse_percent)
+                    .transpose()?
+                    .unwrap_or(DEFAULT_VOLUME_STEP),
+            )),
+            "VolumeDown" => Ok(KeyboardAction::VolumeDown(
+                argument
+                    .map(parse_percent)
+                    .transpose()?
+                    .unwrap_or(DEFAULT_VOLUME_STEP),
+            )),
+            "SetVolume" => Ok(KeyboardAction::SetVolume(parse_percent(required()?)?)),
+            "SeekForward" => Ok(KeyboardAction::SeekForward(
+                argument
+                    .map(parse_duration)
+                    .transpose()?
+                    .unwrap_or(DEFAULT_SEEK_STEP),
+            )),
+            "SeekBackward" => Ok(KeyboardAction::SeekBackward(
+                argument
+                    .map(parse_duration)
+                    .transpose()?
+                    .unwrap_or(DEFAULT_SEEK_STEP),
+            )),
+            "Seek" => {
+                let position = required()?;
+                let position = match position.strip_suffix('%') {
+                    Some(percent) => TrackPosition::Percent(parse_percent(percent)?),
+                    None => TrackPosition::Time(parse_duration(position)?),
+                };
+                Ok(KeyboardAction::Seek(position))
+            }
+            "GotoPane" => {
+                let pane = match required()? {
+                    "Music" => PaneWindow::MusicPane,
+                    "Playlist" => PaneWindow::PlaylistPane,
+                    "Artist" => PaneWindow::ArtistPane,
+                    pane => return Err(format!("No such pane: {pane}")),
+                };
+                Ok(KeyboardAction::GotoPane(pane))
+            }
+            "PushSearchQuery" => Ok(KeyboardAction::PushSearchQuery(parse_char(required()?)?)),
+            "MoveSearchCursor" => Ok(KeyboardAction::MoveSearchCursor(motion()?)),
+            "DeleteSearchQuery" => Ok(KeyboardAction::DeleteSearchQuery(motion()?)),
+            "JumpToLetter" => Ok(KeyboardAction::JumpToLetter(parse_char(required()?)?)),
+            "PushCommandLine" => Ok(KeyboardAction::PushCommandLine(parse_char(required()?)?)),
+            "MoveInCommandLine" => Ok(KeyboardAction::MoveInCommandLine(direction()?)),
+            "MoveInPaneWindow" => Ok(KeyboardAction::MoveInPaneWindow(direction()?)),
+            "MoveInShortcuts" => Ok(KeyboardAction::MoveInShortcuts(direction()?)),
+            "MoveInMusicList" => Ok(KeyboardAction::MoveInMusicList(direction()?)),
+            "MoveInPlaylistList" => Ok(KeyboardAction::MoveInPlaylistList(direction()?)),
+            "MoveInArtistList" => Ok(KeyboardAction::MoveInArtistList(direction()?)),
+            "MoveInPopup" => Ok(KeyboardAction::MoveInPopup(direction()?)),
+            "MoveInSearchHistory" => Ok(KeyboardAction::MoveInSearchHistory(direction()?)),
+            "MoveInSuggestions" => Ok(KeyboardAction::MoveInSuggestions(direction()?)),
+            unknown => Err(format!("Unknown action: {unknown}")),
+        }
+    }
+}
+
+impl std::fmt::Display for KeyboardAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyboardAction::VolumeUp(step) => write!(f, "VolumeUp({step})"),
+            KeyboardAction::VolumeDown(step) => write!(f, "VolumeDown({step})"),
+            KeyboardAction::SetVolume(volume) => write!(f, "SetVolume({volume})"),
+            KeyboardAction::SeekForward(by) => write!(f, "SeekForward({})", format_duration(*by)),
+            KeyboardAction::SeekBackward(by) => {
+                write!(f, "SeekBackward({})", format_duration(*by))
+            }
+            KeyboardAction::Seek(TrackPosition::Time(to)) => {
+                write!(f, "Seek({})", format_duration(*to))
+            }
+            KeyboardAction::Seek(TrackPosition::Percent(to)) => write!(f, "Seek({to}%)"),
+            KeyboardAction::GotoPane(pane) => {
+                let pane = match pane {
+                    PaneWindow::MusicPane => "Music",
+                    PaneWindow::PlaylistPane => "Playlist",
+                    PaneWindow::ArtistPane => "Artist",
+                };
+                write!(f, "GotoPane({pane})")
+            }
+            KeyboardAction::PushSearchQuery(ch) => write!(f, "PushSearchQuery({ch})"),
+            KeyboardAction::MoveSearchCursor(motion) => write!(f, "MoveSearchCursor({motion:?})"),
+            KeyboardAction::DeleteSearchQuery(motion) => {
+                write!(f, "DeleteSearchQuery({motion:?})")
+            }
+            KeyboardAction::JumpToLetter(ch) => write!(f, "JumpToLetter({ch})"),
+            KeyboardAction::PushCommandLine(ch) => write!(f, "PushCommandLine({ch})"),
+            KeyboardAction::MoveInCommandLine(direction) => {
+                write!(f, "MoveInCommandLine({direction:?})")
+            }
+            KeyboardAction::MoveInPaneWindow(direction) => {
+                write!(f, "MoveInPaneWindow({direction:?})")
+            }
+            KeyboardAction::MoveInShortcuts(direction) => {
+                write!(f, "MoveInShortcuts({direction:?})")
+            }
+            KeyboardAction::MoveInMusicList(direction) => {
+                write!(f, "MoveInMusicList({direction:?})")
+            }
+            KeyboardAction::MoveInPlaylistList(direction) => {
+                write!(f, "MoveInPlaylistList({direction:?})")
+            }
+            KeyboardAction::MoveInArtistList(direction) => {
+                write!(f, "MoveInArtistList({direction:?})")
+            }
+            KeyboardAction::MoveInPopup(direction) => write!(f, "MoveInPopup({direction:?})"),
+            KeyboardAction::MoveInSearchHistory(direction) => {
+                write!(f, "MoveInSearchHistory({direction:?})")
+            }
+            KeyboardAction::MoveInSuggestions(direction) => {
+                write!(f, "MoveInSuggestions({direction:?})")
+            }
+            // Rest of them have no argument so are written just as their name
+            plain => write!(f, "{plain:?}"),
+        }
+    }
+}
+
+impl From<KeyboardAction> for String {
+    fn from(action: KeyboardAction) -> Self {
+        action.to_string()
+    }
+}
+
+impl TryFrom<serde_json::Value> for KeyboardAction {
+    type Error = String;
+
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+        use serde_json::Value;
+        match value {
+            Value::String(action) => action.parse(),
+            // Also allow older `{ "Name": argument }` form
+            Value::Object(map) if map.len() == 1 => {
+                let (name, argument) = map.into_iter().next().expect("Checked length above");
+                match argument {
+                    Value::String(argument) => format!("{name}({argument})").parse(),
+                    Value::Number(argument) => format!("{name}({argument})").parse(),
+                    argument => Err(format!("Invalid argument for {name}: {argument}")),
+                }
+            }
+            value => Err(format!("Invalid action: {value}")),
+        }
+    }
+}
+
+fn parse_percent(percent: &str) -> Result<u8, String> {
+    let percent = percent.trim();
+    match percent.strip_suffix('%').unwrap_or(percent).parse::<u8>() {
+        Ok(percent) if percent <= 100 => Ok(percent),
+        _ => Err(format!(
+            "Expected percent from 0 to 100 but found {percent}"
+        )),
+    }
+}
+
+fn parse_char(character: &str) -> Result<char, String> {
+    let mut chars = character.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => Ok(ch),
+        _ => Err(format!("Expected single character but found {character}")),
+    }
+}
+
+/// Parse duration written as `10s`, `1m30s`, `1h` or `500ms`
+/// Plain number is taken as seconds
+fn parse_duration(duration: &str) -> Result<Duration, String> {
+    let duration = duration.trim();
+    if let Ok(secs) = duration.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let invalid = || format!("Invalid duration: {duration}. Expected something like 1m30s");
+    if duration.is_empty() {
+        return Err(invalid());
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = duration;
+    while !rest.is_empty() {
+        let digits_end = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(invalid)?;
+        let unit_end = rest[digits_end..]
+            .find(|c: char| c.is_ascii_digit())
+            .map_or(rest.len(), |end| digits_end + end);
+        let amount = rest[..digits_end].parse::<u64>().map_err(|_| invalid())?;
+        let part = match &rest[digits_end..unit_end] {
+            "h" => amount.checked_mul(60 * 60).map(Duration::from_secs),
+            "m" => amount.checked_mul(60).map(Duration::from_secs),
+            "s" => Some(Duration::from_secs(amount)),
+            "ms" => Some(Duration::from_millis(amount)),
+            _ => None,
+        };
+        total = part
+            .and_then(|part| total.checked_add(part))
+            .ok_or_else(invalid)?;
+        rest = &rest[unit_end..];
+    }
+    Ok(total)
+}
+
+/// Inverse of parse_duration
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let parts = [
+        (secs / 3600, "h"),
+        (secs / 60 % 60, "m"),
+        (secs % 60, "s"),
+        (duration.subsec_millis() as u64, "ms"),
+    ];
+    let formatted = parts
+        .into_iter()
+        .filter(|(amount, _)| *amount > 0)
+        .map(|(amount, unit)| format!("{amount}{unit}"))
+        .collect::<String>();
+    if formatted.is_empty() {
+        "0s".to_string()
+    } else {
+        formatted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keyboard::KeyCode;
+
+    #[test]
+    fn sequence_continuations() {
+        let mapping: KeyboardMapping = [
+            (
+                "gg|PaneWindow",
+                KeyboardAction::MoveInPaneWindow(MoveDirection::First),
+            ),
+            ("gt", KeyboardAction::GotoNextWindow),
+            ("gt|PaneWindow", KeyboardAction::Execute),
+            ("gs|Shortcut", KeyboardAction::Quit),
+            ("g", KeyboardAction::Nothing),
+        ]
+        .into_iter()
+        .map(|(index, action)| (index.to_string().try_into().unwrap(), action))
+        .collect::<HashMap<MappingIndex, _>>()
+        .into();
+
+        let g = Key::from(KeyCode::Char('g'));
+        let t = Key::from(KeyCode::Char('t'));
+        assert_eq!(
+            Some(KeyboardAction::Nothing),
+            mapping.resolve(&[g], Window::PaneWindow, Mode::Normal)
+        );
+        assert_eq!(
+            Some(KeyboardAction::GotoNextWindow),
+            mapping.resolve(&[g, t], Window::Shortcut, Mode::Normal)
+        );
+        assert_eq!(
+            vec![
+                (
+                    vec![g],
+                    KeyboardAction::MoveInPaneWindow(MoveDirection::First)
+                ),
+                (vec![t], KeyboardAction::Execute),
+            ],
+            mapping.continuations(&[g], Window::PaneWindow, Mode::Normal)
+        );
+        assert!(mapping
+            .continuations(&[g, g], Window::PaneWindow, Mode::Normal)
+            .is_empty());
+    }
+
+    #[test]
+    fn modes_are_separate() {
+        let indexes = [
+            "<esc>|insert",
+            "<esc>|SearchBar|insert",
+            "<esc>|SearchBar",
+            "<esc>",
+            ":|command",
+        ];
+        for index in indexes {
+            let parsed = MappingIndex::try_from(index.to_string()).unwrap();
+            assert_eq!(index, String::from(parsed));
+        }
+        assert!(MappingIndex::try_from("x|Elsewhere".to_string()).is_err());
+        assert!(MappingIndex::try_from("x|Elsewhere|insert".to_string()).is_err());
+
+        let mapping: KeyboardMapping = [
+            ("<esc>", KeyboardAction::Escape),
+            ("<esc>|insert", KeyboardAction::GotoNextWindow),
+            ("<esc>|SearchBar|insert", KeyboardAction::Nothing),
+        ]
+        .into_iter()
+        .map(|(index, action)| (index.to_string().try_into().unwrap(), action))
+        .collect::<HashMap<MappingIndex, _>>()
+        .into();
+
+        let esc = [Key::from(KeyCode::Esc)];
+        assert_eq!(
+            Some(KeyboardAction::Escape),
+            mapping.resolve(&esc, Window::SearchBar, Mode::Normal)
+        );
+        assert_eq!(
+            Some(KeyboardAction::Nothing),
+            mapping.resolve(&esc, Window::SearchBar, Mode::Insert)
+        );
+        assert_eq!(
+            Some(KeyboardAction::GotoNextWindow),
+            mapping.resolve(&esc, Window::Popup, Mode::Insert)
+        );
+        assert_eq!(None, mapping.resolve(&esc, Window::Popup, Mode::Command));
+    }
+
+    #[test]
+    fn action_syntax() {
+        let actions = [
+            ("Quit", KeyboardAction::Quit),
+            (
+                "SeekForward(10s)",
+                KeyboardAction::SeekForward(Duration::from_secs(10)),
+            ),
+            (
+                "SeekBackward(1m30s)",
+                KeyboardAction::SeekBackward(Duration::from_secs(90)),
+            ),
+            ("SetVolume(50)", KeyboardAction::SetVolume(50)),
+            ("VolumeUp(10)", KeyboardAction::VolumeUp(10)),
+            (
+                "Seek(25%)",
+                KeyboardAction::Seek(TrackPosition::Percent(25)),
+            ),
+            (
+                "Seek(1h500ms)",
+                KeyboardAction::Seek(TrackPosition::Time(Duration::from_millis(3_600_500))),
+            ),
+            (
+                "GotoPane(Playlist)",
+                KeyboardAction::GotoPane(PaneWindow::PlaylistPane),
+            ),
+            ("JumpToLetter())", KeyboardAction::JumpToLetter(')')),
+            (
+                "MoveInPaneWindow(PageDown)",
+                KeyboardAction::MoveInPaneWindow(MoveDirection::PageDown),
+            ),
+            (
+                "DeleteSearchQuery(WordLeft)",
+                KeyboardAction::DeleteSearchQuery(Motion::WordLeft),
+            ),
+        ];
+        for (action_str, action) in actions {
+            assert_eq!(Ok(action), action_str.parse());
+            assert_eq!(action_str, action.to_string());
+            let json = serde_json::to_value(action).unwrap();
+            assert_eq!(action, serde_json::from_value(json).unwrap());
+        }
+
+        // amounts default when not given
+        assert_eq!(
+            Ok(KeyboardAction::VolumeDown(DEFAULT_VOLUME_STEP)),
+            "VolumeDown".parse()
+        );
+        assert_eq!(
+            Ok(KeyboardAction::SeekForward(DEFAULT_SEEK_STEP)),
+            "SeekForward".parse()
+        );
+        // older object form still works
+        assert_eq!(
+            KeyboardAction::MoveInShortcuts(MoveDirection::Up),
+            serde_json::from_str(r#"{ "MoveInShortcuts": "Up" }"#).unwrap()
+        );
+
+        for invalid in [
+            "Quit(now)",
+            "SetVolume",
+            "SetVolume(101)",
+            "Seek(10x)",
+            "SeekForward(1m30",
+            "GotoPane(Nowhere)",
+            "JumpToLetter(ab)",
+            "Fly",
+        ] {
+            assert!(invalid.parse::<KeyboardAction>().is_err(), "{invalid}");
+        }
+
+        for overflowing in [
+            "SeekForward(9999999999999999999h)",
+            "SeekForward(9999999999999999999m)",
+            "SeekForward(18446744073709551615s1s)",
+        ] {
+            let error = overflowing.parse::<KeyboardAction>().unwrap_err();
+            assert!(
+                error.starts_with("Invalid duration"),
+                "{overflowing}: {error}"
+            );
+        }
+    }
+
+    #[test]
+    fn every_action_name_is_known() {
+        for name in ACTION_NAMES {
+            match name.parse::<KeyboardAction>() {
+                Ok(action) => {
+                    assert!(action.to_string().starts_with(name));
+                    assert!(!KeyboardAction::needs_argument(name), "{name}");
+                }
+                Err(e) => assert!(KeyboardAction::needs_argument(name), "{name}: {e}"),
+            }
+        }
+    }
 }
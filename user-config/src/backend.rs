@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+/// Which kind of api the instance speaks
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ApiFlavor {
+    /// https://docs.invidious.io/api/
+    Invidious,
+    /// https://docs.piped.video/docs/api-documentation/
+    Piped,
+}
+
+/// Where to search and fetch music from
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+pub struct Backend {
+    pub flavor: ApiFlavor,
+    /// Base url of the instance. Eg: https://yewtu.be
+    pub instance: String,
+    /// Ask instance for suggestions while typing in searchbar
+    #[serde(default)]
+    pub suggestions: bool,
+}
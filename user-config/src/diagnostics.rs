@@ -0,0 +1,246 @@
+use crate::action::{KeyboardAction, KeyboardMapping, MappingIndex};
+use crate::mode::Mode;
+use layout_config::window::Window;
+
+/// Something in keymap that is unlikely to do what was meant
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeymapWarning {
+    /// Binding for a window hides global binding of same keys in that window
+    Shadowed {
+        binding: MappingIndex,
+        action: KeyboardAction,
+        hidden: MappingIndex,
+        hidden_action: KeyboardAction,
+    },
+    /// Binding never takes effect
+    Unreachable {
+        binding: MappingIndex,
+        action: KeyboardAction,
+        reason: UnreachableReason,
+    },
+    /// Binding is start of a longer one, so it only acts
+    /// once waiting for rest of the longer one times out
+    Delayed {
+        binding: MappingIndex,
+        action: KeyboardAction,
+        longer: MappingIndex,
+    },
+}
+
+/// Why a binding never takes effect
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnreachableReason {
+    /// Window can not be focused at all
+    NeverFocused(Window),
+    /// Window takes input in other mode than the binding is for
+    WrongMode { window: Window, takes: Mode },
+    /// Every window that takes input in this mode
+    /// have binding of it's own for same keys
+    ShadowedEverywhere,
+}
+
+impl std::fmt::Display for KeymapWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeymapWarning::Shadowed {
+                binding,
+                action,
+                hidden,
+                hidden_action,
+            } => write!(
+                f,
+                "`{binding}` ({action}) hides `{hidden}` ({hidden_action}) in {}",
+                binding.window().unwrap_or(Window::None)
+            ),
+            KeymapWarning::Unreachable {
+                binding,
+                action,
+                reason,
+            } => {
+                write!(f, "`{binding}` ({action}) never takes effect: ")?;
+                match reason {
+                    UnreachableReason::NeverFocused(window) => {
+                        write!(f, "{window} is never focused")
+                    }
+                    UnreachableReason::WrongMode { window, takes } => {
+                        write!(f, "{window} takes input in {takes} mode")
+                    }
+                    UnreachableReason::ShadowedEverywhere => {
+                        write!(f, "every window has it's own binding for these keys")
+                    }
+                }
+            }
+            KeymapWarning::Delayed {
+                binding,
+                action,
+                longer,
+            } => write!(
+                f,
+                "`{binding}` ({action}) only acts after a pause, as `{longer}` starts with same keys"
+            ),
+        }
+    }
+}
+
+/// Windows that can have focus, in the order they are cycled through
+/// followed by popup which takes focus while open
+pub(crate) fn focusable_windows() -> Vec<Window> {
+    std::iter::successors(Some(Window::first()), |window| window.next())
+        .chain([Window::Popup])
+        .collect()
+}
+
+/// Windows that may be focused while in given mode
+fn windows_in(mode: Mode) -> Vec<Window> {
+    focusable_windows()
+        .into_iter()
+        .filter(|window| mode == Mode::Command || Mode::of_window(*window) == mode)
+        .collect()
+}
+
+/// Look for bindings that hide, delay or can never reach one another
+/// Warnings are sorted so that report is same every time
+pub fn check_keymap(keymap: &KeyboardMapping) -> Vec<KeymapWarning> {
+    let focusable = focusable_windows();
+    let mut warnings = Vec::new();
+
+    for (binding, action) in keymap.bindings() {
+        let (keys, mode) = (binding.keys().to_vec(), binding.mode());
+        let windows = windows_in(mode);
+
+        let unreachable = |reason| KeymapWarning::Unreachable {
+            binding: binding.clone(),
+            action,
+            reason,
+        };
+        match binding.window() {
+            Some(window) if !focusable.contains(&window) => {
+                warnings.push(unreachable(UnreachableReason::NeverFocused(window)));
+                continue;
+            }
+            Some(window) if !windows.contains(&window) => {
+                let takes = Mode::of_window(window);
+                warnings.push(unreachable(UnreachableReason::WrongMode { window, takes }));
+                continue;
+            }
+            Some(_) => {
+                let global = MappingIndex::from((keys.clone(), None, mode));
+                if let Some(hidden_action) = keymap.action_for(&global) {
+                    if hidden_action != action {
+                        warnings.push(KeymapWarning::Shadowed {
+                            binding: binding.clone(),
+                            action,
+                            hidden: global,
+                            hidden_action,
+                        });
+                    }
+                }
+            }
+            None => {
+                let shadowed_everywhere = !windows.is_empty()
+                    && windows.iter().all(|window| {
+                        let local = MappingIndex::from((keys.clone(), Some(*window), mode));
+                        keymap.action_for(&local).is_some()
+                    });
+                if shadowed_everywhere {
+                    warnings.push(unreachable(UnreachableReason::ShadowedEverywhere));
+                    continue;
+                }
+            }
+        }
+
+        for (longer, _) in keymap.bindings() {
+            let overlaps = match (binding.window(), longer.window()) {
+                (Some(window), Some(other)) => window == other,
+                _ => true,
+            };
+            if longer.mode() == mode
+                && longer.keys().len() > keys.len()
+                && longer.keys().starts_with(&keys)
+                && overlaps
+            {
+                warnings.push(KeymapWarning::Delayed {
+                    binding: binding.clone(),
+                    action,
+                    longer: longer.clone(),
+                });
+            }
+        }
+    }
+
+    warnings.sort_by_key(|warning| warning.to_string());
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keymap(json: serde_json::Value) -> KeyboardMapping {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn default_keymap_is_clean() {
+        let warnings = check_keymap(&crate::default_config().keyboard);
+        assert!(warnings.is_empty(), "{warnings:#?}");
+    }
+
+    #[test]
+    fn conflicts_are_found() {
+        let keymap = keymap(serde_json::json!({
+            "x": "Quit",
+            "x|PaneWindow": "AddToQueue",
+            "n": "NextTrack",
+            "n|PaneWindow": "NextTrack",
+            "g": "PausePlay",
+            "gg|PaneWindow": "MoveInPaneWindow(First)",
+            "y|SearchBar": "Quit",
+            "y|None": "Quit",
+            "<esc>|insert": "GotoNextWindow",
+            "<esc>|SearchBar|insert": "Nothing",
+        }));
+        let index = |binding: &str| MappingIndex::try_from(binding.to_string()).unwrap();
+
+        assert_eq!(
+            vec![
+                KeymapWarning::Shadowed {
+                    binding: index("<esc>|SearchBar|insert"),
+                    action: KeyboardAction::Nothing,
+                    hidden: index("<esc>|insert"),
+                    hidden_action: KeyboardAction::GotoNextWindow,
+                },
+                KeymapWarning::Unreachable {
+                    binding: index("<esc>|insert"),
+                    action: KeyboardAction::GotoNextWindow,
+                    reason: UnreachableReason::ShadowedEverywhere,
+                },
+                KeymapWarning::Delayed {
+                    binding: index("g"),
+                    action: KeyboardAction::PausePlay,
+                    longer: index("gg|PaneWindow"),
+                },
+                KeymapWarning::Shadowed {
+                    binding: index("x|PaneWindow"),
+                    action: KeyboardAction::AddToQueue,
+                    hidden: index("x"),
+                    hidden_action: KeyboardAction::Quit,
+                },
+                KeymapWarning::Unreachable {
+                    binding: index("y|None"),
+                    action: KeyboardAction::Quit,
+                    reason: UnreachableReason::NeverFocused(Window::None),
+                },
+                KeymapWarning::Unreachable {
+                    binding: index("y|SearchBar"),
+                    action: KeyboardAction::Quit,
+                    reason: UnreachableReason::WrongMode {
+                        window: Window::SearchBar,
+                        takes: Mode::Insert,
+                    },
+                },
+            ],
+            check_keymap(&keymap)
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use crate::action::{KeyboardAction, KeyboardMapping};
+use crate::diagnostics::focusable_windows;
+use crate::keyboard::Key;
+use crate::mode::Mode;
+use layout_config::window::Window;
+
+/// Bindings that apply in same window and mode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpSection {
+    /// Where bindings apply, like `Global` or `PaneWindow, insert mode`
+    pub title: String,
+    /// (keys as written in config, what they do) sorted by keys
+    pub bindings: Vec<(String, KeyboardAction)>,
+}
+
+/// Bindings of keymap grouped by window they apply in
+///
+/// Global bindings come first, then ones of each window in order they are
+/// cycled through. Only bindings whose keys, action or section title
+/// contain `filter` are kept, ignoring case
+pub fn help_sections(keymap: &KeyboardMapping, filter: &str) -> Vec<HelpSection> {
+    let windows = focusable_windows();
+    let window_rank = |window: Option<Window>| match window {
+        None => 0,
+        Some(window) => windows
+            .iter()
+            .position(|w| *w == window)
+            .map_or(windows.len() + 1, |position| position + 1),
+    };
+    let mode_rank = |mode: Mode| match mode {
+        Mode::Normal => 0,
+        Mode::Insert => 1,
+        Mode::Command => 2,
+    };
+
+    let mut scopes = keymap
+        .bindings()
+        .map(|(index, _)| (index.window(), index.mode()))
+        .collect::<Vec<_>>();
+    scopes.sort_by_key(|(window, mode)| (mode_rank(*mode), window_rank(*window)));
+    scopes.dedup();
+
+    let filter = filter.to_lowercase();
+    scopes
+        .into_iter()
+        .filter_map(|(window, mode)| {
+            let title = section_title(window, mode);
+            let title_matches = title.to_lowercase().contains(&filter);
+            let mut bindings = keymap
+                .bindings()
+                .filter(|(index, _)| index.window() == window && index.mode() == mode)
+                .map(|(index, action)| (Key::sequence_to_string(index.keys()), action))
+                .filter(|(keys, action)| {
+                    title_matches
+                        || keys.to_lowercase().contains(&filter)
+                        || action.to_string().to_lowercase().contains(&filter)
+                })
+                .collect::<Vec<_>>();
+            if bindings.is_empty() {
+                return None;
+            }
+            bindings.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Some(HelpSection { title, bindings })
+        })
+        .collect()
+}
+
+fn section_title(window: Option<Window>, mode: Mode) -> String {
+    let window = match window {
+        None => "Global".to_string(),
+        Some(window) => window.to_string(),
+    };
+    match mode {
+        Mode::Normal => window,
+        mode => format!("{window}, {mode} mode"),
+    }
+}
+
+/// Every binding of keymap as markdown, a table for each section
+pub fn cheatsheet(keymap: &KeyboardMapping) -> String {
+    let mut sheet = String::from("# Keybindings\n");
+    for section in help_sections(keymap, "") {
+        sheet += &format!(
+            "\n## {}\n\n| Keys | Action |\n| --- | --- |\n",
+            section.title
+        );
+        for (keys, action) in section.bindings {
+            // `|` would end the table cell
+            let keys = keys.replace('|', "\\|");
+            sheet += &format!("| `{keys}` | {action} |\n");
+        }
+    }
+    sheet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::MoveDirection;
+
+    #[test]
+    fn sections_follow_windows() {
+        let keymap = crate::default_config().keyboard;
+        let sections = help_sections(&keymap, "");
+        let titles = sections
+            .iter()
+            .map(|section| section.title.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "Global",
+                "Shortcut",
+                "PaneTab",
+                "PaneWindow",
+                "Gauge",
+                "Popup",
+                "Global, insert mode",
+                "Global, command mode"
+            ],
+            titles
+        );
+        assert!(sections[0]
+            .bindings
+            .contains(&("<ctrl>c".to_string(), KeyboardAction::ForceQuit)));
+
+        let filtered = help_sections(&keymap, "PANEWINDOW(first");
+        assert_eq!(1, filtered.len());
+        assert_eq!(
+            vec![
+                (
+                    "<home>".to_string(),
+                    KeyboardAction::MoveInPaneWindow(MoveDirection::First)
+                ),
+                (
+                    "gg".to_string(),
+                    KeyboardAction::MoveInPaneWindow(MoveDirection::First)
+                ),
+            ],
+            filtered[0].bindings
+        );
+        // title matching keeps whole section
+        assert_eq!(
+            sections[4].bindings,
+            help_sections(&keymap, "gauge")[0].bindings
+        );
+    }
+
+    #[test]
+    fn cheatsheet_is_markdown() {
+        let keymap = serde_json::from_value(serde_json::json!({
+            "q": "Quit",
+            "<tab>|SearchBar|insert": "GotoNextWindow",
+        }))
+        .unwrap();
+        assert_eq!(
+            "# Keybindings\n\
+            \n## Global\n\n| Keys | Action |\n| --- | --- |\n| `q` | Quit |\n\
+            \n## SearchBar, insert mode\n\n| Keys | Action |\n| --- | --- |\n| `<tab>` | GotoNextWindow |\n",
+            cheatsheet(&keymap)
+        );
+    }
+}
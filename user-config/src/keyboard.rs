@@ -1,25 +1,44 @@
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 
-fn combine_modifiers(modifier: &'static str, character: char) -> String {
-    let mut res = String::with_capacity(modifier.len() + 1);
-    res.push_str(modifier);
-    if character == ' ' {
-        res.push_str("<space>");
-    } else {
-        res.push(character)
-    }
-    res
+/// Modifier keys held down along with a key
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
 }
 
-fn get_prefix_char(prefix_str: &str) -> char {
-    if prefix_str == "<space>" {
-        ' '
-    } else {
-        prefix_str
-            .chars()
-            .next()
-            .expect("Cannot convert empty prefix to char")
+impl Modifiers {
+    pub const NONE: Modifiers = Modifiers {
+        ctrl: false,
+        alt: false,
+        shift: false,
+    };
+    pub const CTRL: Modifiers = Modifiers {
+        ctrl: true,
+        ..Modifiers::NONE
+    };
+    pub const ALT: Modifiers = Modifiers {
+        alt: true,
+        ..Modifiers::NONE
+    };
+    pub const SHIFT: Modifiers = Modifiers {
+        shift: true,
+        ..Modifiers::NONE
+    };
+
+    pub fn is_empty(&self) -> bool {
+        *self == Modifiers::NONE
+    }
+
+    /// Both sets of modifiers together
+    pub fn with(self, other: Modifiers) -> Modifiers {
+        Modifiers {
+            ctrl: self.ctrl || other.ctrl,
+            alt: self.alt || other.alt,
+            shift: self.shift || other.shift,
+        }
     }
 }
 
@@ -27,11 +46,9 @@ fn get_prefix_char(prefix_str: &str) -> char {
 #[serde(transparent)]
 struct KeyboardShortcut<'a>(Cow<'a, str>);
 
-/// A key.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
-#[serde(into = "KeyboardShortcut")]
-#[serde(try_from = "KeyboardShortcut")]
-pub enum Key {
+/// A key, without any modifiers
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum KeyCode {
     /// Backspace.
     Backspace,
     /// Left arrow.
@@ -58,96 +75,202 @@ pub enum Key {
     Insert,
     /// TAB key
     Tab,
+    /// Enter/ Return key
+    Enter,
     /// Function keys.
-    ///
-    /// Only function keys 1 through 12 are supported.
     F(u8),
     /// Normal character.
     Char(char),
-    /// Alt modified character.
-    Alt(char),
-    /// Ctrl modified character.
-    ///
-    /// Note that certain keys may not be modifiable with `ctrl`, due to limitations of terminals.
-    Ctrl(char),
     /// Null byte.
     Null,
     /// Esc key.
     Esc,
 }
 
+/// A key along with modifiers held while pressing it
+///
+/// Note that certain combinations may never be reported, due to limitations of terminals.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[serde(into = "KeyboardShortcut")]
+#[serde(try_from = "KeyboardShortcut")]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: Modifiers,
+}
+
+impl From<KeyCode> for Key {
+    fn from(code: KeyCode) -> Self {
+        Key {
+            code,
+            modifiers: Modifiers::NONE,
+        }
+    }
+}
+
+impl From<KeyCode> for Cow<'static, str> {
+    fn from(code: KeyCode) -> Self {
+        match code {
+            KeyCode::Char(' ') => Cow::Borrowed("<space>"),
+            // `<` would be read as start of a key name
+            KeyCode::Char('<') => Cow::Borrowed("<lt>"),
+            KeyCode::Char('>') => Cow::Borrowed("<gt>"),
+            KeyCode::Char(c) => Cow::Owned(c.to_string()),
+            KeyCode::Esc => Cow::Borrowed("<esc>"),
+            KeyCode::PageUp => Cow::Borrowed("<pageUp>"),
+            KeyCode::PageDown => Cow::Borrowed("<pageDown>"),
+            KeyCode::Insert => Cow::Borrowed("<insert>"),
+            KeyCode::Backspace => Cow::Borrowed("<backspace>"),
+            KeyCode::Left => Cow::Borrowed("<left>"),
+            KeyCode::Right => Cow::Borrowed("<right>"),
+            KeyCode::Up => Cow::Borrowed("<up>"),
+            KeyCode::Down => Cow::Borrowed("<down>"),
+            KeyCode::Home => Cow::Borrowed("<home>"),
+            KeyCode::End => Cow::Borrowed("<end>"),
+            KeyCode::Tab => Cow::Borrowed("<tab>"),
+            KeyCode::Enter => Cow::Borrowed("<enter>"),
+            KeyCode::Null => Cow::Borrowed("<null>"),
+            KeyCode::BackTab => Cow::Borrowed("<backtab>"),
+            KeyCode::Delete => Cow::Borrowed("<delete>"),
+            KeyCode::F(f) => Cow::Owned(format!("<F{f}>")),
+        }
+    }
+}
+
 impl From<Key> for Cow<'static, str> {
-    fn from(key: Key) -> Self {
-        match key {
-            Key::Char(' ') => Cow::Borrowed("<space>"),
-            Key::Char(c) => Cow::Owned(c.to_string()),
-            Key::Ctrl(c) => Cow::Owned(combine_modifiers("<ctrl>", c)),
-            Key::Alt(c) => Cow::Owned(combine_modifiers("<alt>", c)),
-            Key::Esc => Cow::Borrowed("<esc>"),
-            Key::PageUp => Cow::Borrowed("<pageUp>"),
-            Key::PageDown => Cow::Borrowed("<pageDown>"),
-            Key::Insert => Cow::Borrowed("<insert>"),
-            Key::Backspace => Cow::Borrowed("<backspace>"),
-            Key::Left => Cow::Borrowed("<left>"),
-            Key::Right => Cow::Borrowed("<right>"),
-            Key::Up => Cow::Borrowed("<up>"),
-            Key::Down => Cow::Borrowed("<down>"),
-            Key::Home => Cow::Borrowed("<home>"),
-            Key::End => Cow::Borrowed("<end>"),
-            Key::Tab => Cow::Borrowed("<tab>"),
-            Key::Null => Cow::Borrowed("<null>"),
-            Key::BackTab => Cow::Borrowed("<backtab>"),
-            Key::Delete => Cow::Borrowed("<delete>"),
-            Key::F(f) => Cow::Owned(format!("<F{f}>")),
+    fn from(Key { code, modifiers }: Key) -> Self {
+        if modifiers.is_empty() {
+            return code.into();
+        }
+
+        let mut res = String::new();
+        // Always written in this order so that same key is always same string
+        for (held, name) in [
+            (modifiers.ctrl, "<ctrl>"),
+            (modifiers.alt, "<alt>"),
+            (modifiers.shift, "<shift>"),
+        ] {
+            if held {
+                res.push_str(name);
+            }
         }
+        res.push_str(&Cow::<'static, str>::from(code));
+        Cow::Owned(res)
     }
 }
 
-impl TryFrom<Cow<'_, str>> for Key {
-    type Error = &'static str;
+impl Key {
+    /// Key with these modifiers
+    /// Shift on a character is folded into the character itself
+    /// so that `<shift>g` and `G` are the same key
+    pub fn new(code: KeyCode, modifiers: Modifiers) -> Self {
+        match code {
+            KeyCode::Char(c) if modifiers.shift => Key {
+                code: KeyCode::Char(c.to_uppercase().next().unwrap_or(c)),
+                modifiers: Modifiers {
+                    shift: false,
+                    ..modifiers
+                },
+            },
+            // Shift+Tab is always reported as backtab
+            KeyCode::BackTab => Key {
+                code,
+                modifiers: Modifiers {
+                    shift: false,
+                    ..modifiers
+                },
+            },
+            _ => Key { code, modifiers },
+        }
+    }
 
-    fn try_from(value: Cow<'_, str>) -> Result<Self, Self::Error> {
-        let key = value.trim();
+    pub fn ctrl(character: char) -> Self {
+        Key::new(KeyCode::Char(character), Modifiers::CTRL)
+    }
 
-        if let Some(stripped) = key.strip_prefix('<') {
-            let (modifier, prefix) = stripped
+    /// Read keys written one after another, like `gg` or `<space>fs`
+    pub fn parse_sequence(value: &str) -> Result<Vec<Key>, &'static str> {
+        let mut rest = value.trim();
+        let mut keys = Vec::new();
+        while !rest.is_empty() {
+            let (key, remaining) = Key::parse_first(rest)?;
+            keys.push(key);
+            rest = remaining;
+        }
+        if keys.is_empty() {
+            return Err("Empty shortcut");
+        }
+        Ok(keys)
+    }
+
+    /// Inverse of parse_sequence
+    pub fn sequence_to_string(keys: &[Key]) -> String {
+        keys.iter()
+            .map(|key| Cow::<'static, str>::from(*key))
+            .collect()
+    }
+
+    /// First key written in `value` and whatever is after it
+    /// Any number of modifiers in any order may come before the key itself
+    fn parse_first(value: &str) -> Result<(Key, &str), &'static str> {
+        let mut modifiers = Modifiers::NONE;
+        let mut rest = value;
+        loop {
+            let Some(stripped) = rest.strip_prefix('<') else {
+                let character = rest.chars().next().ok_or("Modifier without a key")?;
+                let key = Key::new(KeyCode::Char(character), modifiers);
+                return Ok((key, &rest[character.len_utf8()..]));
+            };
+
+            let (name, after) = stripped
                 .split_once('>')
                 .ok_or("Invalid shortcut modifier format")?;
-            match modifier {
-                "space" => Ok(Key::Char(' ')),
-                "alt" => Ok(Key::Alt(get_prefix_char(prefix))),
-                "ctrl" => Ok(Key::Ctrl(get_prefix_char(prefix))),
-                "left" => Ok(Key::Left),
-                "right" => Ok(Key::Right),
-                "up" => Ok(Key::Up),
-                "down" => Ok(Key::Down),
-                "esc" => Ok(Key::Esc),
-                "backspace" => Ok(Key::Backspace),
-                "backtab" => Ok(Key::BackTab),
-                "tab" => Ok(Key::Tab),
-                "insert" => Ok(Key::Insert),
-                "null" => Ok(Key::Null),
-                "delete" => Ok(Key::Delete),
-                "home" => Ok(Key::Home),
-                "pageUp" => Ok(Key::PageUp),
-                "pageDown" => Ok(Key::PageDown),
-                "end" => Ok(Key::End),
-                "F1" => Ok(Key::F(1)),
-                "F2" => Ok(Key::F(2)),
-                "F3" => Ok(Key::F(3)),
-                "F4" => Ok(Key::F(4)),
-                "F5" => Ok(Key::F(5)),
-                "F6" => Ok(Key::F(6)),
-                "F7" => Ok(Key::F(7)),
-                "F8" => Ok(Key::F(8)),
-                "F9" => Ok(Key::F(9)),
-                "F10" => Ok(Key::F(10)),
-                "F11" => Ok(Key::F(11)),
-                "F12" => Ok(Key::F(12)),
-                _ => Err("Invalid modifier in shortcut"),
-            }
-        } else {
-            Ok(Key::Char(get_prefix_char(key)))
+            let code = match name {
+                "ctrl" | "alt" | "shift" => {
+                    let modifier = match name {
+                        "ctrl" => Modifiers::CTRL,
+                        "alt" => Modifiers::ALT,
+                        _ => Modifiers::SHIFT,
+                    };
+                    modifiers = modifiers.with(modifier);
+                    rest = after;
+                    continue;
+                }
+                "space" => KeyCode::Char(' '),
+                "lt" => KeyCode::Char('<'),
+                "gt" => KeyCode::Char('>'),
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "esc" => KeyCode::Esc,
+                "backspace" => KeyCode::Backspace,
+                "backtab" => KeyCode::BackTab,
+                "tab" => KeyCode::Tab,
+                "enter" => KeyCode::Enter,
+                "insert" => KeyCode::Insert,
+                "null" => KeyCode::Null,
+                "delete" => KeyCode::Delete,
+                "home" => KeyCode::Home,
+                "pageUp" => KeyCode::PageUp,
+                "pageDown" => KeyCode::PageDown,
+                "end" => KeyCode::End,
+                function => match function.strip_prefix('F').map(str::parse::<u8>) {
+                    Some(Ok(n)) => KeyCode::F(n),
+                    _ => return Err("Invalid modifier in shortcut"),
+                },
+            };
+            return Ok((Key::new(code, modifiers), after));
+        }
+    }
+}
+
+impl TryFrom<Cow<'_, str>> for Key {
+    type Error = &'static str;
+
+    fn try_from(value: Cow<'_, str>) -> Result<Self, Self::Error> {
+        match Key::parse_sequence(&value)?.as_slice() {
+            [key] => Ok(*key),
+            _ => Err("Expected single key but found a sequence"),
         }
     }
 }
@@ -170,29 +293,115 @@ impl TryFrom<KeyboardShortcut<'_>> for Key {
 mod tests {
     use super::*;
 
+    #[test]
+    fn key_sequences() {
+        let g = Key::from(KeyCode::Char('g'));
+        let sequences = [
+            ("gg", vec![g, g]),
+            (
+                "<space>fs",
+                vec![
+                    KeyCode::Char(' ').into(),
+                    KeyCode::Char('f').into(),
+                    KeyCode::Char('s').into(),
+                ],
+            ),
+            (
+                "<ctrl><space>x",
+                vec![Key::ctrl(' '), KeyCode::Char('x').into()],
+            ),
+            (
+                "<alt>d<F5><up>",
+                vec![
+                    Key::new(KeyCode::Char('d'), Modifiers::ALT),
+                    KeyCode::F(5).into(),
+                    KeyCode::Up.into(),
+                ],
+            ),
+            (
+                "<ctrl><alt>x<shift><up>",
+                vec![
+                    Key::new(KeyCode::Char('x'), Modifiers::CTRL.with(Modifiers::ALT)),
+                    Key::new(KeyCode::Up, Modifiers::SHIFT),
+                ],
+            ),
+        ];
+        for (sequence_str, keys) in sequences {
+            assert_eq!(Ok(keys.clone()), Key::parse_sequence(sequence_str));
+            assert_eq!(sequence_str, Key::sequence_to_string(&keys));
+        }
+
+        assert!(Key::parse_sequence("").is_err());
+        assert!(Key::parse_sequence("<F256>").is_err());
+        assert!(Key::parse_sequence("<").is_err());
+        assert!(Key::parse_sequence("a<ctrl>").is_err());
+        assert!(Key::parse_sequence("<ctrl><alt>").is_err());
+        assert!(Key::try_from(Cow::Borrowed("gg")).is_err());
+    }
+
+    #[test]
+    fn modifiers_are_normalized() {
+        // order of modifiers in config does not matter
+        assert_eq!(
+            Key::parse_sequence("<ctrl><alt>x"),
+            Key::parse_sequence("<alt><ctrl>x")
+        );
+        // shift with a character is just the uppercase character
+        assert_eq!(
+            Ok(vec![Key::from(KeyCode::Char('G'))]),
+            Key::parse_sequence("<shift>g")
+        );
+        assert_eq!(
+            Ok(vec![Key::new(KeyCode::Char('X'), Modifiers::CTRL)]),
+            Key::parse_sequence("<shift><ctrl>x")
+        );
+        assert_eq!(
+            Key::from(KeyCode::BackTab),
+            Key::new(KeyCode::BackTab, Modifiers::SHIFT)
+        );
+    }
+
     #[test]
     fn serialization_and_deserilization() {
         let mappings = [
-            (Key::Up, "<up>"),
-            (Key::Down, "<down>"),
-            (Key::Left, "<left>"),
-            (Key::Right, "<right>"),
-            (Key::End, "<end>"),
-            (Key::Home, "<home>"),
-            (Key::PageUp, "<pageUp>"),
-            (Key::PageDown, "<pageDown>"),
-            (Key::Insert, "<insert>"),
-            (Key::Delete, "<delete>"),
-            (Key::BackTab, "<backtab>"),
-            (Key::Backspace, "<backspace>"),
-            (Key::Esc, "<esc>"),
-            (Key::Null, "<null>"),
-            (Key::Char(' '), "<space>"),
-            (Key::Char('x'), "x"),
-            (Key::Alt('3'), "<alt>3"),
-            (Key::Ctrl(' '), "<ctrl><space>"),
-            (Key::F(1), "<F1>"),
-            (Key::F(12), "<F12>"),
+            (KeyCode::Up.into(), "<up>"),
+            (KeyCode::Down.into(), "<down>"),
+            (KeyCode::Left.into(), "<left>"),
+            (KeyCode::Right.into(), "<right>"),
+            (KeyCode::End.into(), "<end>"),
+            (KeyCode::Home.into(), "<home>"),
+            (KeyCode::PageUp.into(), "<pageUp>"),
+            (KeyCode::PageDown.into(), "<pageDown>"),
+            (KeyCode::Insert.into(), "<insert>"),
+            (KeyCode::Delete.into(), "<delete>"),
+            (KeyCode::BackTab.into(), "<backtab>"),
+            (KeyCode::Backspace.into(), "<backspace>"),
+            (KeyCode::Esc.into(), "<esc>"),
+            (KeyCode::Enter.into(), "<enter>"),
+            (KeyCode::Null.into(), "<null>"),
+            (KeyCode::Char(' ').into(), "<space>"),
+            (KeyCode::Char('<').into(), "<lt>"),
+            (KeyCode::Char('>').into(), "<gt>"),
+            (Key::ctrl('<'), "<ctrl><lt>"),
+            (KeyCode::Char('x').into(), "x"),
+            (Key::new(KeyCode::Char('3'), Modifiers::ALT), "<alt>3"),
+            (Key::ctrl(' '), "<ctrl><space>"),
+            (KeyCode::F(1).into(), "<F1>"),
+            (KeyCode::F(12).into(), "<F12>"),
+            (KeyCode::F(24).into(), "<F24>"),
+            (
+                Key::new(KeyCode::Char('x'), Modifiers::CTRL.with(Modifiers::ALT)),
+                "<ctrl><alt>x",
+            ),
+            (Key::new(KeyCode::Up, Modifiers::SHIFT), "<shift><up>"),
+            (Key::new(KeyCode::F(5), Modifiers::CTRL), "<ctrl><F5>"),
+            (
+                Key::new(
+                    KeyCode::Delete,
+                    Modifiers::CTRL.with(Modifiers::ALT).with(Modifiers::SHIFT),
+                ),
+                "<ctrl><alt><shift><delete>",
+            ),
         ];
         for (key, key_str) in mappings.into_iter() {
             let converted_str: Cow<'static, str> = key.into();
@@ -208,6 +417,49 @@ mod tests {
                 key, converted_key,
                 "{key_str} is not same as {converted_key:?}"
             );
+
+            let json = serde_json::to_string(&key).unwrap();
+            assert_eq!(key, serde_json::from_str::<Key>(&json).unwrap());
+        }
+    }
+
+    #[test]
+    fn every_key_is_read_back() {
+        let named = [
+            KeyCode::Backspace,
+            KeyCode::Left,
+            KeyCode::Right,
+            KeyCode::Up,
+            KeyCode::Down,
+            KeyCode::Home,
+            KeyCode::End,
+            KeyCode::PageUp,
+            KeyCode::PageDown,
+            KeyCode::BackTab,
+            KeyCode::Delete,
+            KeyCode::Insert,
+            KeyCode::Tab,
+            KeyCode::Enter,
+            KeyCode::Null,
+            KeyCode::Esc,
+        ];
+        let functions = (0..=u8::MAX).map(KeyCode::F);
+        let characters = " <>|:x9é".chars().map(KeyCode::Char);
+        let modifier_sets = [
+            Modifiers::NONE,
+            Modifiers::CTRL,
+            Modifiers::ALT.with(Modifiers::SHIFT),
+        ];
+        for code in named.into_iter().chain(functions).chain(characters) {
+            for modifiers in modifier_sets {
+                let key = Key::new(code, modifiers);
+                let written = Key::sequence_to_string(&[key, key]);
+                assert_eq!(
+                    Ok(vec![key, key]),
+                    Key::parse_sequence(&written),
+                    "{written}"
+                );
+            }
         }
     }
 }
@@ -1,13 +1,20 @@
 use action::KeyboardMapping;
+use backend::Backend;
 use layout_config::ui::UI;
 use layout_config::{length::Length, window::Window};
 use preferences::theme::Theme;
+use preferences::WidgetPreferences;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 use crate::action::MoveDirection;
 
 pub mod action;
+pub mod backend;
+pub mod diagnostics;
+pub mod help;
 pub mod keyboard;
+pub mod mode;
 pub mod preferences;
 pub mod styles;
 
@@ -25,17 +32,29 @@ pub struct Config {
     pub layout: UI,
     pub theme: Theme,
     pub keyboard: KeyboardMapping,
+    pub backend: Backend,
+    /// Themes that can be switched to with `:theme <name>`
+    #[serde(default)]
+    pub themes: HashMap<String, Theme>,
+    /// Layouts that can be switched to with `:layout <name>`
+    #[serde(default)]
+    pub layouts: HashMap<String, UI>,
+    #[serde(default)]
+    pub preferences: WidgetPreferences,
 }
 
 pub fn default_config() -> Config {
-    use action::KeyboardAction;
-    use keyboard::Key;
+    use action::{KeyboardAction, Motion};
+    use keyboard::{Key, KeyCode, Modifiers};
     use layout_config::direction::Direction;
     use layout_config::identifier::Identifier::{Container, Gadget};
     use layout_config::item::Item;
-    use std::collections::HashMap;
+    use mode::Mode;
     use styles::color::RGB;
 
+    let move_cursor = KeyboardAction::MoveSearchCursor;
+    let delete = KeyboardAction::DeleteSearchQuery;
+
     Config {
         layout: UI {
             window_height: Length::AtLeast(24),
@@ -128,53 +147,253 @@ pub fn default_config() -> Config {
             inactive_color: RGB(69, 85, 68),
         },
         keyboard: [
-            (Key::Tab, None, KeyboardAction::GotoNextWindow),
-            (Key::BackTab, None, KeyboardAction::GotoPrviousWindow),
-            (Key::Char(' '), None, KeyboardAction::PausePlay),
-            (Key::Char('q'), None, KeyboardAction::Quit),
-            (Key::Ctrl('c'), None, KeyboardAction::ForceQuit),
-            (Key::Char('+'), None, KeyboardAction::VolumeUp),
-            (Key::Char('-'), None, KeyboardAction::VolumeDown),
-            (Key::Char('n'), None, KeyboardAction::NextTrack),
-            (Key::Char('p'), None, KeyboardAction::PreviousTrack),
-            (Key::Char('s'), None, KeyboardAction::ShuffleToggle),
-            (Key::Char('r'), None, KeyboardAction::RepeatSwitch),
-            (Key::Esc, None, KeyboardAction::Escape),
-            (
-                Key::Right,
+            (KeyCode::Tab, None, KeyboardAction::GotoNextWindow),
+            (KeyCode::BackTab, None, KeyboardAction::GotoPrviousWindow),
+            (KeyCode::Char(' '), None, KeyboardAction::PausePlay),
+            (KeyCode::Char('q'), None, KeyboardAction::Quit),
+            (
+                KeyCode::Char('+'),
+                None,
+                KeyboardAction::VolumeUp(action::DEFAULT_VOLUME_STEP),
+            ),
+            (
+                KeyCode::Char('-'),
+                None,
+                KeyboardAction::VolumeDown(action::DEFAULT_VOLUME_STEP),
+            ),
+            (KeyCode::Char('n'), None, KeyboardAction::NextTrack),
+            (KeyCode::Char('p'), None, KeyboardAction::PreviousTrack),
+            (KeyCode::Char('s'), None, KeyboardAction::ShuffleToggle),
+            (KeyCode::Char('r'), None, KeyboardAction::RepeatSwitch),
+            (KeyCode::Esc, None, KeyboardAction::Escape),
+            (KeyCode::Enter, None, KeyboardAction::Execute),
+            (KeyCode::Char(':'), None, KeyboardAction::OpenCommandLine),
+            (KeyCode::Char('?'), None, KeyboardAction::AppDetails),
+            (
+                KeyCode::Char('a'),
+                Some(Window::PaneWindow),
+                KeyboardAction::AddToQueue,
+            ),
+            (
+                KeyCode::Char('i'),
+                Some(Window::PaneWindow),
+                KeyboardAction::ShowDetails,
+            ),
+            (
+                KeyCode::Right,
                 Some(Window::PaneWindow),
                 KeyboardAction::MoveInPaneWindow(MoveDirection::Right),
             ),
             (
-                Key::Right,
+                KeyCode::Right,
                 Some(Window::PaneTab),
                 KeyboardAction::MoveInPaneWindow(MoveDirection::Right),
             ),
             (
-                Key::Left,
+                KeyCode::Left,
                 Some(Window::PaneWindow),
                 KeyboardAction::MoveInPaneWindow(MoveDirection::Left),
             ),
             (
-                Key::Left,
+                KeyCode::Left,
                 Some(Window::PaneTab),
                 KeyboardAction::MoveInPaneWindow(MoveDirection::Left),
             ),
             (
-                Key::Down,
+                KeyCode::Up,
+                Some(Window::PaneWindow),
+                KeyboardAction::MoveInPaneWindow(MoveDirection::Up),
+            ),
+            (
+                KeyCode::Down,
+                Some(Window::PaneWindow),
+                KeyboardAction::MoveInPaneWindow(MoveDirection::Down),
+            ),
+            (
+                KeyCode::PageUp,
+                Some(Window::PaneWindow),
+                KeyboardAction::MoveInPaneWindow(MoveDirection::PageUp),
+            ),
+            (
+                KeyCode::PageDown,
+                Some(Window::PaneWindow),
+                KeyboardAction::MoveInPaneWindow(MoveDirection::PageDown),
+            ),
+            (
+                KeyCode::Home,
+                Some(Window::PaneWindow),
+                KeyboardAction::MoveInPaneWindow(MoveDirection::First),
+            ),
+            (
+                KeyCode::End,
+                Some(Window::PaneWindow),
+                KeyboardAction::MoveInPaneWindow(MoveDirection::Last),
+            ),
+            (
+                KeyCode::Char('G'),
+                Some(Window::PaneWindow),
+                KeyboardAction::MoveInPaneWindow(MoveDirection::Last),
+            ),
+            (
+                KeyCode::Right,
+                Some(Window::Gauge),
+                KeyboardAction::SeekForward(action::DEFAULT_SEEK_STEP),
+            ),
+            (
+                KeyCode::Left,
+                Some(Window::Gauge),
+                KeyboardAction::SeekBackward(action::DEFAULT_SEEK_STEP),
+            ),
+            (
+                KeyCode::Down,
                 Some(Window::Shortcut),
                 KeyboardAction::MoveInShortcuts(MoveDirection::Down),
             ),
             (
-                Key::Up,
+                KeyCode::Up,
                 Some(Window::Shortcut),
                 KeyboardAction::MoveInShortcuts(MoveDirection::Up),
             ),
+            (
+                KeyCode::Up,
+                Some(Window::Popup),
+                KeyboardAction::MoveInPopup(MoveDirection::Up),
+            ),
+            (
+                KeyCode::Down,
+                Some(Window::Popup),
+                KeyboardAction::MoveInPopup(MoveDirection::Down),
+            ),
+            (
+                KeyCode::PageUp,
+                Some(Window::Popup),
+                KeyboardAction::MoveInPopup(MoveDirection::PageUp),
+            ),
+            (
+                KeyCode::PageDown,
+                Some(Window::Popup),
+                KeyboardAction::MoveInPopup(MoveDirection::PageDown),
+            ),
+            (
+                KeyCode::Home,
+                Some(Window::Popup),
+                KeyboardAction::MoveInPopup(MoveDirection::First),
+            ),
+            (
+                KeyCode::End,
+                Some(Window::Popup),
+                KeyboardAction::MoveInPopup(MoveDirection::Last),
+            ),
         ]
-        .map(|(k1, k2, v)| ((k1, k2).into(), v))
+        .map(|(k1, k2, v)| ((Key::from(k1), k2).into(), v))
         .into_iter()
+        .chain([
+            ((Key::ctrl('c'), None).into(), KeyboardAction::ForceQuit),
+            ((Key::ctrl('p'), None).into(), KeyboardAction::OpenPalette),
+            (
+                (
+                    vec![KeyCode::Char('g').into(), KeyCode::Char('g').into()],
+                    Some(Window::PaneWindow),
+                )
+                    .into(),
+                KeyboardAction::MoveInPaneWindow(MoveDirection::First),
+            ),
+        ])
+        .chain(
+            [
+                (KeyCode::Esc.into(), KeyboardAction::GotoNextWindow),
+                (KeyCode::Tab.into(), KeyboardAction::GotoNextWindow),
+                (KeyCode::BackTab.into(), KeyboardAction::GotoPrviousWindow),
+                (KeyCode::Backspace.into(), KeyboardAction::PopSearchQuery),
+                (Key::ctrl('c'), KeyboardAction::ForceQuit),
+                (KeyCode::Left.into(), move_cursor(Motion::Left)),
+                (KeyCode::Right.into(), move_cursor(Motion::Right)),
+                (KeyCode::Home.into(), move_cursor(Motion::Start)),
+                (KeyCode::End.into(), move_cursor(Motion::End)),
+                (Key::ctrl('a'), move_cursor(Motion::Start)),
+                (Key::ctrl('e'), move_cursor(Motion::End)),
+                (
+                    Key::new(KeyCode::Left, Modifiers::CTRL),
+                    move_cursor(Motion::WordLeft),
+                ),
+                (
+                    Key::new(KeyCode::Right, Modifiers::CTRL),
+                    move_cursor(Motion::WordRight),
+                ),
+                (
+                    Key::new(KeyCode::Char('b'), Modifiers::ALT),
+                    move_cursor(Motion::WordLeft),
+                ),
+                (
+                    Key::new(KeyCode::Char('f'), Modifiers::ALT),
+                    move_cursor(Motion::WordRight),
+                ),
+                (KeyCode::Delete.into(), delete(Motion::Right)),
+                (Key::ctrl('w'), delete(Motion::WordLeft)),
+                (
+                    Key::new(KeyCode::Char('d'), Modifiers::ALT),
+                    delete(Motion::WordRight),
+                ),
+                (Key::ctrl('u'), delete(Motion::Start)),
+                (Key::ctrl('k'), delete(Motion::End)),
+                (KeyCode::Enter.into(), KeyboardAction::Execute),
+                (
+                    KeyCode::Up.into(),
+                    KeyboardAction::MoveInSuggestions(MoveDirection::Up),
+                ),
+                (
+                    KeyCode::Down.into(),
+                    KeyboardAction::MoveInSuggestions(MoveDirection::Down),
+                ),
+                (
+                    Key::ctrl('p'),
+                    KeyboardAction::MoveInSearchHistory(MoveDirection::Up),
+                ),
+                (
+                    Key::ctrl('n'),
+                    KeyboardAction::MoveInSearchHistory(MoveDirection::Down),
+                ),
+                (Key::ctrl('r'), KeyboardAction::ReverseSearchHistory),
+            ]
+            .map(|(key, action)| ((vec![key], None, Mode::Insert).into(), action)),
+        )
+        .chain(
+            [
+                (KeyCode::Enter.into(), KeyboardAction::ExecuteCommandLine),
+                (KeyCode::Tab.into(), KeyboardAction::CompleteCommandLine),
+                (KeyCode::Backspace.into(), KeyboardAction::PopCommandLine),
+                (KeyCode::Esc.into(), KeyboardAction::CloseCommandLine),
+                (
+                    KeyCode::Up.into(),
+                    KeyboardAction::MoveInCommandLine(MoveDirection::Up),
+                ),
+                (
+                    KeyCode::Down.into(),
+                    KeyboardAction::MoveInCommandLine(MoveDirection::Down),
+                ),
+                (Key::ctrl('c'), KeyboardAction::ForceQuit),
+            ]
+            .map(|(key, action)| ((vec![key], None, Mode::Command).into(), action)),
+        )
         .collect::<HashMap<_, _>>()
         .into(),
+        backend: Backend {
+            flavor: backend::ApiFlavor::Invidious,
+            instance: "https://yewtu.be".to_string(),
+            suggestions: true,
+        },
+        preferences: WidgetPreferences::default(),
+        themes: [(
+            "dark".to_string(),
+            Theme {
+                base_color: RGB(192, 192, 192),
+                highlight_color: RGB(95, 135, 175),
+                active_color: RGB(135, 175, 175),
+                inactive_color: RGB(68, 68, 68),
+            },
+        )]
+        .into(),
+        layouts: HashMap::new(),
     }
 }
 
@@ -199,6 +418,54 @@ mod tests {
         assert_eq!(config_written.keyboard, config_generated.keyboard);
         assert_eq!(config_written.theme, config_generated.theme);
         assert_eq!(config_written.layout, config_generated.layout);
+        assert_eq!(config_written.backend, config_generated.backend);
+        assert_eq!(config_written.themes, config_generated.themes);
+        assert_eq!(config_written.layouts, config_generated.layouts);
+        assert_eq!(config_written.preferences, config_generated.preferences);
         assert_eq!(config_written, config_generated);
     }
+
+    #[test]
+    fn preferences_hide_gadgets_set_to_null() {
+        let preferences: WidgetPreferences =
+            serde_json::from_str(r#"{ "shortcuts": null, "searchbar": { "cursor": "_" } }"#)
+                .unwrap();
+
+        assert_eq!(None, preferences.shortcuts);
+        assert_eq!('_', preferences.searchbar.unwrap().cursor);
+        // left out ones keep their defaults
+        assert_eq!(
+            WidgetPreferences::default().music_pane,
+            preferences.music_pane
+        );
+    }
+
+    #[test]
+    fn shortcuts_are_user_defined() {
+        use preferences::shortcut::{ShortcutAction, ShortcutItem, ShortcutPreferences};
+
+        let shortcuts: ShortcutPreferences = serde_json::from_str(
+            r#"{ "items": [
+                { "label": "Chill", "icon": "~", "action": { "search": "lofi -live" } },
+                { "label": "Disk", "action": { "localDir": "/music" } }
+            ] }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![
+                ShortcutItem {
+                    label: "Chill".to_string(),
+                    icon: Some("~".to_string()),
+                    action: ShortcutAction::Search("lofi -live".to_string()),
+                },
+                ShortcutItem {
+                    label: "Disk".to_string(),
+                    icon: None,
+                    action: ShortcutAction::LocalDir("/music".into()),
+                },
+            ],
+            shortcuts.items
+        );
+    }
 }
@@ -0,0 +1,36 @@
+use layout_config::window::Window;
+use serde::{Deserialize, Serialize};
+
+/// Decides which set of keybindings are in effect
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    /// Keys are commands to the app
+    #[default]
+    Normal,
+    /// Keys are typed into the focused text field unless bound otherwise
+    Insert,
+    /// Keys are typed into the command line unless bound otherwise
+    Command,
+}
+
+impl std::fmt::Display for Mode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mode =
+            serde_json::to_value(self).expect("to_value call to simple enum should not fail");
+        let mode = mode
+            .as_str()
+            .expect("unit variant is always serialized as string");
+        write!(f, "{mode}")
+    }
+}
+
+impl Mode {
+    /// Mode that input is taken in while this window is focused
+    pub fn of_window(window: Window) -> Self {
+        match window {
+            Window::SearchBar => Mode::Insert,
+            _ => Mode::Normal,
+        }
+    }
+}
@@ -6,9 +6,17 @@ pub mod theme;
 use pane::PanePreferences;
 use pane_tab::PaneTabPreferences;
 use searchbar::SearchbarPreferences;
+use serde::{Deserialize, Serialize};
 use shortcut::ShortcutPreferences;
-use theme::Theme;
 
+/// Options of each gadget, under `preferences` in config
+///
+/// Gadgets left out of config get their defaults,
+/// while those set to `null` are hidden
+/// Colors are not here but in `theme` of config
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[serde(default, deny_unknown_fields)]
 pub struct WidgetPreferences {
     /// if option is NONE, music pane will not be shown in ui
     pub music_pane: Option<PanePreferences>,
@@ -17,5 +25,18 @@ pub struct WidgetPreferences {
     pub pane_tab: Option<PaneTabPreferences>,
     pub shortcuts: Option<ShortcutPreferences>,
     pub searchbar: Option<SearchbarPreferences>,
-    pub theme: Theme,
+}
+
+/// Every gadget shown with it's default options
+impl Default for WidgetPreferences {
+    fn default() -> Self {
+        WidgetPreferences {
+            music_pane: Some(PanePreferences::default()),
+            artist_pane: Some(PanePreferences::default()),
+            playlist_pane: Some(PanePreferences::default()),
+            pane_tab: Some(PaneTabPreferences::default()),
+            shortcuts: Some(ShortcutPreferences::default()),
+            searchbar: Some(SearchbarPreferences::default()),
+        }
+    }
 }
@@ -1,3 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
 pub struct PanePreferences {
     /// How many number of list item to show
     /// when we are fetching result from local source
@@ -6,3 +11,12 @@ pub struct PanePreferences {
     /// when fetching result from remote source
     pub remote_result_count: u8,
 }
+
+impl Default for PanePreferences {
+    fn default() -> Self {
+        PanePreferences {
+            local_result_count: 100,
+            remote_result_count: 20,
+        }
+    }
+}
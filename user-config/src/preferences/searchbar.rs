@@ -1,3 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
 pub struct SearchbarPreferences {
+    /// Shown at cursor when it is past the end of text
     pub cursor: char,
 }
+
+impl Default for SearchbarPreferences {
+    fn default() -> Self {
+        SearchbarPreferences { cursor: ' ' }
+    }
+}
@@ -1,4 +1,9 @@
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Views that app provides on it's own
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub enum Shortcut {
     Trending,
     YoutubeCommunity,
@@ -25,6 +30,66 @@ impl From<Shortcut> for &'static str {
     }
 }
 
+/// What is done when entry of sidebar is executed
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ShortcutAction {
+    // search as if typed in searchbar, filters and all
+    Search(String),
+    // tracks of playlist, given by it's url or id
+    Playlist(String),
+    // music files in this directory
+    LocalDir(PathBuf),
+    // one of the built-in views
+    View(Shortcut),
+}
+
+/// Entry of sidebar
+///
+/// Eg: `{ "label": "Chill", "icon": "~", "action": { "search": "lofi duration:>10m" } }`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ShortcutItem {
+    pub label: String,
+    /// Shown before label. Eg: an emoji or a glyph of nerd font
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    pub action: ShortcutAction,
+}
+
+impl From<Shortcut> for ShortcutItem {
+    fn from(shortcut: Shortcut) -> Self {
+        ShortcutItem {
+            label: <&str>::from(shortcut).to_string(),
+            icon: None,
+            action: ShortcutAction::View(shortcut),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
 pub struct ShortcutPreferences {
-    pub items: Vec<Shortcut>,
+    /// Entries of sidebar, top to bottom
+    pub items: Vec<ShortcutItem>,
+}
+
+impl Default for ShortcutPreferences {
+    fn default() -> Self {
+        ShortcutPreferences {
+            items: [
+                Shortcut::Trending,
+                Shortcut::YoutubeCommunity,
+                Shortcut::LikedSongs,
+                Shortcut::MyPlaylist,
+                Shortcut::Downloaded,
+                Shortcut::FollowingArtist,
+                Shortcut::Local,
+                Shortcut::Search,
+            ]
+            .into_iter()
+            .map(ShortcutItem::from)
+            .collect(),
+        }
+    }
 }